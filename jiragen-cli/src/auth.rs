@@ -0,0 +1,85 @@
+use jiragen::{AuthType, ClientOptions, Config, CustomError, Error, OAuthConfig};
+use std::fs;
+use std::path::PathBuf;
+
+/// Processes the `auth login` SubCommand.
+/// Authorizes JiraGen with JIRA Cloud through the OAuth 2.0 app, and stores the app settings and
+/// the refresh token for use with `--auth-type oauth`. When `site` is set, the token is for that
+/// site; otherwise it is for the first site the user grants access to. The authorization server is
+/// sent requests with the same client options as JIRA, e.g. `--proxy`.
+pub fn login(
+    client_id: String,
+    client_secret: String,
+    port: u16,
+    site: String,
+    options: &ClientOptions,
+) -> Result<(), Error> {
+    let oauth = OAuthConfig {
+        redirect_port: port,
+        ..OAuthConfig::new(
+            &client_id,
+            &client_secret,
+            config_dir()?.join("oauth-token.json"),
+        )
+    };
+
+    let site = Some(site.as_str()).filter(|s| !s.is_empty());
+    oauth.login(options, site, |url| {
+        println!(
+            "Open this URL in a browser to authorize JiraGen:\n\n{}\n\nWaiting for the redirect to {} ...",
+            url,
+            oauth.redirect_uri()
+        );
+    })?;
+
+    let config_path = oauth_config_path()?;
+    oauth.save(&config_path)?;
+    println!("Authorized. Wrote: {:?}", config_path.as_os_str());
+
+    Ok(())
+}
+
+/// Adds the OAuth 2.0 app settings stored by `auth login` to a config that uses `OAuth` auth.
+pub fn with_oauth(conf: Config) -> Result<Config, Error> {
+    if conf.auth != AuthType::OAuth {
+        return Ok(conf);
+    }
+
+    Ok(Config {
+        oauth: Some(load_oauth_config()?),
+        ..conf
+    })
+}
+
+fn load_oauth_config() -> Result<OAuthConfig, Error> {
+    let config_path = oauth_config_path()?;
+    let contents = fs::read_to_string(&config_path).map_err(|_| {
+        Error::CustomError(CustomError {
+            message: "JiraGen is not authorized with OAuth".to_string(),
+            details: format!(
+                "{:?} does not exist. Run `jiragen auth login` first.",
+                config_path.as_os_str()
+            ),
+        })
+    })?;
+
+    Ok(serde_json::from_str(&contents)?)
+}
+
+fn oauth_config_path() -> Result<PathBuf, Error> {
+    Ok(config_dir()?.join("oauth.json"))
+}
+
+fn config_dir() -> Result<PathBuf, Error> {
+    let dir = dirs::config_dir()
+        .ok_or_else(|| {
+            Error::CustomError(CustomError {
+                message: "Could not find the user's config directory".to_string(),
+                details: String::new(),
+            })
+        })?
+        .join("jiragen");
+    fs::create_dir_all(&dir)?;
+
+    Ok(dir)
+}
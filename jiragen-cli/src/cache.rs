@@ -0,0 +1,24 @@
+use jiragen::{CacheOptions, Error};
+use std::env;
+use std::path::PathBuf;
+
+/// The directory JIRA's metadata is cached in: `--cache-dir`, otherwise `JIRAGEN_CACHE_DIR`,
+/// otherwise `jiragen` in the user's cache directory.
+pub fn dir(cache_dir: Option<PathBuf>) -> Option<PathBuf> {
+    cache_dir
+        .or_else(|| env::var_os("JIRAGEN_CACHE_DIR").map(PathBuf::from))
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .or_else(|| dirs::cache_dir().map(|dir| dir.join("jiragen")))
+}
+
+/// Processes the `cache clear` SubCommand.
+/// Removes every cached response, of every JIRA URL.
+pub fn clear(options: &CacheOptions) -> Result<(), Error> {
+    options.clear()?;
+    match &options.dir {
+        Some(dir) => println!("Cleared the cache in {}", dir.display()),
+        None => println!("There is no cache directory to clear"),
+    }
+
+    Ok(())
+}
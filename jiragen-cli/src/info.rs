@@ -2,39 +2,28 @@ use comfy_table::modifiers::UTF8_ROUND_CORNERS;
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{ContentArrangement, Table};
 use itertools::izip;
-use jiragen::{Config, CustomError, Error, JiraClient};
-use serde_json::Value;
-
-/// Get raw JSON from JIRA about a project to gather identifier info.
-pub fn get(conf: Config, project: String) -> Result<(), jiragen::Error> {
-    let jira = JiraClient::new();
-    let url = format!("{}/rest/api/2/project/{}", conf.jira_url, &project);
-
-    let response = jira
-        .client
-        .get(&url)
-        .basic_auth(conf.jira_user.as_str(), Some(conf.jira_key.as_str()))
-        .send()?;
-
-    if !&response.status().is_success() {
-        return Err(Error::CustomError(CustomError {
-            message: format!(
-                "JIRA responded with status {}:",
-                &response.status().as_str()
-            ),
-            details: response.text()?,
-        }));
-    }
+use jiragen::{Error, JiraApi, Project};
+
+/// Get info from JIRA about a project to gather identifier info, and about the JIRA deployment
+/// that was detected.
+pub fn get(jira: &impl JiraApi, project: String) -> Result<(), jiragen::Error> {
+    let table = create_project_table(jira.get_project(&project)?);
 
-    let v: Value = serde_json::from_str(response.text()?.as_str())?;
-    let table = create_project_table(v);
+    match jira.server_info() {
+        Ok(info) => println!(
+            "JIRA {} {} at {}\n",
+            info.deployment_type, info.version, info.base_url
+        ),
+        Err(Error::Api { .. }) => println!("JIRA did not report its deployment or version.\n"),
+        Err(e) => return Err(e),
+    }
 
     println!("Project {}:\n\n{}", project, table);
 
     Ok(())
 }
 
-fn create_project_table(data: Value) -> Table {
+fn create_project_table(data: Project) -> Table {
     let mut table = Table::new();
 
     table
@@ -43,39 +32,20 @@ fn create_project_table(data: Value) -> Table {
         .set_content_arrangement(ContentArrangement::Dynamic)
         .set_header(vec!["Components", "Issue Type", "Roles"]);
 
-    let components_col: Vec<_> = data["components"]
-        .as_array()
-        .unwrap()
-        .into_iter()
-        .map(|comp| {
-            format!(
-                "Name: {}\nID: {}",
-                comp["name"].as_str().unwrap(),
-                comp["id"].as_str().unwrap()
-            )
-        })
-        .collect();
-
-    let issue_types_col: Vec<_> = data["issueTypes"]
-        .as_array()
-        .unwrap()
-        .into_iter()
-        .map(|iss| {
-            format!(
-                "Name: {}\nID: {}",
-                iss["name"].as_str().unwrap(),
-                iss["id"].as_str().unwrap()
-            )
-        })
+    let components_col: Vec<_> = data
+        .components
+        .iter()
+        .map(|comp| format!("Name: {}\nID: {}", comp.name, comp.id))
         .collect();
 
-    let roles_col: Vec<_> = data["roles"]
-        .as_object()
-        .unwrap()
+    let issue_types_col: Vec<_> = data
+        .issue_types
         .iter()
-        .map(|(k, _v)| k.to_owned())
+        .map(|iss| format!("Name: {}\nID: {}", iss.name, iss.id))
         .collect();
 
+    let roles_col: Vec<_> = data.roles.into_keys().collect();
+
     let rows = rows(components_col, issue_types_col, roles_col);
     for row in rows {
         table.add_row(row);
@@ -96,3 +66,31 @@ fn rows(mut comps: Vec<String>, mut iss: Vec<String>, mut roles: Vec<String>) ->
     }
     rows
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiragen::FakeJira;
+
+    #[test]
+    fn projects_are_tabled_by_issue_type() {
+        let jira = FakeJira::new();
+        jira.add_project("ABC", &["Task", "Bug"]);
+
+        let table = create_project_table(jira.get_project("ABC").unwrap()).to_string();
+
+        assert!(table.contains("Name: Task"));
+        assert!(table.contains("Name: Bug"));
+        assert!(get(&jira, "ABC".to_string()).is_ok());
+    }
+
+    #[test]
+    fn missing_projects_are_errors() {
+        let jira = FakeJira::new();
+
+        assert!(matches!(
+            get(&jira, "ABC".to_string()),
+            Err(Error::Api { status: 404, .. })
+        ));
+    }
+}
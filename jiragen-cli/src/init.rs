@@ -5,7 +5,7 @@ use std::path::PathBuf;
 /// Creates the config file as well as the issues template file.
 pub fn create_file_templates(issues_path: PathBuf) -> Result<(), Error> {
     let mut csv_writer = csv::Writer::from_path(&issues_path)?;
-    csv_writer.write_record(&[
+    csv_writer.write_record([
         "project.key",
         "summary",
         "description",
@@ -13,7 +13,7 @@ pub fn create_file_templates(issues_path: PathBuf) -> Result<(), Error> {
         "labels[]",
         "assignee.name",
     ])?;
-    csv_writer.write_record(&[
+    csv_writer.write_record([
         "Project",
         "Summary",
         "Description",
@@ -0,0 +1,27 @@
+use jiragen::{lint, CustomError, Error, Severity};
+use std::fs::File;
+use std::path::PathBuf;
+
+/// Processes the `lint` SubCommand.
+/// Checks the issues template without contacting JIRA, printing one JSON object per problem found.
+/// Fails when any problem has the `error` severity.
+pub fn check_template(issues_path: PathBuf) -> Result<(), Error> {
+    let lints = lint(File::open(&issues_path)?)?;
+
+    for l in lints.iter() {
+        println!("{}", serde_json::to_string(l)?);
+    }
+
+    let errors = lints
+        .iter()
+        .filter(|l| l.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(Error::CustomError(CustomError {
+            message: format!("{:?} has {} lint errors", issues_path.as_os_str(), errors),
+            details: format!("{} warnings", lints.len() - errors),
+        }));
+    }
+
+    Ok(())
+}
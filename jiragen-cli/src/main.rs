@@ -96,25 +96,38 @@
 //! # { "fixVersions": [ {"id": "10000"}, {"id": "10001"} ] }
 //! ```
 
+mod auth;
+mod cache;
 mod info;
 mod init;
+mod lint;
 mod push;
 
-use clap::{Parser, Subcommand};
+use auth::{login, with_oauth};
+use clap::builder::RangedU64ValueParser;
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
 use info::get;
 use init::create_file_templates;
-use jiragen::Config;
+use jiragen::{
+    ApiVersion, AuthType, CacheOptions, ClientOptions, Config, Error, JiraClient, RetryPolicy,
+    BULK_CREATE_LIMIT, DEFAULT_CACHE_TTL_SECS,
+};
+use lint::check_template;
 use push::create_tickets;
 use std::env;
+use std::io::{self, IsTerminal};
 use std::path::PathBuf;
+use std::process;
+use tracing_subscriber::EnvFilter;
 
-#[derive(Parser, Debug)]
+#[derive(Parser)]
 #[command(
   name = "JiraGen",
   about = r#"
 A CLI tool to generate JIRA issues and place them on a board. The `--key` or `-k` option is used
 for setting the JIRA API key, it is hidden to prevent displaying the environment variable
-`JIRA_KEY`.
+`JIRA_KEY`. The same goes for `--token`, the personal access token used for `bearer` auth, which
+defaults from `JIRA_TOKEN`.
   "#,
   version,
   long_about = None,
@@ -132,6 +145,89 @@ struct CliArgs {
     #[clap(short, long, hide = true, default_value_t = default_env("JIRA_KEY"))]
     key: String,
 
+    /// Sets the personal access token used for `bearer` auth
+    #[clap(long, hide = true, default_value_t = default_env("JIRA_TOKEN"))]
+    token: String,
+
+    /// Sets how requests are authenticated (defaults to `bearer` when a token is set, otherwise
+    /// `basic`)
+    #[arg(long, value_enum)]
+    auth_type: Option<AuthArg>,
+
+    /// Sets the version of JIRA's REST API to use; with `3` (JIRA Cloud), descriptions and comments
+    /// are converted from plain text or Markdown to the Atlassian Document Format
+    #[arg(long, value_enum, default_value = "2")]
+    api_version: ApiArg,
+
+    /// Sets the most times a request is sent when JIRA rate limits it or is unavailable; `1`
+    /// disables retries
+    #[arg(
+        long,
+        default_value_t = RetryPolicy::default().max_attempts,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    max_attempts: u32,
+
+    /// Sets how long a request may take, in seconds
+    #[arg(long, value_name = "SECS")]
+    timeout: Option<u64>,
+
+    /// Sets how long connecting to JIRA may take, in seconds
+    #[arg(long, value_name = "SECS")]
+    connect_timeout: Option<u64>,
+
+    /// Sends every request through a proxy, e.g. `http://proxy.corp.net:3128`
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Trusts the CA certificates of a PEM file, in addition to the system's
+    #[arg(long, value_name = "PATH")]
+    ca_cert: Option<PathBuf>,
+
+    /// Authenticates with the client certificate of a PEM file (mutual TLS); requires
+    /// `--client-key`
+    #[arg(long, value_name = "PATH", requires = "client_key")]
+    client_cert: Option<PathBuf>,
+
+    /// Sets the PEM file of the client certificate's PKCS #8 private key
+    #[arg(long, value_name = "PATH", requires = "client_cert")]
+    client_key: Option<PathBuf>,
+
+    /// Sends a header with every request, e.g. `--header 'X-Team: platform'`; may be repeated
+    #[arg(long = "header", value_name = "NAME: VALUE", value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+
+    /// Records every request sent to JIRA, and its response, to a JSON file, with credentials
+    /// redacted
+    #[arg(long, value_name = "PATH", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Answers requests with the responses of a file written by `--record`, instead of JIRA
+    #[arg(long, value_name = "PATH")]
+    replay: Option<PathBuf>,
+
+    /// Sets the directory JIRA's fields, create metadata, projects and users are cached in
+    /// (defaults from `JIRAGEN_CACHE_DIR`, otherwise the user's cache directory)
+    #[arg(long, value_name = "PATH")]
+    cache_dir: Option<PathBuf>,
+
+    /// Sets how long cached metadata is used for, in seconds
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_CACHE_TTL_SECS)]
+    cache_ttl: u64,
+
+    /// Requests metadata from JIRA again rather than using the cache, and caches it
+    #[arg(long, conflicts_with = "offline")]
+    refresh: bool,
+
+    /// Uses cached metadata, however old, and sends no requests to JIRA
+    #[arg(long)]
+    offline: bool,
+
+    /// Logs each request sent to JIRA: `-v` its URL, status and duration, `-vv` also its rate
+    /// limit headers, `-vvv` also its JSON bodies. Credentials are never logged
+    #[arg(short, long, action = ArgAction::Count)]
+    verbose: u8,
+
     /// Sets the path to the issues file, represented as a CSV
     #[clap(short, long, default_value_os_t = default_issues())]
     issues: PathBuf,
@@ -141,14 +237,70 @@ struct CliArgs {
     command: CmdProgs,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum AuthArg {
+    /// Basic Authentication with the username and API key
+    Basic,
+    /// A personal access token sent as a bearer token (JIRA Data Center)
+    Bearer,
+    /// A session cookie created by logging in with the username and password (JIRA Server)
+    Cookie,
+    /// An OAuth 2.0 token stored by `jiragen auth login` (JIRA Cloud)
+    Oauth,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ApiArg {
+    /// `/rest/api/2` (JIRA Cloud, Server and Data Center)
+    #[value(name = "2")]
+    V2,
+    /// `/rest/api/3` (JIRA Cloud)
+    #[value(name = "3")]
+    V3,
+}
+
 #[derive(Subcommand, Debug)]
 enum CmdProgs {
+    /// Manage OAuth 2.0 authorization
+    Auth {
+        #[command(subcommand)]
+        command: AuthCmd,
+    },
+    /// Manage the cache of JIRA's metadata
+    Cache {
+        #[command(subcommand)]
+        command: CacheCmd,
+    },
     Init,
     Push {
+        /// A .csv dataset; the issues template is pushed once per row, with the row's columns
+        /// available as `{{column}}` placeholders
+        #[arg(long)]
+        data: Option<PathBuf>,
+
         /// Link all tickets in a `Relates To` relationship
         #[arg(short, long)]
         link: Option<String>,
+
+        /// Print the request that would be sent to JIRA without creating any issues
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip validating the issues against JIRA's create metadata before pushing
+        #[arg(long)]
+        skip_validation: bool,
+
+        /// The most issues to create with each bulk create request
+        #[arg(
+            long,
+            default_value_t = BULK_CREATE_LIMIT,
+            value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        chunk_size: usize,
     },
+    /// Check the issues file for mistakes without contacting JIRA
+    Lint,
+    /// Print the JIRA deployment and version, and a project's components, issue types and roles
     Info {
         /// Project key to query JIRA about project, ex: JRA in a ticket JRA-123
         #[arg(short, long, default_value = "INF")]
@@ -156,23 +308,151 @@ enum CmdProgs {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum CacheCmd {
+    /// Remove every cached response, of every JIRA URL
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+enum AuthCmd {
+    /// Authorize JiraGen with JIRA Cloud in a browser, and store the refresh token
+    Login {
+        /// The client id of the OAuth 2.0 app (defaults from `JIRA_OAUTH_CLIENT_ID`)
+        #[arg(long, default_value_t = default_env("JIRA_OAUTH_CLIENT_ID"))]
+        client_id: String,
+
+        /// The client secret of the OAuth 2.0 app (defaults from `JIRA_OAUTH_CLIENT_SECRET`)
+        #[arg(long, hide = true, default_value_t = default_env("JIRA_OAUTH_CLIENT_SECRET"))]
+        client_secret: String,
+
+        /// The local port of the app's callback URL, `http://localhost:<port>/callback`
+        #[arg(long, default_value_t = 8910)]
+        port: u16,
+    },
+}
+
 fn main() {
     let cli_args = CliArgs::parse();
+
+    // print warnings, e.g. retried requests, and as much about each request as `--verbose` asks
+    // for, unless `RUST_LOG` says otherwise
+    let level = match cli_args.verbose {
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    };
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(format!("warn,jiragen={}", level)));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .init();
+    let auth = match (cli_args.auth_type, cli_args.token.is_empty()) {
+        (Some(AuthArg::Basic), _) | (None, true) => AuthType::Basic,
+        (Some(AuthArg::Bearer), _) | (None, false) => AuthType::Bearer,
+        (Some(AuthArg::Cookie), _) => AuthType::Cookie,
+        (Some(AuthArg::Oauth), _) => AuthType::OAuth,
+    };
+    let jira_key = match (auth, cli_args.token.is_empty()) {
+        (AuthType::Bearer, false) => cli_args.token,
+        _ => cli_args.key,
+    };
     let conf = Config {
         jira_url: cli_args.domain,
         jira_user: cli_args.user,
-        jira_key: cli_args.key,
+        jira_key,
+        auth,
+        api_version: match cli_args.api_version {
+            ApiArg::V2 => ApiVersion::V2,
+            ApiArg::V3 => ApiVersion::V3,
+        },
+        oauth: None,
+        retry: RetryPolicy {
+            max_attempts: cli_args.max_attempts,
+            ..Default::default()
+        },
+        client: ClientOptions {
+            timeout_secs: cli_args.timeout,
+            connect_timeout_secs: cli_args.connect_timeout,
+            proxy: cli_args.proxy,
+            ca_cert: cli_args.ca_cert,
+            client_cert: cli_args.client_cert,
+            client_key: cli_args.client_key,
+            headers: cli_args.headers.into_iter().collect(),
+            record: cli_args.record,
+            replay: cli_args.replay,
+        },
+        cache: CacheOptions {
+            dir: cache::dir(cli_args.cache_dir),
+            ttl_secs: Some(cli_args.cache_ttl),
+            refresh: cli_args.refresh,
+            offline: cli_args.offline,
+        },
     };
 
     let res = match cli_args.command {
+        CmdProgs::Auth {
+            command:
+                AuthCmd::Login {
+                    client_id,
+                    client_secret,
+                    port,
+                },
+        } => login(client_id, client_secret, port, conf.jira_url, &conf.client),
+        CmdProgs::Cache {
+            command: CacheCmd::Clear,
+        } => cache::clear(&conf.cache),
         CmdProgs::Init => create_file_templates(cli_args.issues),
-        CmdProgs::Push { link: l } => create_tickets(conf, cli_args.issues, l),
-        CmdProgs::Info { project: p } => get(conf, p),
+        CmdProgs::Push {
+            data,
+            link: l,
+            dry_run: d,
+            skip_validation,
+            chunk_size,
+        } => with_oauth(conf).and_then(JiraClient::new).and_then(|jira| {
+            create_tickets(
+                &jira,
+                cli_args.issues,
+                data,
+                l,
+                d,
+                skip_validation,
+                chunk_size,
+            )
+        }),
+        CmdProgs::Lint => check_template(cli_args.issues),
+        CmdProgs::Info { project: p } => with_oauth(conf)
+            .and_then(JiraClient::new)
+            .and_then(|jira| get(&jira, p)),
     };
 
-    match res {
-        Ok(_) => (),
-        Err(e) => println!("{:#?}", e),
+    if let Err(e) = res {
+        report(&e);
+        process::exit(1);
+    }
+}
+
+// Prints an error to stderr. JIRA's errors are printed as a list of its messages, with the field
+// each message is about.
+fn report(e: &Error) {
+    match e {
+        Error::Api { request_id, .. } => {
+            eprintln!("{}", e);
+            if let Some(request_id) = request_id {
+                eprintln!("(request id: {})", request_id);
+            }
+        }
+        e => eprintln!("{:#?}", e),
+    }
+}
+
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    match header.split_once(':') {
+        Some((name, value)) => Ok((name.trim().to_string(), value.trim().to_string())),
+        None => Err("expected `NAME: VALUE`".to_string()),
     }
 }
 
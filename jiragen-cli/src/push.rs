@@ -1,92 +1,326 @@
-use csv::{Reader, StringRecord};
-use jiragen::{csv_to_json, Config, CustomError, Error, JiraClient, JiraIssue};
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{ContentArrangement, Table};
+use jiragen::{
+    validate_fields, ApiVersion, CreateMeta, CustomError, Dataset, Error, IssueUpdate, JiraApi,
+    JiraIssue, Template,
+};
 use serde_json::{json, Value};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// Creates issues from a template file in JIRA.
+/// Creates issues from a template file in JIRA. When a dataset file is given, the template is
+/// instantiated and pushed once per dataset row. Unless `skip_validation` is set, every issue is
+/// validated against JIRA's create metadata before any issue is created. Issues are created in
+/// bulk requests of at most `chunk_size` issues.
 pub fn create_tickets(
-    conf: Config,
+    jira: &impl JiraApi,
     issues_path: PathBuf,
+    data_path: Option<PathBuf>,
     link: Option<String>,
+    dry_run: bool,
+    skip_validation: bool,
+    chunk_size: usize,
 ) -> Result<(), Error> {
-    let jira = JiraClient::new();
-    let mut csv_reader = Reader::from_path(&issues_path).unwrap();
-    let ids_record = csv_reader.headers()?.clone();
-    let ids: Vec<&str> = ids_record.iter().collect();
-    let mut csv_records = csv_reader.into_records();
-
-    // skip line 2, which contains human-readable field names
-    csv_records.next();
-
-    // create bulk issues to send starting on line 3
-    let filtered_csv_records: Vec<StringRecord> = csv_records
-        .filter_map(|record_result| match record_result {
-            Ok(result) => Some(result),
-            Err(_) => None,
-        })
-        .collect();
-    let json_values = csv_to_json(ids, filtered_csv_records)?;
-    let link_data = match link {
-        None => None,
-        Some(l) => {
-            let data = format!(
-                r#"
-                {{
-                    "issuelinks":[
-                        {{
-                           "add":{{
-                              "type":{{
-                                 "name":"Relates",
-                                 "outward":"relates to",
-                                 "inward":"relates to"
-                              }},
-                              "outwardIssue":{{
-                                 "key":"{}"
-                              }}
-                           }}
-                        }}
-                    ]
-                }}"#,
-                l
+    let template = Template::from_path(&issues_path)?;
+    let link_data = link_update(link);
+
+    let instances: Vec<(Option<usize>, String, Template)> = match data_path {
+        None => vec![(None, String::new(), template)],
+        Some(path) => Dataset::from_path(path)?
+            .vars()
+            .iter()
+            .enumerate()
+            .map(|(i, vars)| {
+                let values: Vec<String> =
+                    vars.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+                let heading = format!("Data row {} ({}): ", i + 1, values.join(", "));
+                (Some(i + 1), heading, template.instantiate(vars))
+            })
+            .collect(),
+    };
+
+    let mut batches = Vec::new();
+    for (data_row, heading, template) in instances {
+        let template_rows = template.rows.len();
+
+        // expand `@foreach` rows before converting to JSON
+        let template = template.expand()?;
+        let json_values = template.to_json()?;
+
+        // `update.<field>.<operation>` columns are the issue's update, before the link
+        let mut issues = Vec::new();
+        for record_json in json_values {
+            let mut issue = JiraIssue::from_row(record_json)?;
+            if let Some(link_data) = link_data.clone() {
+                issue.add_update(link_data)?;
+            }
+            issues.push(issue);
+        }
+
+        batches.push(Batch {
+            data_row,
+            heading,
+            template_rows,
+            template,
+            issues,
+        });
+    }
+
+    if dry_run {
+        for batch in batches.iter() {
+            // the issues as they would be sent, i.e. with rich text in ADF for API version 3
+            let issues: Vec<JiraIssue> = match jira.api_version() {
+                ApiVersion::V2 => batch.issues.clone(),
+                ApiVersion::V3 => batch.issues.iter().map(JiraIssue::to_adf).collect(),
+            };
+            let requests: Vec<Value> = issues
+                .chunks(chunk_size)
+                .map(|chunk| json!({ "issueUpdates": chunk }))
+                .collect();
+            println!(
+                "{}Dry run: {} issues would be created from {} template rows, in {} requests, before users, epics and rich-text custom fields are adapted to the site's deployment. Requests:\n\n{}\n",
+                batch.heading,
+                batch.issues.len(),
+                batch.template_rows,
+                requests.len(),
+                serde_json::to_string_pretty(&requests)?
             );
-            let link_data: Value = serde_json::from_str(data.as_str())?;
-            Some(link_data)
         }
+        return Ok(());
+    }
+
+    // refer to users and epics as the site's deployment expects, before validating; dry runs
+    // don't, so that they don't contact JIRA
+    for batch in batches.iter_mut() {
+        batch.issues = jira.adapt_issues(&batch.issues)?;
+    }
+
+    if !skip_validation {
+        validate(jira, &issues_path, &batches)?;
+    }
+
+    let mut failures = new_table();
+    let mut failed = 0;
+    let mut total = 0;
+    for batch in batches.iter() {
+        let response = jira
+            .bulk_create_chunked(&batch.issues, chunk_size)
+            .map_err(|e| match e {
+                Error::CustomError(err) => Error::CustomError(CustomError {
+                    message: format!("{}{}", batch.heading, err.message),
+                    ..err
+                }),
+                Error::Api {
+                    status,
+                    mut error_messages,
+                    field_errors,
+                    request_id,
+                } if !batch.heading.is_empty() => {
+                    error_messages.insert(0, batch.heading.trim_end_matches(": ").to_string());
+                    Error::Api {
+                        status,
+                        error_messages,
+                        field_errors,
+                        request_id,
+                    }
+                }
+                e => e,
+            })?;
+
+        let keys: Vec<&str> = response.issues.iter().map(|i| i.key.as_str()).collect();
+        println!(
+            "{}{} of {} issues created: {}",
+            batch.heading,
+            keys.len(),
+            batch.issues.len(),
+            keys.join(", ")
+        );
+
+        for error in response.errors.iter() {
+            let location = row_location(issues_path.as_path(), batch, error.failed_element_number);
+            let messages = &error.element_errors;
+            for message in messages.error_messages.iter() {
+                failures.add_row(vec![location.clone(), String::new(), message.clone()]);
+            }
+            for (field, message) in messages.errors.iter() {
+                failures.add_row(vec![location.clone(), field.clone(), message.clone()]);
+            }
+            if messages.error_messages.is_empty() && messages.errors.is_empty() {
+                let status = format!("JIRA responded with status {}", error.status);
+                failures.add_row(vec![location, String::new(), status]);
+            }
+        }
+        failed += response.errors.len();
+        total += batch.issues.len();
+    }
+
+    if failed == 0 {
+        return Ok(());
+    }
+
+    println!("{}", failures);
+    Err(Error::CustomError(CustomError {
+        message: format!("{} of {} issues could not be created", failed, total),
+        details: "The issues above were not created; the other issues were.".to_string(),
+    }))
+}
+
+// The issues created from one instance of the template.
+struct Batch {
+    data_row: Option<usize>,
+    heading: String,
+    template_rows: usize,
+    template: Template,
+    issues: Vec<JiraIssue>,
+}
+
+// Validates every batch against the create metadata of the projects it uses, and prints a table
+// of the problems found.
+fn validate(jira: &impl JiraApi, issues_path: &Path, batches: &[Batch]) -> Result<(), Error> {
+    let mut projects: Vec<&str> = Vec::new();
+    for batch in batches.iter() {
+        for issue in batch.issues.iter() {
+            let project = &issue.fields["project"];
+            let key_or_id = project["key"]
+                .as_str()
+                .or_else(|| project["id"].as_str())
+                .or_else(|| project.as_str());
+            if let Some(key_or_id) = key_or_id {
+                if !projects.contains(&key_or_id) {
+                    projects.push(key_or_id);
+                }
+            }
+        }
+    }
+
+    let mut createmeta = CreateMeta::default();
+    for project in projects {
+        createmeta
+            .projects
+            .extend(jira.get_createmeta(project)?.projects);
+    }
+
+    let mut table = new_table();
+
+    let mut problems = 0;
+    for batch in batches.iter() {
+        let fields: Vec<Value> = batch.issues.iter().map(|i| i.fields.clone()).collect();
+        for violation in validate_fields(&fields, &createmeta) {
+            let location = row_location(issues_path, batch, violation.index);
+            table.add_row(vec![location, violation.field, violation.problem]);
+            problems += 1;
+        }
+    }
+
+    if problems == 0 {
+        return Ok(());
+    }
+
+    println!("{}", table);
+    Err(Error::CustomError(CustomError {
+        message: format!("{} problems found in the issues template", problems),
+        details:
+            "No issues were created. Fix the problems above, or push with `--skip-validation`."
+                .to_string(),
+    }))
+}
+
+// A table of problems, by the template row and field they are in.
+fn new_table() -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["Row", "Field", "Problem"]);
+    table
+}
+
+// Where the issue at `index` of a batch came from: the line of its template row (and the file, if
+// it was included from another template), and the data row the batch was instantiated with.
+fn row_location(issues_path: &Path, batch: &Batch, index: usize) -> String {
+    let location = match batch.template.rows.get(index) {
+        Some(row) => match &row.file {
+            Some(file) if file != issues_path => format!("{}:{}", file.display(), row.line),
+            _ => format!("line {}", row.line),
+        },
+        None => format!("issue {}", index + 1),
     };
 
-    let issues_to_create: Vec<JiraIssue> = json_values
-        .into_iter()
-        .map(|record_json| JiraIssue {
-            update: link_data.clone(),
-            fields: record_json,
-        })
-        .collect();
-
-    let request_json = json!({ "issueUpdates": issues_to_create });
-    let url = format!("{}/rest/api/2/issue/bulk", &conf.jira_url);
-    let req = jira
-        .client
-        .post(&url)
-        .json(&request_json)
-        .basic_auth(&conf.jira_user, Some(&conf.jira_key))
-        .build()?;
-
-    let response = jira.client.execute(req)?;
-
-    if !&response.status().is_success() {
-        return Err(Error::CustomError(CustomError {
-            message: format!(
-                "JIRA responded with status {}:",
-                &response.status().as_str()
-            ),
-            details: response.text()?,
-        }));
-    }
-
-    println!(
-        "Issues created successfully. Response:\n\n{}",
-        response.text()?
-    );
-
-    Ok(())
+    match batch.data_row {
+        Some(data_row) => format!("data row {}, {}", data_row, location),
+        None => location,
+    }
+}
+
+// The `update` that links each created issue to `link` in a `Relates To` relationship.
+fn link_update(link: Option<String>) -> Option<IssueUpdate> {
+    link.map(|key| IssueUpdate::new().add_link("Relates", &key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiragen::FakeJira;
+    use std::env;
+    use std::fs;
+
+    fn fake() -> FakeJira {
+        let jira = FakeJira::new();
+        jira.add_project("ABC", &["Task"]);
+        jira
+    }
+
+    // Writes an issues template to a file of its own in the temp directory.
+    fn issues_file(name: &str, csv: &str) -> PathBuf {
+        let path =
+            env::temp_dir().join(format!("jiragen-{}-unit-{}.csv", std::process::id(), name));
+        fs::write(&path, csv).unwrap();
+        path
+    }
+
+    fn push(jira: &FakeJira, issues: PathBuf, dry_run: bool) -> Result<(), Error> {
+        create_tickets(jira, issues, None, None, dry_run, false, 50)
+    }
+
+    #[test]
+    fn every_row_is_created() {
+        let jira = fake();
+        let issues = issues_file(
+            "create",
+            "project.key,issuetype.name,summary,labels[]\n\
+             Project,Issue Type,Summary,Labels\n\
+             ABC,Task,Tag the release,release\n\
+             ABC,Task,Publish the crates,\n",
+        );
+
+        push(&jira, issues, false).unwrap();
+
+        let created = jira.issues();
+        assert_eq!(created.len(), 2);
+        assert_eq!(created[0].fields["summary"], "Tag the release");
+        assert_eq!(created[0].fields["labels"], json!(["release"]));
+        assert_eq!(created[1].fields["summary"], "Publish the crates");
+    }
+
+    #[test]
+    fn nothing_is_created_when_validation_fails_or_on_dry_runs() {
+        let jira = fake();
+        let invalid = issues_file(
+            "invalid",
+            "project.key,issuetype.name,summary\n\
+             Project,Issue Type,Summary\n\
+             ABC,Task,Tag the release\n\
+             ABC,Story,Publish the crates\n",
+        );
+        let valid = issues_file(
+            "dry-run",
+            "project.key,issuetype.name,summary\nProject,Issue Type,Summary\nABC,Task,Tag it\n",
+        );
+
+        assert!(push(&jira, invalid, false).is_err());
+        push(&jira, valid, true).unwrap();
+
+        assert!(jira.issues().is_empty());
+    }
 }
@@ -0,0 +1,58 @@
+mod common;
+
+use common::cache_dir;
+use jiragen_testkit::MockJira;
+use std::env;
+use std::process::{Command, Output};
+
+fn jiragen(domain: &str, cache_dir: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_jiragen"))
+        .env_remove("JIRA_TOKEN")
+        .args(["--domain", domain, "--user", "user", "--key", "key"])
+        .args(["--cache-dir", cache_dir])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn info_works_offline_until_the_cache_is_cleared() {
+    let cache_dir = cache_dir();
+    let cache_dir = cache_dir.to_str().unwrap();
+    let jira = MockJira::with_sample_data();
+    let domain = jira.url();
+
+    let online = jiragen(&domain, cache_dir, &["info", "--project", "ABC"]);
+    drop(jira);
+    let offline = jiragen(
+        &domain,
+        cache_dir,
+        &["--offline", "info", "--project", "ABC"],
+    );
+    let cleared = jiragen(&domain, cache_dir, &["cache", "clear"]);
+    let uncached = jiragen(
+        &domain,
+        cache_dir,
+        &["--offline", "info", "--project", "ABC"],
+    );
+
+    assert!(online.status.success(), "{:?}", online);
+    assert!(offline.status.success(), "{:?}", offline);
+    assert_eq!(offline.stdout, online.stdout);
+    assert!(cleared.status.success(), "{:?}", cleared);
+    assert!(!uncached.status.success());
+    let stderr = String::from_utf8_lossy(&uncached.stderr);
+    assert!(stderr.contains("Not cached for offline use"), "{}", stderr);
+}
+
+#[test]
+fn refresh_and_offline_conflict() {
+    let output = jiragen(
+        "http://localhost",
+        "unused",
+        &["--refresh", "--offline", "info"],
+    );
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
@@ -0,0 +1,11 @@
+// Helpers shared by the CLI's tests.
+use std::env;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A cache directory of its own, so that no run reads what another cached.
+pub fn cache_dir() -> PathBuf {
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    let run = RUNS.fetch_add(1, Ordering::SeqCst);
+    env::temp_dir().join(format!("jiragen-{}-cache-{}", std::process::id(), run))
+}
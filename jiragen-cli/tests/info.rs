@@ -0,0 +1,53 @@
+mod common;
+
+use common::cache_dir;
+use jiragen_testkit::MockJira;
+use std::env;
+use std::process::Command;
+
+#[test]
+fn info_prints_the_project() {
+    let jira = MockJira::with_sample_data();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_jiragen"))
+        .env_remove("JIRA_TOKEN")
+        .env("JIRAGEN_CACHE_DIR", cache_dir())
+        .args(["--domain", &jira.url(), "--user", "user", "--key", "key"])
+        .args(["info", "--project", "ABC"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for expected in [
+        "JIRA Cloud 1001.0.0-SNAPSHOT",
+        "Project ABC",
+        "Backend",
+        "Task",
+        "Bug",
+        "Administrators",
+    ] {
+        assert!(stdout.contains(expected), "{}", stdout);
+    }
+}
+
+#[test]
+fn info_reports_unknown_projects() {
+    let jira = MockJira::with_sample_data();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_jiragen"))
+        .env_remove("JIRA_TOKEN")
+        .env("JIRAGEN_CACHE_DIR", cache_dir())
+        .args(["--domain", &jira.url(), "--user", "user", "--key", "key"])
+        .args(["info", "--project", "XYZ"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("No project could be found with key 'XYZ'."),
+        "{}",
+        stderr
+    );
+}
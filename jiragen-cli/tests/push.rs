@@ -0,0 +1,235 @@
+mod common;
+
+use common::cache_dir;
+use jiragen_testkit::MockJira;
+use serde_json::json;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+// Writes an issues template to a file of its own in the temp directory.
+fn issues_file(name: &str, csv: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("jiragen-{}-{}.csv", std::process::id(), name));
+    fs::write(&path, csv).unwrap();
+    path
+}
+
+fn jiragen(jira: &MockJira, issues: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_jiragen"))
+        .env_remove("JIRA_TOKEN")
+        .env("JIRAGEN_CACHE_DIR", cache_dir())
+        .args(["--domain", &jira.url(), "--user", "user", "--key", "key"])
+        .arg("--issues")
+        .arg(issues)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn push_creates_every_issue() {
+    let jira = MockJira::with_sample_data();
+    let issues = issues_file(
+        "push",
+        "project.key,issuetype.name,summary,labels[]\n\
+         Project,Issue Type,Summary,Labels\n\
+         ABC,Task,Write the release notes,release\n\
+         ABC,Bug,Fix the build,\n",
+    );
+
+    let output = jiragen(&jira, &issues, &["push"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("2 of 2 issues created: ABC-1, ABC-2"),
+        "{}",
+        stdout
+    );
+    let created = jira.issues();
+    assert_eq!(created[0].fields["summary"], "Write the release notes");
+    assert_eq!(created[0].fields["labels"][0], "release");
+    assert_eq!(created[1].issue_type, "Bug");
+}
+
+#[test]
+fn push_links_every_issue() {
+    let jira = MockJira::with_sample_data();
+    let epic = issues_file(
+        "link-epic",
+        "project.key,issuetype.name,summary\nProject,Issue Type,Summary\nABC,Task,Release 1.0\n",
+    );
+    let issues = issues_file(
+        "link",
+        "project.key,issuetype.name,summary\n\
+         Project,Issue Type,Summary\n\
+         ABC,Task,Tag the release\n\
+         ABC,Task,Publish the crates\n",
+    );
+
+    assert!(jiragen(&jira, &epic, &["push"]).status.success());
+    let output = jiragen(&jira, &issues, &["push", "--link", "ABC-1"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let links = jira.links();
+    assert_eq!(links.len(), 2);
+    assert!(links
+        .iter()
+        .all(|l| l.link_type == "Relates" && l.outward_issue == "ABC-1"));
+}
+
+#[test]
+fn push_creates_nothing_when_validation_fails() {
+    let jira = MockJira::with_sample_data();
+    let issues = issues_file(
+        "invalid",
+        "project.key,issuetype.name,summary,priority.name\n\
+         Project,Issue Type,Summary,Priority\n\
+         ABC,Task,A valid issue,High\n\
+         ABC,Task,,Urgent\n",
+    );
+
+    let output = jiragen(&jira, &issues, &["push"]);
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("line 4"), "{}", stdout);
+    assert!(stdout.contains("priority"), "{}", stdout);
+    assert!(jira.issues().is_empty());
+}
+
+#[test]
+fn push_reports_the_issues_jira_rejects() {
+    let jira = MockJira::with_sample_data();
+    let issues = issues_file(
+        "rejected",
+        "project.key,issuetype.name,summary\n\
+         Project,Issue Type,Summary\n\
+         ABC,Task,Created\n\
+         ABC,Task,\n",
+    );
+
+    let output = jiragen(&jira, &issues, &["push", "--skip-validation"]);
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("1 of 2 issues created: ABC-1"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("Summary is required."), "{}", stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("1 of 2 issues could not be created"),
+        "{}",
+        stderr
+    );
+    assert_eq!(jira.issues().len(), 1);
+}
+
+#[test]
+fn dry_run_creates_nothing() {
+    let jira = MockJira::with_sample_data();
+    // dry runs don't contact JIRA, even to adapt users and epics
+    jira.require_credentials("someone", "else");
+    let issues = issues_file(
+        "dry-run",
+        "project.key,issuetype.name,summary,assignee.name,epic\n\
+         Project,Issue Type,Summary,Assignee,Epic\n\
+         ABC,Task,Not yet,jane@example.com,ABC-1\n",
+    );
+
+    let output = jiragen(&jira, &issues, &["push", "--dry-run"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\"summary\": \"Not yet\""), "{}", stdout);
+    assert!(stdout.contains("\"epic\": \"ABC-1\""), "{}", stdout);
+    assert!(jira.issues().is_empty());
+}
+
+#[test]
+fn push_sends_descriptions_as_adf_with_api_version_3() {
+    let jira = MockJira::with_sample_data();
+    let issues = issues_file(
+        "api-v3",
+        "project.key,issuetype.name,summary,description\n\
+         Project,Issue Type,Summary,Description\n\
+         ABC,Task,Release,\"Steps:\n- tag **1.0**\n- publish\"\n",
+    );
+
+    let output = jiragen(&jira, &issues, &["--api-version", "3", "push"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let description = &jira.issues()[0].fields["description"];
+    assert_eq!(description["type"], "doc");
+    assert_eq!(description["content"][1]["type"], "bulletList");
+}
+
+#[test]
+fn push_adapts_assignees_and_epics_to_the_deployment() {
+    let deployments = [
+        // Cloud's users are found by email address, and Server's by name
+        (
+            MockJira::with_sample_data(),
+            "jane@example.com",
+            json!({ "accountId": "5b10a2844c20165700ede21g" }),
+            "parent",
+            json!({ "key": "ABC-1" }),
+        ),
+        (
+            MockJira::with_sample_server_data(),
+            "jane",
+            json!({ "name": "jane" }),
+            "customfield_10014",
+            json!("ABC-1"),
+        ),
+    ];
+
+    for (jira, user, assignee, epic_field, epic) in deployments {
+        let issues = issues_file(
+            "deployment",
+            &format!(
+                "project.key,issuetype.name,summary,assignee.name,epic\n\
+                 Project,Issue Type,Summary,Assignee,Epic\n\
+                 ABC,Epic,Release,{0},\n\
+                 ABC,Task,Tag it,{0},ABC-1\n",
+                user
+            ),
+        );
+
+        let output = jiragen(&jira, &issues, &["push", "--chunk-size", "1"]);
+
+        assert!(output.status.success(), "{:?}", output);
+        let task = &jira.issues()[1];
+        assert_eq!(task.fields["assignee"], assignee);
+        assert_eq!(task.fields[epic_field], epic);
+        assert!(!task.fields.contains_key("epic"));
+    }
+}
+
+#[test]
+fn push_applies_update_columns() {
+    let jira = MockJira::with_sample_data();
+    let issues = issues_file(
+        "update",
+        "project.key,issuetype.name,summary,update.labels.add[],update.labels.add[],update.comment.add.body\n\
+         Project,Issue Type,Summary,Label,Label,Comment\n\
+         ABC,Task,Tag the release,release,docs,Created by the release template\n\
+         ABC,Task,Publish the crates,,,\n",
+    );
+
+    let output = jiragen(&jira, &issues, &["push"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let created = jira.issues();
+    assert_eq!(created[0].fields["labels"], json!(["release", "docs"]));
+    assert_eq!(
+        created[0].fields["comment"]["comments"][0]["body"],
+        "Created by the release template"
+    );
+    assert!(!created[1].fields.contains_key("labels"));
+    assert!(!created[1].fields.contains_key("update"));
+}
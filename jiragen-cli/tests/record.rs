@@ -0,0 +1,46 @@
+mod common;
+
+use common::cache_dir;
+use jiragen_testkit::MockJira;
+use std::env;
+use std::fs;
+use std::process::{Command, Output};
+
+fn jiragen(domain: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_jiragen"))
+        .env_remove("JIRA_TOKEN")
+        .env("JIRAGEN_CACHE_DIR", cache_dir())
+        .args(["--domain", domain, "--user", "user", "--key", "key"])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn replayed_pushes_match_the_recorded_push() {
+    let dir = env::temp_dir();
+    let issues = dir.join(format!("jiragen-{}-record.csv", std::process::id()));
+    let recording = dir.join(format!("jiragen-{}-record.json", std::process::id()));
+    fs::write(
+        &issues,
+        "project.key,issuetype.name,summary\n\
+         Project,Issue Type,Summary\n\
+         ABC,Task,Recorded\n\
+         ABC,Task,\n",
+    )
+    .unwrap();
+    let (issues, recording) = (issues.to_str().unwrap(), recording.to_str().unwrap());
+
+    let jira = MockJira::with_sample_data();
+    let recorded = jiragen(
+        &jira.url(),
+        &["--record", recording, "--issues", issues, "push"],
+    );
+    drop(jira);
+    let replayed = jiragen("", &["--replay", recording, "--issues", issues, "push"]);
+
+    assert!(String::from_utf8_lossy(&recorded.stdout).contains("line 4"));
+    assert_eq!(replayed.status.code(), recorded.status.code());
+    assert_eq!(replayed.stdout, recorded.stdout);
+    assert_eq!(replayed.stderr, recorded.stderr);
+}
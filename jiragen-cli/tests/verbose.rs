@@ -0,0 +1,83 @@
+mod common;
+
+use common::cache_dir;
+use jiragen_testkit::MockJira;
+use std::env;
+use std::fs;
+use std::process::{Command, Output};
+
+fn push(jira: &MockJira, args: &[&str]) -> Output {
+    let issues = env::temp_dir().join(format!("jiragen-{}-verbose.csv", std::process::id()));
+    fs::write(
+        &issues,
+        "project.key,issuetype.name,summary\nProject,Issue Type,Summary\nABC,Task,Traced\n",
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_jiragen"))
+        .env_remove("JIRA_TOKEN")
+        .env("JIRAGEN_CACHE_DIR", cache_dir())
+        .env_remove("RUST_LOG")
+        .args([
+            "--domain",
+            &jira.url(),
+            "--user",
+            "user",
+            "--key",
+            "s3cr3t-key",
+        ])
+        .arg("--issues")
+        .arg(&issues)
+        .args(args)
+        .arg("push")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn requests_are_only_logged_when_verbose() {
+    let jira = MockJira::with_sample_data();
+
+    let output = push(&jira, &[]);
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stderr.is_empty(), "{:?}", output);
+}
+
+#[test]
+fn verbosity_adds_headers_then_bodies() {
+    let jira = MockJira::with_sample_data();
+
+    let info = String::from_utf8(push(&jira, &["-v"]).stderr).unwrap();
+    let debug = String::from_utf8(push(&jira, &["-vv"]).stderr).unwrap();
+    let trace = String::from_utf8(push(&jira, &["-vvv"]).stderr).unwrap();
+
+    assert!(info.contains("jira_request"), "{}", info);
+    assert!(info.contains("path=/rest/api/2/issue/bulk"), "{}", info);
+    assert!(info.contains("status=201 Created"), "{}", info);
+    assert!(!info.contains("request_id"), "{}", info);
+    assert!(debug.contains("request_id=mock-request"), "{}", debug);
+    assert!(!debug.contains("\"Traced\""), "{}", debug);
+    assert!(trace.contains("\"summary\": \"Traced\""), "{}", trace);
+}
+
+#[test]
+fn credentials_are_never_logged() {
+    let jira = MockJira::with_sample_data();
+    jira.require_credentials("user", "s3cr3t-key");
+
+    for auth_type in ["basic", "cookie"] {
+        let output = push(&jira, &["-vvv", "--auth-type", auth_type]);
+
+        assert!(output.status.success(), "{:?}", output);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("response body"), "{}", stderr);
+        assert!(!stderr.contains("s3cr3t-key"), "{}", stderr);
+        assert!(!stderr.contains("mock-session"), "{}", stderr);
+        assert!(
+            !stderr.to_lowercase().contains("authorization"),
+            "{}",
+            stderr
+        );
+    }
+}
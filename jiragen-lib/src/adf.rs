@@ -0,0 +1,264 @@
+//! Conversion between text and the [Atlassian Document Format](https://developer.atlassian.com/cloud/jira/platform/apis/document/structure/)
+//! (ADF), in which version 3 of JIRA's REST API sends and expects rich-text fields, such as an
+//! issue's description and comments.
+//!
+//! [`from_markdown`](fn.from_markdown.html) converts plain text, or the common subset of
+//! Markdown: paragraphs, `#` headings, `-`/`*` and `1.` lists, `>` quotes, fenced code blocks,
+//! `---` rules, and `**strong**`, `*emphasis*`, `~~strikethrough~~`, `` `code` `` and
+//! `[links](https://example.com)` within a line. Line breaks within a paragraph are kept.
+//! ```
+//! use jiragen::adf;
+//! use serde_json::json;
+//!
+//! let doc = adf::from_markdown("Ship **1.0**\n\n- tag it");
+//!
+//! assert_eq!(doc["content"][0]["content"][1], json!({
+//!   "type": "text",
+//!   "text": "1.0",
+//!   "marks": [{ "type": "strong" }],
+//! }));
+//! assert_eq!(doc["content"][1]["type"], "bulletList");
+//! assert_eq!(adf::to_text(&doc), "Ship 1.0\n\n- tag it");
+//! ```
+use serde_json::{json, Map, Value};
+
+/// Converts plain text or Markdown to an ADF document.
+pub fn from_markdown(markdown: &str) -> Value {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut blocks = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            i += 1;
+        } else if let Some(language) = trimmed.strip_prefix("```") {
+            let start = i + 1;
+            let end = (start..lines.len())
+                .find(|&j| lines[j].trim() == "```")
+                .unwrap_or(lines.len());
+            blocks.push(code_block(language.trim(), &lines[start..end].join("\n")));
+            i = end + 1;
+        } else if let Some((level, text)) = heading(trimmed) {
+            blocks.push(json!({
+                "type": "heading",
+                "attrs": { "level": level },
+                "content": inline(text),
+            }));
+            i += 1;
+        } else if trimmed == "---" || trimmed == "***" {
+            blocks.push(json!({ "type": "rule" }));
+            i += 1;
+        } else if let Some((ordered, _)) = list_item(trimmed) {
+            let mut items = Vec::new();
+            while let Some((item_ordered, text)) = lines.get(i).and_then(|l| list_item(l.trim())) {
+                if item_ordered != ordered {
+                    break;
+                }
+                items.push(json!({ "type": "listItem", "content": [paragraph(&[text])] }));
+                i += 1;
+            }
+            let list_type = match ordered {
+                true => "orderedList",
+                false => "bulletList",
+            };
+            blocks.push(json!({ "type": list_type, "content": items }));
+        } else if trimmed.starts_with('>') {
+            let mut quoted = Vec::new();
+            while let Some(text) = lines.get(i).and_then(|l| l.trim().strip_prefix('>')) {
+                quoted.push(text.trim());
+                i += 1;
+            }
+            blocks.push(json!({ "type": "blockquote", "content": [paragraph(&quoted)] }));
+        } else {
+            let mut paragraph_lines = Vec::new();
+            while let Some(line) = lines.get(i).map(|l| l.trim()) {
+                let starts_block = line.is_empty()
+                    || line.starts_with("```")
+                    || line.starts_with('>')
+                    || heading(line).is_some()
+                    || list_item(line).is_some();
+                if starts_block && !paragraph_lines.is_empty() {
+                    break;
+                }
+                paragraph_lines.push(line);
+                i += 1;
+            }
+            blocks.push(paragraph(&paragraph_lines));
+        }
+    }
+
+    json!({ "type": "doc", "version": 1, "content": blocks })
+}
+
+/// Whether a value is an ADF document.
+pub fn is_document(value: &Value) -> bool {
+    value["type"] == "doc" && value["content"].is_array()
+}
+
+/// The text of an ADF document, with blocks separated by blank lines and list items prefixed with
+/// `- `. Values that aren't ADF documents, e.g. the plain strings of API version 2, are returned
+/// as is if they are strings.
+pub fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value if is_document(value) => blocks_text(&value["content"]),
+        _ => String::new(),
+    }
+}
+
+fn blocks_text(blocks: &Value) -> String {
+    let blocks = blocks.as_array().map(Vec::as_slice).unwrap_or_default();
+    let texts: Vec<String> = blocks.iter().map(block_text).collect();
+    texts.join("\n\n")
+}
+
+fn block_text(block: &Value) -> String {
+    let children = block["content"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    match block["type"].as_str().unwrap_or_default() {
+        "text" => block["text"].as_str().unwrap_or_default().to_string(),
+        "hardBreak" => "\n".to_string(),
+        "rule" => "---".to_string(),
+        "bulletList" | "orderedList" => {
+            let items: Vec<String> = children
+                .iter()
+                .map(|item| format!("- {}", blocks_text(&item["content"])))
+                .collect();
+            items.join("\n")
+        }
+        "blockquote" | "listItem" => blocks_text(&block["content"]),
+        _ => children.iter().map(block_text).collect(),
+    }
+}
+
+fn code_block(language: &str, code: &str) -> Value {
+    let mut block = json!({ "type": "codeBlock", "content": [] });
+    if !language.is_empty() {
+        block["attrs"] = json!({ "language": language });
+    }
+    if !code.is_empty() {
+        block["content"] = json!([{ "type": "text", "text": code }]);
+    }
+    block
+}
+
+// The level and text of a `#` heading.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    match level {
+        1..=6 => Some((level, text.trim())),
+        _ => None,
+    }
+}
+
+// Whether a list item is ordered, and its text.
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    if let Some(text) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some((false, text.trim()));
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    match digits {
+        0 => None,
+        _ => line[digits..]
+            .strip_prefix(". ")
+            .map(|text| (true, text.trim())),
+    }
+}
+
+// A paragraph of lines separated by hard breaks.
+fn paragraph(lines: &[&str]) -> Value {
+    let mut content = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            content.push(json!({ "type": "hardBreak" }));
+        }
+        content.extend(inline(line));
+    }
+    json!({ "type": "paragraph", "content": content })
+}
+
+// The text nodes of a line, with the marks of its inline formatting.
+fn inline(text: &str) -> Vec<Value> {
+    let mut nodes = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        // `_` within a word, e.g. `snake_case`, isn't emphasis
+        let within_word = c == '_' && plain.chars().last().is_some_and(char::is_alphanumeric);
+        let formatted = match c {
+            '*' | '_' | '~' | '`' if !within_word => emphasis(rest),
+            '[' => link(rest),
+            _ => None,
+        };
+        match formatted {
+            Some((node, len)) => {
+                if !plain.is_empty() {
+                    nodes.push(text_node(&std::mem::take(&mut plain), Vec::new()));
+                }
+                nodes.push(node);
+                rest = &rest[len..];
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        nodes.push(text_node(&plain, Vec::new()));
+    }
+
+    nodes
+}
+
+// `**strong**`, `__strong__`, `*em*`, `_em_`, `~~strike~~` or `` `code` `` at the start of `text`,
+// and its length.
+fn emphasis(text: &str) -> Option<(Value, usize)> {
+    let (delimiter, mark) = [
+        ("**", "strong"),
+        ("__", "strong"),
+        ("~~", "strike"),
+        ("*", "em"),
+        ("_", "em"),
+        ("`", "code"),
+    ]
+    .into_iter()
+    .find(|(delimiter, _)| text.starts_with(delimiter))?;
+
+    let inner = &text[delimiter.len()..];
+    if inner.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let end = inner.find(delimiter).filter(|&end| end > 0)?;
+    let node = text_node(&inner[..end], vec![json!({ "type": mark })]);
+    Some((node, delimiter.len() * 2 + end))
+}
+
+// `[text](url)` at the start of `text`, and its length.
+fn link(text: &str) -> Option<(Value, usize)> {
+    let close = text.find("](")?;
+    let end = close + 2 + text[close + 2..].find(')')?;
+    let href = &text[close + 2..end];
+    let node = text_node(
+        &text[1..close],
+        vec![json!({ "type": "link", "attrs": { "href": href } })],
+    );
+    Some((node, end + 1))
+}
+
+fn text_node(text: &str, marks: Vec<Value>) -> Value {
+    let mut node = Map::new();
+    node.insert("type".to_string(), json!("text"));
+    node.insert("text".to_string(), json!(text));
+    if !marks.is_empty() {
+        node.insert("marks".to_string(), json!(marks));
+    }
+    Value::Object(node)
+}
@@ -1,36 +1,378 @@
 /// This file contains functionality specific to interacting with the JIRA API.
-use reqwest::blocking::Client;
-use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE};
-use serde::Serialize;
-use serde_json::Value;
+use crate::client::{self, Authentication, ClientState};
+use crate::config::Config;
+use crate::deployment::{self, Adapter};
+use crate::endpoints::{self, ApiRequest, RawResponse};
+use crate::error::{CustomError, Error};
+use crate::models::{
+    BulkCreateResponse, CreateMeta, CreatedIssue, DeploymentType, Field, Issue, JiraIssue, Project,
+    SearchPage, SearchResults, ServerInfo, User,
+};
+use crate::retry::Attempts;
+use crate::search::{SearchIter, SearchOptions};
+use crate::transport::Transport;
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::thread;
+
+/// The most issues JIRA creates with one request to the bulk issue creation endpoint.
+pub const BULK_CREATE_LIMIT: usize = 50;
 
 /// A `JiraClient` instance handles requests sent to JIRA. An instance is created via
 /// `JiraClient::new(`[`Config`](struct.Config.html)`)`, and then that instance can then be used
-/// for creating requests to JIRA, via `.init_request()` (which creates authorization headers using
-/// the `Config` username/key).
-#[derive(Debug)]
+/// for creating requests to JIRA, either with its typed methods or via `.init_request()` (which
+/// authenticates using the `Config` username/key and [`AuthType`](enum.AuthType.html)).
 pub struct JiraClient {
-    pub client: Client,
+    client: Client,
+    state: ClientState,
+}
+
+// The client and `Config` (with the API key redacted); sessions and tokens are left out.
+impl fmt::Debug for JiraClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JiraClient")
+            .field("client", &self.client)
+            .field("config", &self.state.config)
+            .finish_non_exhaustive()
+    }
 }
 
 impl JiraClient {
-    /// Creates a new `reqwest` client and returns the `JiraClient` struct wrapper.
-    pub fn new() -> Self {
-        let mut headers = HeaderMap::new();
-        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    /// Creates a new `reqwest` client with the `Config`'s [`ClientOptions`](struct.ClientOptions.html)
+    /// and returns the `JiraClient` struct wrapper, or the error of an invalid option.
+    pub fn new(config: Config) -> Result<Self, Error> {
+        let client = Transport::new(&config.client)?.blocking_client()?;
+        let state = ClientState::new(config)?;
 
-        let client = Client::builder().default_headers(headers).build().unwrap();
+        Ok(Self { client, state })
+    }
 
-        Self { client }
+    /// The configuration the client sends requests with.
+    pub fn config(&self) -> &Config {
+        &self.state.config
     }
-}
 
-#[derive(Debug, Serialize)]
-/// The object to send to JIRA’s "bulk issue creation" API endpoint
-pub struct JiraIssue {
-    /// not implemented, set as `None`.
-    pub update: Option<Value>,
-    /// A `serde_json` [Value](https://docs.serde.rs/serde_json/enum.Value.html).
-    pub fields: Value,
+    /// Creates a single issue, as it is: [`adapt_issues`](#method.adapt_issues) adapts it to the
+    /// site's deployment first.
+    pub fn create_issue(&self, issue: &JiraIssue) -> Result<CreatedIssue, Error> {
+        self.send(endpoints::create_issue(
+            self.state.config.api_version,
+            issue,
+        ))
+    }
+
+    /// Creates issues with the bulk issue creation endpoint, in requests of at most
+    /// [`BULK_CREATE_LIMIT`](constant.BULK_CREATE_LIMIT.html) issues.
+    pub fn bulk_create(&self, issues: &[JiraIssue]) -> Result<BulkCreateResponse, Error> {
+        self.bulk_create_chunked(issues, BULK_CREATE_LIMIT)
+    }
+
+    /// Creates issues with the bulk issue creation endpoint, sending them in order in requests of
+    /// at most `chunk_size` issues. The responses are aggregated into one, in which the
+    /// `failed_element_number` of each error is the index of the issue in `issues`. The issues are
+    /// sent as they are, like with [`create_issue`](#method.create_issue).
+    pub fn bulk_create_chunked(
+        &self,
+        issues: &[JiraIssue],
+        chunk_size: usize,
+    ) -> Result<BulkCreateResponse, Error> {
+        if chunk_size == 0 {
+            return Err(endpoints::invalid_chunk_size());
+        }
+
+        let mut response = BulkCreateResponse::default();
+        for (i, chunk) in issues.chunks(chunk_size).enumerate() {
+            let offset = i * chunk_size;
+            let chunk_response = self
+                .send_raw(endpoints::bulk_create(self.state.config.api_version, chunk))
+                .and_then(endpoints::bulk_create_response)
+                .map_err(|e| {
+                    endpoints::chunk_error(e, offset, chunk.len(), response.issues.len())
+                })?;
+            endpoints::merge_bulk_response(&mut response, offset, chunk_response);
+        }
+
+        Ok(response)
+    }
+
+    /// Gets a project, including its components, issue types and roles, by key or id.
+    pub fn get_project(&self, project: &str) -> Result<Project, Error> {
+        self.send(endpoints::get_project(
+            self.state.config.api_version,
+            project,
+        ))
+    }
+
+    /// Gets an issue by key or id.
+    pub fn get_issue(&self, issue: &str) -> Result<Issue, Error> {
+        self.send(endpoints::get_issue(self.state.config.api_version, issue))
+    }
+
+    /// Gets one page of the issues matching a JQL query.
+    pub fn search(
+        &self,
+        jql: &str,
+        start_at: u64,
+        max_results: u64,
+    ) -> Result<SearchResults, Error> {
+        self.send(endpoints::search(
+            self.state.config.api_version,
+            jql,
+            start_at,
+            max_results,
+            &SearchOptions::default(),
+        ))
+    }
+
+    /// Gets one page of the issues matching a JQL query, given the `next_page_token` of the page
+    /// before it, or `None` for the first page. JIRA Cloud is searched with its enhanced search
+    /// endpoint, and Server and Data Center with `startAt`, whose tokens are the `startAt` of the
+    /// next page.
+    pub fn search_page(
+        &self,
+        jql: &str,
+        page_token: Option<&str>,
+        max_results: u64,
+    ) -> Result<SearchPage, Error> {
+        let options = SearchOptions {
+            page_size: max_results,
+            ..Default::default()
+        };
+        self.search_page_with(jql, page_token, &options)
+    }
+
+    /// Gets one page of the issues matching a JQL query, like [`search_page`](#method.search_page),
+    /// with the fields, expansions and page size of the options.
+    pub fn search_page_with(
+        &self,
+        jql: &str,
+        page_token: Option<&str>,
+        options: &SearchOptions,
+    ) -> Result<SearchPage, Error> {
+        let deployment = self.deployment()?;
+        let request = endpoints::page_request(
+            self.state.config.api_version,
+            deployment,
+            jql,
+            page_token,
+            options,
+        )?;
+        endpoints::page_response(deployment, self.send_raw(request)?)
+    }
+
+    /// Iterates over every issue matching a JQL query, e.g. a [`Jql`](struct.Jql.html), requesting
+    /// pages as they are needed.
+    /// ```
+    /// # use jiragen::{Config, JiraClient};
+    /// use jiragen::{Jql, Order, SearchOptions};
+    ///
+    /// # let mut server = mockito::Server::new();
+    /// # server.mock("GET", "/rest/api/2/serverInfo").with_status(404).create();
+    /// # let page = |start_at: u64, keys: &[&str]| {
+    /// #   let issues: Vec<_> = keys
+    /// #     .iter()
+    /// #     .map(|k| serde_json::json!({ "id": "1", "key": k, "self": "", "fields": { "summary": k } }))
+    /// #     .collect();
+    /// #   serde_json::json!({ "startAt": start_at, "maxResults": 2, "total": 3, "issues": issues })
+    /// #     .to_string()
+    /// # };
+    /// # for (start_at, keys) in [(0, vec!["REL-1", "REL-2"]), (2, vec!["REL-3"])] {
+    /// #   server
+    /// #     .mock("POST", "/rest/api/2/search")
+    /// #     .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+    /// #       "jql": "project = \"REL\" ORDER BY key ASC",
+    /// #       "startAt": start_at,
+    /// #       "fields": ["summary"],
+    /// #     })))
+    /// #     .with_body(page(start_at, &keys))
+    /// #     .create();
+    /// # }
+    /// # let jira = JiraClient::new(Config { jira_url: server.url(), ..Default::default() })?;
+    /// let jql = Jql::new().project("REL").order_by("key", Order::Asc);
+    /// let options = SearchOptions {
+    ///   fields: vec!["summary".to_string()],
+    ///   page_size: 2,
+    ///   ..Default::default()
+    /// };
+    ///
+    /// let summaries = jira
+    ///   .search_iter(jql, options)
+    ///   .map(|issue| issue.map(|issue| issue.fields["summary"].clone()))
+    ///   .collect::<Result<Vec<_>, _>>()?;
+    ///
+    /// assert_eq!(summaries, ["REL-1", "REL-2", "REL-3"]);
+    /// # Ok::<(), jiragen::Error>(())
+    /// ```
+    pub fn search_iter(&self, jql: impl Into<String>, options: SearchOptions) -> SearchIter<'_> {
+        SearchIter::new(self, jql, options)
+    }
+
+    /// Finds the users whose name or email address match a query.
+    pub fn find_users(&self, query: &str) -> Result<Vec<User>, Error> {
+        let deployment = self.deployment()?;
+        self.send(endpoints::find_users(
+            self.state.config.api_version,
+            deployment,
+            query,
+        ))
+    }
+
+    /// Gets every system and custom field.
+    pub fn get_fields(&self) -> Result<Vec<Field>, Error> {
+        self.send(endpoints::get_fields(self.state.config.api_version))
+    }
+
+    /// Gets the create metadata, including fields, of every issue type in a project, given its key
+    /// or numeric id.
+    pub fn get_createmeta(&self, project: &str) -> Result<CreateMeta, Error> {
+        self.send(endpoints::get_createmeta(
+            self.state.config.api_version,
+            project,
+        ))
+    }
+
+    /// The version and deployment of the JIRA site, requested once and then kept for the life of
+    /// the client.
+    pub fn server_info(&self) -> Result<ServerInfo, Error> {
+        if let Some(info) = self.state.known_server_info() {
+            return Ok(info);
+        }
+
+        let info = self.send(endpoints::get_server_info(self.state.config.api_version))?;
+        Ok(self.state.detected(info))
+    }
+
+    /// The issues as the site's deployment expects them. Users are referred to by account id on
+    /// JIRA Cloud, looking up names and email addresses, and by name on Server and Data Center.
+    /// The `epic` pseudo-field, an epic's key, is replaced with `parent` on Cloud and with the
+    /// Epic Link field on Server. With version 3 of the API, the text of rich-text custom fields,
+    /// i.e. multi-line text fields, is converted to [ADF](adf/index.html). Issues are kept as they
+    /// are when the site doesn't report its deployment.
+    pub fn adapt_issues(&self, issues: &[JiraIssue]) -> Result<Vec<JiraIssue>, Error> {
+        if !deployment::needs_adapting(issues, self.state.config.api_version) {
+            return Ok(issues.to_vec());
+        }
+        let deployment = match self.deployment()? {
+            Some(deployment) => deployment,
+            None => return Ok(issues.to_vec()),
+        };
+
+        let fields = self.site_fields()?;
+        let adapter = Adapter {
+            deployment,
+            api_version: self.state.config.api_version,
+            fields: &fields,
+        };
+        let mut users = BTreeMap::new();
+        for query in adapter.user_queries(issues) {
+            let found = self.find_users(&query)?;
+            users.insert(query, found);
+        }
+
+        Ok(adapter.adapt_all(issues, &users))
+    }
+
+    /// Creates a request to a path of the JIRA API, e.g. `init_request("GET", "/rest/api/2/field")`,
+    /// with the JIRA URL and authorization headers of the client's `Config` already set. With
+    /// `Cookie` auth, the first request logs in to create the session.
+    pub fn init_request(&self, method: &str, path: &str) -> Result<RequestBuilder, Error> {
+        let method = Method::from_bytes(method.to_uppercase().as_bytes()).map_err(|_| {
+            Error::CustomError(CustomError {
+                message: "Invalid HTTP method".to_string(),
+                details: format!("`{}` is not a valid HTTP method", method),
+            })
+        })?;
+
+        self.request(method, path)
+    }
+
+    // Creates an authenticated request to a path of the JIRA API.
+    fn request(&self, method: Method, path: &str) -> Result<RequestBuilder, Error> {
+        let target = match self.state.authentication()? {
+            Authentication::Ready(target) => target,
+            Authentication::Login(login) => {
+                // the login request itself is sent without authentication
+                let builder = self
+                    .client
+                    .request(login.method.clone(), self.state.url(&login.path));
+                let response = self.execute(client::prepare(builder, &login), &login)?;
+                self.state.logged_in(response)?
+            }
+            Authentication::Refresh(oauth, token) => self
+                .state
+                .refreshed(oauth.refresh(&self.state.config.client, &token)?)?,
+        };
+
+        Ok(target.authorize(self.client.request(method, target.url(path))))
+    }
+
+    // The site's deployment, or `None` when JIRA doesn't report it.
+    fn deployment(&self) -> Result<Option<DeploymentType>, Error> {
+        client::deployment(self.server_info())
+    }
+
+    // The site's fields, requested once.
+    fn site_fields(&self) -> Result<Vec<Field>, Error> {
+        match self.state.known_fields() {
+            Some(fields) => Ok(fields),
+            None => Ok(self.state.fields_received(self.get_fields()?)),
+        }
+    }
+
+    // Sends an authenticated request to an endpoint, and deserializes the response.
+    fn send<T: DeserializeOwned>(&self, request: ApiRequest) -> Result<T, Error> {
+        endpoints::parse_response(self.send_raw(request)?)
+    }
+
+    // Sends an authenticated request to an endpoint, and returns the response as is, unless it is
+    // replayed or cached.
+    fn send_raw(&self, request: ApiRequest) -> Result<RawResponse, Error> {
+        if let Some(response) = self.state.stored(&request)? {
+            return Ok(response);
+        }
+
+        let builder = self.request(request.method.clone(), &request.path)?;
+        let mut response = self.execute(client::prepare(builder, &request), &request)?;
+        // a request rejected because the session has expired is sent once more, in a new session
+        if self.state.session_expired(&response) {
+            let builder = self.request(request.method.clone(), &request.path)?;
+            response = self.execute(client::prepare(builder, &request), &request)?;
+        }
+        self.state.store(&request, &response);
+
+        Ok(response)
+    }
+
+    // Sends a request, retrying it as the `RetryPolicy` allows, and returns the last response.
+    #[tracing::instrument(
+        name = "jira_request",
+        skip_all,
+        fields(method = %request.method, path = %request.path)
+    )]
+    fn execute(&self, builder: RequestBuilder, request: &ApiRequest) -> Result<RawResponse, Error> {
+        let mut attempts = Attempts::new(&self.state.config, request);
+        let mut builder = builder;
+
+        let response = loop {
+            let next = builder.try_clone();
+            attempts.start();
+            let result = builder.send();
+            let outcome = result.as_ref().map(|r| (r.url(), r.status(), r.headers()));
+            match (attempts.retry_delay(outcome, next.is_some()), next) {
+                (Some(delay), Some(next)) => {
+                    thread::sleep(delay);
+                    builder = next;
+                }
+                _ => break result?,
+            }
+        };
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let response = RawResponse::new(status, &headers, response.text()?);
+        self.state.received(request, response)
+    }
 }
@@ -0,0 +1,216 @@
+// Caching the responses of metadata endpoints (fields, create metadata, projects, users and
+// server info) on disk, in a directory per JIRA URL with a file per request. Files are named after
+// a hash of the request, and keep the request they answer, so that requests whose names would be
+// alike or too long for a file name are cached apart.
+use crate::config::{CacheOptions, Config};
+use crate::endpoints::{ApiRequest, RawResponse};
+use crate::error::{CustomError, Error};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long cached responses are used for, unless `CacheOptions.ttl_secs` is set: one hour.
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 60 * 60;
+
+// The file that marks the directory of a site as created by the cache, so that clearing the cache
+// only removes what it created. It is a cache directory tag (https://bford.info/cachedir/), which
+// backup tools skip.
+const TAG_FILE: &str = "CACHEDIR.TAG";
+const TAG: &str = "Signature: 8a477f597d28d172789f06886806bc55\n\
+                   # This directory is a cache of JIRA responses, created by jiragen.\n";
+
+// Site directory names are cut to this many characters, well within file name limits.
+const MAX_DIR_NAME_LEN: usize = 100;
+
+// A cached response, the request it answers, and when it was cached.
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    /// The JIRA URL, method, path and query of the request.
+    key: String,
+    /// Seconds since the Unix epoch.
+    cached_at: u64,
+    status: u16,
+    body: Value,
+}
+
+/// The cache of one JIRA site.
+pub(crate) struct Cache {
+    // The directory of the site's entries.
+    dir: PathBuf,
+    jira_url: String,
+    ttl: Duration,
+    refresh: bool,
+    offline: bool,
+}
+
+impl Cache {
+    /// The cache of the `Config`'s JIRA URL, when `CacheOptions.dir` is set.
+    pub fn new(config: &Config) -> Option<Self> {
+        let options = &config.cache;
+        let dir = options.dir.as_ref()?;
+
+        Some(Self {
+            dir: dir.join(dir_name(config.jira_url.trim_end_matches('/'))),
+            jira_url: config.jira_url.clone(),
+            ttl: Duration::from_secs(options.ttl_secs.unwrap_or(DEFAULT_CACHE_TTL_SECS)),
+            refresh: options.refresh,
+            offline: options.offline,
+        })
+    }
+
+    /// The cached response to a request, if it is cacheable and was cached less than the TTL ago
+    /// (or at any time, offline). Offline, a request that isn't cached is an error.
+    pub fn get(&self, request: &ApiRequest) -> Result<Option<RawResponse>, Error> {
+        if !request.cacheable {
+            return match self.offline {
+                true => Err(Error::CustomError(CustomError {
+                    message: "JIRA can't be contacted offline".to_string(),
+                    details: format!(
+                        "`offline` is set, so {} {} was not sent",
+                        request.method, request.path
+                    ),
+                })),
+                false => Ok(None),
+            };
+        }
+
+        let entry = match self.refresh {
+            true => None,
+            false => self.read(request),
+        };
+        let entry = entry.filter(|entry| {
+            let age = now().saturating_sub(entry.cached_at);
+            self.offline || age < self.ttl.as_secs()
+        });
+
+        match (entry, self.offline) {
+            (Some(entry), _) => {
+                tracing::debug!(path = %request.path, "cached response");
+                Ok(Some(RawResponse {
+                    status: StatusCode::from_u16(entry.status).unwrap_or(StatusCode::OK),
+                    request_id: None,
+                    body: entry.body.to_string(),
+                }))
+            }
+            (None, true) => Err(Error::CustomError(CustomError {
+                message: "Not cached for offline use".to_string(),
+                details: format!(
+                    "{} {} has not been cached for {}; send it once without `offline` to cache it",
+                    request.method, request.path, self.jira_url
+                ),
+            })),
+            (None, false) => Ok(None),
+        }
+    }
+
+    /// Caches the response to a cacheable request, if it succeeded. A cache that can't be written
+    /// to is only warned about, since the response itself is fine.
+    pub fn put(&self, request: &ApiRequest, response: &RawResponse) {
+        if !request.cacheable || !response.status.is_success() {
+            return;
+        }
+        let body = match serde_json::from_str(&response.body) {
+            Ok(body) => body,
+            Err(_) => return,
+        };
+
+        let entry = Entry {
+            key: self.key(request),
+            cached_at: now(),
+            status: response.status.as_u16(),
+            body,
+        };
+        let written = fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(self.dir.join(TAG_FILE), TAG))
+            .and_then(|_| fs::write(self.path(request), serde_json::to_string(&entry)?));
+        if let Err(e) = written {
+            tracing::warn!("could not cache the response to {}: {}", request.path, e);
+        }
+    }
+
+    // The entry of a request, unless its file is of another request with the same hash.
+    fn read(&self, request: &ApiRequest) -> Option<Entry> {
+        let entry = fs::read_to_string(self.path(request)).ok()?;
+        serde_json::from_str(&entry)
+            .ok()
+            .filter(|entry: &Entry| entry.key == self.key(request))
+    }
+
+    // The JIRA URL, method, path and query of a request, which its entry is kept with.
+    fn key(&self, request: &ApiRequest) -> String {
+        let mut key = format!("{} {}{}", request.method, self.jira_url, request.path);
+        for (i, (param, value)) in request.query.iter().enumerate() {
+            let separator = if i == 0 { '?' } else { '&' };
+            key.push_str(&format!("{}{}={}", separator, param, value));
+        }
+        key
+    }
+
+    // The file of a request, named after the hash of its key.
+    fn path(&self, request: &ApiRequest) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(self.key(request).as_bytes())))
+    }
+}
+
+impl CacheOptions {
+    /// Removes every cached response, of every JIRA URL. Only the directories the cache created
+    /// in `dir`, and the responses in them, are removed; other files are left as they are.
+    pub fn clear(&self) -> Result<(), Error> {
+        let dir = match &self.dir {
+            Some(dir) if dir.is_dir() => dir,
+            _ => return Ok(()),
+        };
+
+        for site in fs::read_dir(dir)? {
+            let site = site?.path();
+            let tagged = fs::read_to_string(site.join(TAG_FILE)).is_ok_and(|tag| tag == TAG);
+            if !tagged {
+                continue;
+            }
+            for entry in fs::read_dir(&site)? {
+                let entry = entry?.path();
+                if entry.extension().is_some_and(|ext| ext == "json") {
+                    fs::remove_file(entry)?;
+                }
+            }
+            fs::remove_file(site.join(TAG_FILE))?;
+            // a directory with other files in it is not only the cache's, and is kept
+            let _ = fs::remove_dir(&site);
+        }
+
+        Ok(())
+    }
+}
+
+// A URL as a directory name, without its scheme, with every character but letters, digits, `-`
+// and `.` replaced with `_`, and cut to `MAX_DIR_NAME_LEN` characters.
+fn dir_name(url: &str) -> String {
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    url.chars()
+        .map(|c| match c.is_ascii_alphanumeric() || "-.".contains(c) {
+            true => c,
+            false => '_',
+        })
+        .take(MAX_DIR_NAME_LEN)
+        .collect()
+}
+
+// The 64-bit FNV-1a hash of some bytes, which, unlike `std`'s hashers, is the same in every build.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
@@ -0,0 +1,311 @@
+// What the blocking and async `JiraClient`s share: the state they keep between requests, and every
+// decision about a request other than how it is sent. The clients only send requests, and wait
+// between retries, each in its own way.
+use crate::cache::Cache;
+use crate::config::{AuthType, Config};
+use crate::endpoints::{self, ApiRequest, RawResponse, SessionResponse};
+use crate::error::Error;
+use crate::logging;
+use crate::models::{DeploymentType, Field, ServerInfo};
+use crate::oauth::{OAuthConfig, OAuthToken};
+use crate::recording::Tape;
+use reqwest::header::COOKIE;
+use reqwest::StatusCode;
+use std::sync::Mutex;
+
+/// The state of a client. Its locks are never held while a request is sent, so that the async
+/// client can share it.
+pub(crate) struct ClientState {
+    pub config: Config,
+    // The session cookie, once logged in with `Cookie` auth.
+    session: Mutex<Option<String>>,
+    // The current token, once loaded with `OAuth` auth.
+    oauth_token: Mutex<Option<OAuthToken>>,
+    // The recording requests are recorded to or replayed from.
+    tape: Option<Tape>,
+    // The on-disk cache of metadata responses.
+    cache: Option<Cache>,
+    // The site's version and deployment, once detected.
+    server_info: Mutex<Option<ServerInfo>>,
+    // The site's fields, once requested to adapt issues.
+    fields: Mutex<Option<Vec<Field>>>,
+}
+
+/// What a client needs to do to authenticate a request.
+pub(crate) enum Authentication<'a> {
+    /// Nothing: the request can be sent to the target.
+    Ready(Target),
+    /// Send the login request, without authentication, and pass its response to
+    /// [`logged_in`](struct.ClientState.html#method.logged_in).
+    Login(ApiRequest),
+    /// Refresh the expired token, and pass the new token to
+    /// [`refreshed`](struct.ClientState.html#method.refreshed).
+    Refresh(&'a OAuthConfig, OAuthToken),
+}
+
+/// The base URL requests are sent to, and the credentials they are sent with.
+pub(crate) struct Target {
+    base_url: String,
+    credentials: Credentials,
+}
+
+enum Credentials {
+    Basic { user: String, key: String },
+    Bearer(String),
+    Cookie(String),
+}
+
+impl Target {
+    /// The URL of a path of the JIRA API.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Adds the credentials to a request.
+    pub fn authorize<B: RequestBuilder>(&self, builder: B) -> B {
+        match &self.credentials {
+            Credentials::Basic { user, key } => builder.basic_auth(user, key),
+            Credentials::Bearer(token) => builder.bearer_auth(token),
+            Credentials::Cookie(cookie) => builder.cookie(cookie),
+        }
+    }
+}
+
+impl ClientState {
+    /// Opens the recording and the cache of the `Config`.
+    pub fn new(config: Config) -> Result<Self, Error> {
+        let tape = Tape::new(&config)?;
+        // recordings and replays are of every request, so they bypass the cache
+        let cache = Cache::new(&config).filter(|_| tape.is_none());
+
+        Ok(Self {
+            config,
+            session: Mutex::new(None),
+            oauth_token: Mutex::new(None),
+            tape,
+            cache,
+            server_info: Mutex::new(None),
+            fields: Mutex::new(None),
+        })
+    }
+
+    /// The URL of a path of the site's own API.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.config.jira_url.trim_end_matches('/'), path)
+    }
+
+    /// What the client needs to do to authenticate a request with the `Config`'s `AuthType`.
+    /// `Cookie` auth logs in once, and `OAuth` auth loads the stored token on first use and
+    /// refreshes it when it has expired.
+    pub fn authentication(&self) -> Result<Authentication<'_>, Error> {
+        let credentials = match self.config.auth {
+            AuthType::Basic => Credentials::Basic {
+                user: self.config.jira_user.clone(),
+                key: self.config.jira_key.clone(),
+            },
+            AuthType::Bearer => Credentials::Bearer(self.config.jira_key.clone()),
+            AuthType::Cookie => match self.session.lock().unwrap().clone() {
+                Some(cookie) => Credentials::Cookie(cookie),
+                None => {
+                    let login =
+                        endpoints::create_session(&self.config.jira_user, &self.config.jira_key);
+                    return Ok(Authentication::Login(login));
+                }
+            },
+            AuthType::OAuth => {
+                let oauth = self.oauth()?;
+                let mut token = self.oauth_token.lock().unwrap();
+                let current = match token.take() {
+                    Some(t) => t,
+                    None => OAuthToken::load(&oauth.token_file)?,
+                };
+                if current.is_expired() {
+                    return Ok(Authentication::Refresh(oauth, current));
+                }
+
+                let target = oauth_target(oauth, &current);
+                *token = Some(current);
+                return Ok(Authentication::Ready(target));
+            }
+        };
+
+        Ok(Authentication::Ready(Target {
+            base_url: self.url(""),
+            credentials,
+        }))
+    }
+
+    /// Keeps the session cookie of a login response.
+    pub fn logged_in(&self, response: RawResponse) -> Result<Target, Error> {
+        let login: SessionResponse = endpoints::parse_response(response)?;
+        let cookie = login.cookie();
+        *self.session.lock().unwrap() = Some(cookie.clone());
+
+        Ok(Target {
+            base_url: self.url(""),
+            credentials: Credentials::Cookie(cookie),
+        })
+    }
+
+    /// Whether JIRA rejected a request because the session has expired, in which case the session
+    /// is forgotten, so that the request can be sent again after logging in again.
+    pub fn session_expired(&self, response: &RawResponse) -> bool {
+        if self.config.auth != AuthType::Cookie || response.status != StatusCode::UNAUTHORIZED {
+            return false;
+        }
+
+        let expired = self.session.lock().unwrap().take().is_some();
+        if expired {
+            tracing::debug!("the JIRA session has expired; logging in again");
+        }
+        expired
+    }
+
+    /// Keeps a refreshed OAuth token.
+    pub fn refreshed(&self, token: OAuthToken) -> Result<Target, Error> {
+        let target = oauth_target(self.oauth()?, &token);
+        *self.oauth_token.lock().unwrap() = Some(token);
+
+        Ok(target)
+    }
+
+    fn oauth(&self) -> Result<&OAuthConfig, Error> {
+        self.config
+            .oauth
+            .as_ref()
+            .ok_or_else(endpoints::oauth_not_configured)
+    }
+
+    /// The response to a request that is replayed, or answered from the cache, rather than sent.
+    pub fn stored(&self, request: &ApiRequest) -> Result<Option<RawResponse>, Error> {
+        if let Some(tape) = self.tape.as_ref().filter(|t| t.is_replay()) {
+            return tape.replay(request).map(Some);
+        }
+        match self.cache.as_ref() {
+            Some(cache) => cache.get(request),
+            None => Ok(None),
+        }
+    }
+
+    /// Caches the response to a request, when it is metadata.
+    pub fn store(&self, request: &ApiRequest, response: &RawResponse) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.put(request, response);
+        }
+    }
+
+    /// Logs the body of the response a request was sent and answered with, and records it.
+    pub fn received(
+        &self,
+        request: &ApiRequest,
+        response: RawResponse,
+    ) -> Result<RawResponse, Error> {
+        logging::log_response_body(&response, &self.config.jira_key);
+        if let Some(tape) = self.tape.as_ref() {
+            tape.record(request, &response)?;
+        }
+
+        Ok(response)
+    }
+
+    /// The site's version and deployment, once detected.
+    pub fn known_server_info(&self) -> Option<ServerInfo> {
+        self.server_info.lock().unwrap().clone()
+    }
+
+    /// Keeps the site's version and deployment for the life of the client.
+    pub fn detected(&self, info: ServerInfo) -> ServerInfo {
+        tracing::debug!(
+            deployment = %info.deployment_type,
+            version = %info.version,
+            "detected JIRA"
+        );
+        *self.server_info.lock().unwrap() = Some(info.clone());
+        info
+    }
+
+    /// The site's fields, once requested.
+    pub fn known_fields(&self) -> Option<Vec<Field>> {
+        self.fields.lock().unwrap().clone()
+    }
+
+    /// Keeps the site's fields for the life of the client.
+    pub fn fields_received(&self, fields: Vec<Field>) -> Vec<Field> {
+        *self.fields.lock().unwrap() = Some(fields.clone());
+        fields
+    }
+}
+
+/// The site's deployment, given the response to the server info request, or `None` when JIRA
+/// responded with an error, e.g. from behind a proxy that only lets some endpoints through.
+pub(crate) fn deployment(
+    server_info: Result<ServerInfo, Error>,
+) -> Result<Option<DeploymentType>, Error> {
+    match server_info {
+        Ok(info) => Ok(Some(info.deployment_type)),
+        Err(Error::Api { status, .. }) => {
+            tracing::debug!(status, "JIRA did not report its deployment");
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Adds the query and body of an `ApiRequest` to a request.
+pub(crate) fn prepare<B: RequestBuilder>(builder: B, request: &ApiRequest) -> B {
+    let mut builder = builder;
+    if !request.query.is_empty() {
+        builder = builder.query(&request.query);
+    }
+    if let Some(body) = request.body.as_ref() {
+        builder = builder.json(body);
+    }
+    builder
+}
+
+// OAuth requests go through the API gateway rather than the site's own URL.
+fn oauth_target(oauth: &OAuthConfig, token: &OAuthToken) -> Target {
+    Target {
+        base_url: oauth.site_url(token),
+        credentials: Credentials::Bearer(token.access_token.clone()),
+    }
+}
+
+/// The methods of the blocking and async `RequestBuilder`s that requests are built with.
+pub(crate) trait RequestBuilder: Sized {
+    fn basic_auth(self, user: &str, key: &str) -> Self;
+    fn bearer_auth(self, token: &str) -> Self;
+    fn cookie(self, cookie: &str) -> Self;
+    fn query(self, query: &[(String, String)]) -> Self;
+    fn json(self, body: &serde_json::Value) -> Self;
+}
+
+macro_rules! impl_request_builder {
+    ($builder:ty) => {
+        impl RequestBuilder for $builder {
+            fn basic_auth(self, user: &str, key: &str) -> Self {
+                <$builder>::basic_auth(self, user, Some(key))
+            }
+
+            fn bearer_auth(self, token: &str) -> Self {
+                <$builder>::bearer_auth(self, token)
+            }
+
+            fn cookie(self, cookie: &str) -> Self {
+                <$builder>::header(self, COOKIE, cookie)
+            }
+
+            fn query(self, query: &[(String, String)]) -> Self {
+                <$builder>::query(self, query)
+            }
+
+            fn json(self, body: &serde_json::Value) -> Self {
+                <$builder>::json(self, body)
+            }
+        }
+    };
+}
+
+impl_request_builder!(reqwest::blocking::RequestBuilder);
+#[cfg(feature = "async")]
+impl_request_builder!(reqwest::RequestBuilder);
@@ -1,13 +1,172 @@
+use crate::oauth::OAuthConfig;
+use crate::redact::{is_secret, Secret, REDACTED};
+use crate::retry::RetryPolicy;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
 
-/// JiraGen configuration that is used for sending requests to JIRA. The username and API key
-/// fields are required because JiraGen uses Basic Authentication to send requests to JIRA.
-#[derive(Debug, Deserialize, Serialize)]
+/// JiraGen configuration that is used for sending requests to JIRA. How the username and API key
+/// fields are used to authenticate depends on [`auth`](enum.AuthType.html). Its `Debug` output
+/// redacts `jira_key`.
+#[derive(Clone, Default, Deserialize, Serialize)]
 pub struct Config {
     /// The URL of the JIRA server.
     pub jira_url: String,
     /// The user account name to login to JIRA
     pub jira_user: String,
-    /// The user's API key to login to JIRA
+    /// The user's API key to login to JIRA, or the personal access token for `Bearer` auth.
     pub jira_key: String,
+    /// The scheme used to authenticate requests.
+    #[serde(default)]
+    pub auth: AuthType,
+    /// The version of the REST API requests are sent to.
+    #[serde(default)]
+    pub api_version: ApiVersion,
+    /// The OAuth 2.0 app and token file, required for `OAuth` auth.
+    #[serde(default)]
+    pub oauth: Option<OAuthConfig>,
+    /// How requests that JIRA rate limited or failed to answer are retried.
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// How the HTTP client connects to JIRA.
+    #[serde(default)]
+    pub client: ClientOptions,
+    /// Where and for how long metadata responses are cached on disk.
+    #[serde(default)]
+    pub cache: CacheOptions,
+}
+
+/// HTTP client settings, for networks that need a proxy, an internal CA, or client certificates.
+/// Unset options keep `reqwest`'s defaults, e.g. the system's proxy settings and certificates.
+/// Its `Debug` output redacts the values of credential headers, e.g. `Authorization`.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ClientOptions {
+    /// How long a request may take, in seconds, from connecting until the response is read. The
+    /// blocking client defaults to 30 seconds.
+    pub timeout_secs: Option<u64>,
+    /// How long connecting may take, in seconds.
+    pub connect_timeout_secs: Option<u64>,
+    /// The proxy every request is sent through, e.g. `http://proxy.corp.net:3128`.
+    pub proxy: Option<String>,
+    /// A PEM file of the CA certificates to trust in addition to the system's.
+    pub ca_cert: Option<PathBuf>,
+    /// A PEM file of the client certificate, for mutual TLS. Requires `client_key`.
+    pub client_cert: Option<PathBuf>,
+    /// A PEM file of the client certificate's PKCS #8 private key.
+    pub client_key: Option<PathBuf>,
+    /// Headers sent with every request, in addition to `Accept` and `Content-Type`.
+    pub headers: BTreeMap<String, String>,
+    /// A JSON file to record every request sent with the client's typed methods, and JIRA's
+    /// response, to (see [`Recording`](struct.Recording.html)), e.g. to attach to a bug report.
+    pub record: Option<PathBuf>,
+    /// A JSON file recorded with `record` to send responses from instead of JIRA, without network
+    /// access. Each request is answered with the first response not replayed yet to a request with
+    /// the same method, path and query.
+    pub replay: Option<PathBuf>,
+}
+
+/// An on-disk cache of the responses of the metadata endpoints: fields, create metadata, projects,
+/// users and server info. Each JIRA URL has a directory of its own in `dir`, with a file per
+/// request. Responses are only cached when `dir` is set, and never while recording or replaying.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CacheOptions {
+    /// The directory to cache responses in.
+    pub dir: Option<PathBuf>,
+    /// How long cached responses are used for, in seconds. Defaults to
+    /// [`DEFAULT_CACHE_TTL_SECS`](constant.DEFAULT_CACHE_TTL_SECS.html).
+    pub ttl_secs: Option<u64>,
+    /// Requests every response again, ignoring cached ones, and caches it.
+    pub refresh: bool,
+    /// Answers metadata requests from the cache only, however old the cached responses are, and
+    /// sends no requests to JIRA: requests that aren't cached, or can't be, are errors.
+    pub offline: bool,
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("jira_url", &self.jira_url)
+            .field("jira_user", &self.jira_user)
+            .field("jira_key", &Secret(&self.jira_key))
+            .field("auth", &self.auth)
+            .field("api_version", &self.api_version)
+            .field("oauth", &self.oauth)
+            .field("retry", &self.retry)
+            .field("client", &self.client)
+            .field("cache", &self.cache)
+            .finish()
+    }
+}
+
+impl fmt::Debug for ClientOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers: BTreeMap<&str, &str> = self
+            .headers
+            .iter()
+            .map(|(name, value)| match is_secret(name) {
+                true => (name.as_str(), REDACTED),
+                false => (name.as_str(), value.as_str()),
+            })
+            .collect();
+
+        f.debug_struct("ClientOptions")
+            .field("timeout_secs", &self.timeout_secs)
+            .field("connect_timeout_secs", &self.connect_timeout_secs)
+            .field("proxy", &self.proxy)
+            .field("ca_cert", &self.ca_cert)
+            .field("client_cert", &self.client_cert)
+            .field("client_key", &self.client_key)
+            .field("headers", &headers)
+            .field("record", &self.record)
+            .field("replay", &self.replay)
+            .finish()
+    }
+}
+
+/// The version of JIRA's REST API that requests are sent to, `"2"` or `"3"` in a config file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ApiVersion {
+    /// `/rest/api/2`, supported by JIRA Cloud, Server and Data Center. Rich-text fields are plain
+    /// strings (in JIRA's wiki markup).
+    #[default]
+    #[serde(rename = "2")]
+    V2,
+    /// `/rest/api/3`, supported by JIRA Cloud. Rich-text fields are in the
+    /// [Atlassian Document Format](adf/index.html): the description, environment and added
+    /// comments of issues are converted to it from plain text or Markdown when they are created,
+    /// and are returned in it by JIRA.
+    #[serde(rename = "3")]
+    V3,
+}
+
+impl ApiVersion {
+    /// The version's number, as in `/rest/api/<number>`.
+    pub fn number(self) -> u8 {
+        match self {
+            ApiVersion::V2 => 2,
+            ApiVersion::V3 => 3,
+        }
+    }
+}
+
+/// The authentication scheme used to send requests to JIRA.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthType {
+    /// Basic Authentication with the username and API key (JIRA Cloud) or password.
+    #[default]
+    Basic,
+    /// A personal access token, sent as `Authorization: Bearer <jira_key>` (JIRA Data Center).
+    Bearer,
+    /// A session cookie, created by logging in with the username and password, and again when
+    /// JIRA responds that the session has expired (legacy JIRA Server).
+    Cookie,
+    /// An OAuth 2.0 access token, obtained with
+    /// [`OAuthConfig::login`](struct.OAuthConfig.html#method.login) and refreshed automatically
+    /// (JIRA Cloud). Requests are sent through the Atlassian API gateway instead of `jira_url`.
+    #[serde(rename = "oauth")]
+    OAuth,
 }
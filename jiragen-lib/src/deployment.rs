@@ -0,0 +1,262 @@
+// Adapting issues to the deployment of a JIRA site. Cloud identifies users by account id and
+// links issues to epics with `parent`; Server and Data Center identify users by name and link
+// issues to epics with the Epic Link custom field. Templates may use either form, or the `epic`
+// pseudo-field, and the clients rewrite them for the site they send to. The site's fields also
+// tell which custom fields are rich text, which version 3 of the API expects in ADF.
+use crate::adf;
+use crate::config::ApiVersion;
+use crate::models::{DeploymentType, Field, JiraIssue, User};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The pseudo-field an issue's epic can be set with, by key, on any deployment.
+pub const EPIC_FIELD: &str = "epic";
+
+// The custom field type and name of the Epic Link field of JIRA Server.
+const EPIC_LINK_TYPE: &str = "com.pyxis.greenhopper.jira:gh-epic-link";
+const EPIC_LINK_NAME: &str = "Epic Link";
+
+// The custom field type of multi-line text fields, which are rich text.
+const TEXTAREA_TYPE: &str = "com.atlassian.jira.plugin.system.customfieldtypes:textarea";
+
+// The system fields whose values are users, even when the site's fields aren't known.
+const USER_FIELDS: [&str; 2] = ["assignee", "reporter"];
+
+// The properties a user can be referred to by, on either deployment.
+const USER_REFS: [&str; 4] = ["accountId", "name", "emailAddress", "key"];
+
+/// Whether any of the issues sets its epic, refers to a user, or, with version 3 of the API, sets
+/// a custom field to text that may be rich text, so that the site's deployment and fields are
+/// needed to adapt them. References to projects and issue types are never users.
+pub(crate) fn needs_adapting(issues: &[JiraIssue], api_version: ApiVersion) -> bool {
+    issues.iter().any(|issue| {
+        let fields = match issue.fields.as_object() {
+            Some(fields) => fields,
+            None => return false,
+        };
+        fields.contains_key(EPIC_FIELD)
+            || fields
+                .iter()
+                .filter(|(id, _)| *id != "project" && *id != "issuetype")
+                .any(|(_, value)| user_refs(value).next().is_some())
+            || (api_version == ApiVersion::V3
+                && fields
+                    .iter()
+                    .any(|(id, value)| id.starts_with("customfield_") && value.is_string()))
+    })
+}
+
+/// Rewrites issues for a deployment and API version, given the site's fields.
+pub(crate) struct Adapter<'a> {
+    pub deployment: DeploymentType,
+    pub api_version: ApiVersion,
+    pub fields: &'a [Field],
+}
+
+impl Adapter<'_> {
+    /// The names and email addresses of the users the issues refer to without an account id,
+    /// which JIRA Cloud needs account ids for. Empty for Server and Data Center.
+    pub fn user_queries(&self, issues: &[JiraIssue]) -> Vec<String> {
+        if !self.deployment.is_cloud() {
+            return Vec::new();
+        }
+
+        let mut queries = BTreeSet::new();
+        for issue in issues {
+            for id in self.user_fields() {
+                for user in user_refs(&issue.fields[id.as_str()]) {
+                    if user.get("accountId").is_none() {
+                        queries.extend(user_query(user));
+                    }
+                }
+            }
+        }
+        queries.into_iter().collect()
+    }
+
+    /// The issues as the deployment expects them, given the users found for each of the
+    /// [`user_queries`](#method.user_queries): users referred to by account id on Cloud and by
+    /// name on Server, the `epic` pseudo-field replaced with `parent` on Cloud and the Epic Link
+    /// field on Server, and, with version 3 of the API, the text of rich-text custom fields
+    /// converted to ADF. References that can't be adapted are kept as they are, for JIRA to
+    /// report.
+    pub fn adapt_all(
+        &self,
+        issues: &[JiraIssue],
+        users: &BTreeMap<String, Vec<User>>,
+    ) -> Vec<JiraIssue> {
+        let account_ids: BTreeMap<String, String> = users
+            .iter()
+            .filter_map(|(query, users)| Some((query.clone(), account_id(query, users)?)))
+            .collect();
+
+        issues
+            .iter()
+            .map(|issue| self.adapt(issue, &account_ids))
+            .collect()
+    }
+
+    // The issue with its users looked up in `account_ids`, by name or email address.
+    fn adapt(&self, issue: &JiraIssue, account_ids: &BTreeMap<String, String>) -> JiraIssue {
+        let mut issue = issue.clone();
+        let fields = match issue.fields.as_object_mut() {
+            Some(fields) => fields,
+            None => return issue,
+        };
+
+        for id in self.user_fields() {
+            match fields.get_mut(&id) {
+                Some(Value::Array(users)) => {
+                    for user in users.iter_mut() {
+                        self.adapt_user(user, account_ids);
+                    }
+                }
+                Some(user) => self.adapt_user(user, account_ids),
+                None => {}
+            }
+        }
+
+        if self.api_version == ApiVersion::V3 {
+            for id in self.rich_text_fields() {
+                if let Some(text) = fields.get(&id).and_then(Value::as_str) {
+                    let document = adf::from_markdown(text);
+                    fields.insert(id, document);
+                }
+            }
+        }
+
+        let epic = match fields.remove(EPIC_FIELD) {
+            Some(Value::String(epic)) if !epic.trim().is_empty() => epic,
+            Some(Value::String(_)) | None => return issue,
+            Some(epic) => {
+                fields.insert(EPIC_FIELD.to_string(), epic);
+                return issue;
+            }
+        };
+        match (self.deployment.is_cloud(), self.epic_link_field()) {
+            (true, _) => {
+                fields
+                    .entry("parent")
+                    .or_insert_with(|| json!({ "key": epic }));
+            }
+            (false, Some(epic_link)) => {
+                fields.insert(epic_link.id.clone(), json!(epic));
+            }
+            (false, None) => {
+                tracing::warn!("JIRA has no Epic Link field to set the epic {} with", epic);
+                fields.insert(EPIC_FIELD.to_string(), json!(epic));
+            }
+        }
+
+        issue
+    }
+
+    fn adapt_user(&self, user: &mut Value, account_ids: &BTreeMap<String, String>) {
+        let reference = match user.as_object() {
+            Some(reference) => reference,
+            None => return,
+        };
+
+        match self.deployment.is_cloud() {
+            true if reference.contains_key("accountId") => {}
+            true => {
+                let account_id = user_query(reference).and_then(|q| account_ids.get(&q));
+                if let Some(account_id) = account_id {
+                    *user = json!({ "accountId": account_id });
+                }
+            }
+            // Server has no account ids, so an account id in a template is taken to be a name
+            false => {
+                if let (Some(account_id), None, None) = (
+                    reference.get("accountId"),
+                    reference.get("name"),
+                    reference.get("key"),
+                ) {
+                    *user = json!({ "name": account_id });
+                }
+            }
+        }
+    }
+
+    // The ids of the fields whose values are users.
+    fn user_fields(&self) -> BTreeSet<String> {
+        let mut ids: BTreeSet<String> = USER_FIELDS.iter().map(|id| id.to_string()).collect();
+        ids.extend(
+            self.fields
+                .iter()
+                .filter(|field| {
+                    field.schema.as_ref().is_some_and(|schema| {
+                        schema.field_type == "user" || schema.items.as_deref() == Some("user")
+                    })
+                })
+                .map(|field| field.id.clone()),
+        );
+        ids
+    }
+
+    // The ids of the custom fields whose values are rich text. The system ones, e.g.
+    // `description`, are converted by `JiraIssue::to_adf` when issues are sent.
+    fn rich_text_fields(&self) -> BTreeSet<String> {
+        self.fields
+            .iter()
+            .filter(|field| {
+                let custom_type = field.schema.as_ref().and_then(|s| s.custom.as_deref());
+                custom_type == Some(TEXTAREA_TYPE)
+            })
+            .map(|field| field.id.clone())
+            .collect()
+    }
+
+    fn epic_link_field(&self) -> Option<&Field> {
+        self.fields.iter().find(|field| {
+            let custom_type = field.schema.as_ref().and_then(|s| s.custom.as_deref());
+            custom_type == Some(EPIC_LINK_TYPE) || (field.custom && field.name == EPIC_LINK_NAME)
+        })
+    }
+}
+
+// The account id of the user a query (a name or email address) refers to: the only user whose
+// email address or name is the query. Users found only because their name or display name
+// contains the query are never taken to be the user, even when they are the only one found.
+fn account_id(query: &str, users: &[User]) -> Option<String> {
+    let exact: Vec<&User> = users
+        .iter()
+        .filter(|user| {
+            let email = user.email_address.as_deref().unwrap_or_default();
+            email.eq_ignore_ascii_case(query) || user.name.as_deref() == Some(query)
+        })
+        .collect();
+    match exact.as_slice() {
+        [user] => user.account_id.clone(),
+        _ => {
+            tracing::warn!(
+                "{} JIRA users have the name or email address {}, so it is not replaced with an \
+                 account id",
+                exact.len(),
+                query
+            );
+            None
+        }
+    }
+}
+
+// The user references in a value: the value itself or the items of an array, when they are
+// objects with a user property.
+fn user_refs(value: &Value) -> impl Iterator<Item = &serde_json::Map<String, Value>> {
+    let values = match value {
+        Value::Array(values) => values.as_slice(),
+        value => std::slice::from_ref(value),
+    };
+    values
+        .iter()
+        .filter_map(Value::as_object)
+        .filter(|reference| USER_REFS.iter().any(|k| reference.contains_key(*k)))
+}
+
+// The name or email address a user reference without an account id can be looked up by.
+fn user_query(reference: &serde_json::Map<String, Value>) -> Option<String> {
+    ["emailAddress", "name", "key"]
+        .iter()
+        .find_map(|k| reference.get(*k).and_then(Value::as_str))
+        .map(str::to_string)
+}
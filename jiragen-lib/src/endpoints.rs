@@ -0,0 +1,417 @@
+// The JIRA API endpoints used by JiraGen. These describe requests independently of the HTTP
+// client, so that the blocking and async clients send exactly the same requests.
+use crate::config::ApiVersion;
+use crate::error::{CustomError, Error};
+use crate::models::{
+    BulkCreateError, BulkCreateResponse, DeploymentType, JiraIssue, SearchPage, SearchResults,
+};
+use crate::search::SearchOptions;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// A request to a path of the JIRA API.
+#[derive(Clone, Debug)]
+pub(crate) struct ApiRequest {
+    pub method: Method,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub body: Option<Value>,
+    /// Whether sending the request more than once has the same effect as sending it once, so that
+    /// it can be retried after any transient failure.
+    pub idempotent: bool,
+    /// Whether the response is metadata that can be cached on disk.
+    pub cacheable: bool,
+}
+
+impl ApiRequest {
+    fn new(method: Method, path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            query: Vec::new(),
+            body: None,
+            idempotent: method != Method::POST && method != Method::PATCH,
+            cacheable: false,
+            method,
+        }
+    }
+
+    // Marks a `POST` request that only reads, e.g. a search, as idempotent.
+    fn read_only(mut self) -> Self {
+        self.idempotent = true;
+        self
+    }
+
+    // Marks a request for metadata, whose response can be cached.
+    fn cached(mut self) -> Self {
+        self.cacheable = true;
+        self
+    }
+
+    fn query(mut self, name: &str, value: &str) -> Self {
+        self.query.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn json(mut self, body: Value) -> Self {
+        self.body = Some(body);
+        self
+    }
+}
+
+// The path of an endpoint of a version of the REST API, e.g. `/rest/api/3/field`.
+fn api_path(version: ApiVersion, path: &str) -> String {
+    format!("/rest/api/{}{}", version.number(), path)
+}
+
+// An issue as the version of the API expects it.
+fn issue_body(version: ApiVersion, issue: &JiraIssue) -> Value {
+    match version {
+        ApiVersion::V2 => json!(issue),
+        ApiVersion::V3 => json!(issue.to_adf()),
+    }
+}
+
+pub(crate) fn create_issue(version: ApiVersion, issue: &JiraIssue) -> ApiRequest {
+    ApiRequest::new(Method::POST, &api_path(version, "/issue")).json(issue_body(version, issue))
+}
+
+pub(crate) fn bulk_create(version: ApiVersion, issues: &[JiraIssue]) -> ApiRequest {
+    let issues: Vec<Value> = issues.iter().map(|i| issue_body(version, i)).collect();
+    ApiRequest::new(Method::POST, &api_path(version, "/issue/bulk"))
+        .json(json!({ "issueUpdates": issues }))
+}
+
+/// Adds the response to one chunk of a bulk create, whose first issue was at `offset` in the whole
+/// request, to the aggregated response. The `failed_element_number` of each error is offset so
+/// that it indexes the whole request rather than the chunk.
+pub(crate) fn merge_bulk_response(
+    aggregated: &mut BulkCreateResponse,
+    offset: usize,
+    chunk: BulkCreateResponse,
+) {
+    aggregated.issues.extend(chunk.issues);
+    aggregated
+        .errors
+        .extend(chunk.errors.into_iter().map(|error| BulkCreateError {
+            failed_element_number: error.failed_element_number + offset,
+            ..error
+        }));
+}
+
+/// The response of the bulk issue creation endpoint. JIRA responds with `400 Bad Request` when
+/// none of the issues could be created, with the errors of each issue in the usual response body.
+pub(crate) fn bulk_create_response(response: RawResponse) -> Result<BulkCreateResponse, Error> {
+    if response.status.is_success() {
+        return Ok(serde_json::from_str(&response.body)?);
+    }
+
+    match serde_json::from_str::<BulkCreateResponse>(&response.body) {
+        Ok(created) if response.status == StatusCode::BAD_REQUEST && !created.errors.is_empty() => {
+            Ok(created)
+        }
+        _ => Err(status_error(response)),
+    }
+}
+
+/// The error for a chunk of a bulk create that failed, after the chunks before it were created.
+pub(crate) fn chunk_error(error: Error, offset: usize, len: usize, created: usize) -> Error {
+    match error {
+        Error::Api {
+            status,
+            mut error_messages,
+            field_errors,
+            request_id,
+        } if offset > 0 => {
+            error_messages.insert(
+                0,
+                format!(
+                    "Issues {} to {} were not created, after {} issues were created.",
+                    offset + 1,
+                    offset + len,
+                    created
+                ),
+            );
+            Error::Api {
+                status,
+                error_messages,
+                field_errors,
+                request_id,
+            }
+        }
+        e => e,
+    }
+}
+
+/// The error for a bulk create chunk size of zero.
+pub(crate) fn invalid_chunk_size() -> Error {
+    Error::CustomError(CustomError {
+        message: "Invalid chunk size".to_string(),
+        details: "Issues must be bulk created at least one at a time".to_string(),
+    })
+}
+
+pub(crate) fn get_project(version: ApiVersion, project: &str) -> ApiRequest {
+    ApiRequest::new(
+        Method::GET,
+        &api_path(version, &format!("/project/{}", project)),
+    )
+    .cached()
+}
+
+pub(crate) fn get_issue(version: ApiVersion, issue: &str) -> ApiRequest {
+    ApiRequest::new(
+        Method::GET,
+        &api_path(version, &format!("/issue/{}", issue)),
+    )
+}
+
+/// Searches with `startAt` paging, for the fields and expansions of the options (or JIRA's
+/// default fields).
+pub(crate) fn search(
+    version: ApiVersion,
+    jql: &str,
+    start_at: u64,
+    max_results: u64,
+    options: &SearchOptions,
+) -> ApiRequest {
+    let mut body = json!({ "jql": jql, "startAt": start_at, "maxResults": max_results });
+    if !options.fields.is_empty() {
+        body["fields"] = json!(options.fields);
+    }
+    if !options.expand.is_empty() {
+        body["expand"] = json!(options.expand);
+    }
+
+    ApiRequest::new(Method::POST, &api_path(version, "/search"))
+        .json(body)
+        .read_only()
+}
+
+/// Searches with the enhanced search endpoint of JIRA Cloud, which pages with tokens rather than
+/// `startAt`, and only returns the fields asked for: the navigable fields, unless the options name
+/// others.
+fn search_jql(
+    version: ApiVersion,
+    jql: &str,
+    page_token: Option<&str>,
+    options: &SearchOptions,
+) -> ApiRequest {
+    let fields = match options.fields.is_empty() {
+        true => vec!["*navigable".to_string()],
+        false => options.fields.clone(),
+    };
+    let mut body = json!({ "jql": jql, "maxResults": options.page_size, "fields": fields });
+    if let Some(token) = page_token {
+        body["nextPageToken"] = json!(token);
+    }
+    // unlike `/search`, the enhanced search endpoint takes its expansions as one string
+    if !options.expand.is_empty() {
+        body["expand"] = json!(options.expand.join(","));
+    }
+
+    ApiRequest::new(Method::POST, &api_path(version, "/search/jql"))
+        .json(body)
+        .read_only()
+}
+
+/// The `startAt` a page token of JIRA Server stands for. Server has no page tokens, so its
+/// pages are requested with `startAt` encoded as the token.
+pub(crate) fn start_at(page_token: Option<&str>) -> Result<u64, Error> {
+    match page_token {
+        None => Ok(0),
+        Some(token) => token.parse().map_err(|_| {
+            Error::CustomError(CustomError {
+                message: "Invalid page token".to_string(),
+                details: format!("`{}` is not a page token of this JIRA site", token),
+            })
+        }),
+    }
+}
+
+/// A page of `startAt` search results as a page of token search results.
+pub(crate) fn search_page(results: SearchResults) -> SearchPage {
+    let next = results.start_at + results.issues.len() as u64;
+    let next_page_token = match results.issues.is_empty() || next >= results.total {
+        true => None,
+        false => Some(next.to_string()),
+    };
+
+    SearchPage {
+        issues: results.issues,
+        next_page_token,
+    }
+}
+
+/// Requests a page of a search, given the token of the page before it: with the enhanced search
+/// endpoint on JIRA Cloud, and with `startAt` otherwise.
+pub(crate) fn page_request(
+    version: ApiVersion,
+    deployment: Option<DeploymentType>,
+    jql: &str,
+    page_token: Option<&str>,
+    options: &SearchOptions,
+) -> Result<ApiRequest, Error> {
+    match pages_with_tokens(deployment) {
+        true => Ok(search_jql(version, jql, page_token, options)),
+        false => {
+            let start_at = start_at(page_token)?;
+            Ok(search(version, jql, start_at, options.page_size, options))
+        }
+    }
+}
+
+/// The page of issues a `page_request` was answered with.
+pub(crate) fn page_response(
+    deployment: Option<DeploymentType>,
+    response: RawResponse,
+) -> Result<SearchPage, Error> {
+    match pages_with_tokens(deployment) {
+        true => parse_response(response),
+        false => Ok(search_page(parse_response(response)?)),
+    }
+}
+
+// Whether the site is searched with page tokens rather than `startAt`.
+fn pages_with_tokens(deployment: Option<DeploymentType>) -> bool {
+    deployment.is_some_and(|deployment| deployment.is_cloud())
+}
+
+pub(crate) fn get_server_info(version: ApiVersion) -> ApiRequest {
+    ApiRequest::new(Method::GET, &api_path(version, "/serverInfo")).cached()
+}
+
+pub(crate) fn get_fields(version: ApiVersion) -> ApiRequest {
+    ApiRequest::new(Method::GET, &api_path(version, "/field")).cached()
+}
+
+/// Finds users by name or email address: with the `query` parameter of JIRA Cloud, and the
+/// `username` parameter of JIRA Server. When the deployment isn't known, version 3 of the API is
+/// taken to be Cloud.
+pub(crate) fn find_users(
+    version: ApiVersion,
+    deployment: Option<DeploymentType>,
+    query: &str,
+) -> ApiRequest {
+    let cloud = match deployment {
+        Some(deployment) => deployment.is_cloud(),
+        None => version == ApiVersion::V3,
+    };
+    let param = match cloud {
+        true => "query",
+        false => "username",
+    };
+
+    ApiRequest::new(Method::GET, &api_path(version, "/user/search"))
+        .query(param, query)
+        .cached()
+}
+
+pub(crate) fn get_createmeta(version: ApiVersion, project: &str) -> ApiRequest {
+    let project_param = match project.chars().all(|c| c.is_ascii_digit()) {
+        true => "projectIds",
+        false => "projectKeys",
+    };
+
+    ApiRequest::new(Method::GET, &api_path(version, "/issue/createmeta"))
+        .query(project_param, project)
+        .query("expand", "projects.issuetypes.fields")
+        .cached()
+}
+
+pub(crate) fn create_session(username: &str, password: &str) -> ApiRequest {
+    ApiRequest::new(Method::POST, "/rest/auth/1/session")
+        .json(json!({ "username": username, "password": password }))
+}
+
+// The response of the session login endpoint.
+#[derive(Deserialize)]
+pub(crate) struct SessionResponse {
+    session: SessionCookie,
+}
+
+#[derive(Deserialize)]
+struct SessionCookie {
+    name: String,
+    value: String,
+}
+
+impl SessionResponse {
+    /// The `name=value` session cookie.
+    pub fn cookie(&self) -> String {
+        format!("{}={}", self.session.name, self.session.value)
+    }
+}
+
+/// The parts of a response that JiraGen reads.
+#[derive(Debug)]
+pub(crate) struct RawResponse {
+    pub status: StatusCode,
+    /// The id JIRA logged the request with, from the `X-AREQUESTID` header.
+    pub request_id: Option<String>,
+    pub body: String,
+}
+
+impl RawResponse {
+    pub fn new(status: StatusCode, headers: &HeaderMap, body: String) -> Self {
+        Self {
+            status,
+            request_id: headers
+                .get("x-arequestid")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+            body,
+        }
+    }
+}
+
+/// Deserializes a response, or returns the error JIRA responded with.
+pub(crate) fn parse_response<T: DeserializeOwned>(response: RawResponse) -> Result<T, Error> {
+    if !response.status.is_success() {
+        return Err(status_error(response));
+    }
+
+    Ok(serde_json::from_str(&response.body)?)
+}
+
+// The body JIRA responds to a failed request with.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorCollection {
+    #[serde(default)]
+    error_messages: Vec<String>,
+    #[serde(default)]
+    errors: BTreeMap<String, String>,
+}
+
+/// The error for a response JIRA sent with a non-2xx status. When the body is not one of JIRA's
+/// error collections, e.g. a proxy's error page, it is kept as the only error message.
+pub(crate) fn status_error(response: RawResponse) -> Error {
+    let body = response.body.trim();
+    let errors = match serde_json::from_str::<ErrorCollection>(body) {
+        Ok(errors) => errors,
+        Err(_) if body.is_empty() => ErrorCollection::default(),
+        Err(_) => ErrorCollection {
+            error_messages: vec![body.to_string()],
+            ..Default::default()
+        },
+    };
+
+    Error::Api {
+        status: response.status.as_u16(),
+        error_messages: errors.error_messages,
+        field_errors: errors.errors,
+        request_id: response.request_id,
+    }
+}
+
+/// The error for a `Config` that uses `OAuth` auth without OAuth settings.
+pub(crate) fn oauth_not_configured() -> Error {
+    Error::CustomError(CustomError {
+        message: "OAuth is not configured".to_string(),
+        details: "`Config.oauth` must be set to use `AuthType::OAuth`".to_string(),
+    })
+}
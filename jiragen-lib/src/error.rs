@@ -2,6 +2,8 @@ use csv::Error as csvError;
 use failure::Fail;
 use reqwest::Error as reqwestError;
 use serde_json::Error as jsonError;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::io::Error as ioError;
 
 #[derive(Debug, Fail)]
@@ -17,16 +19,97 @@ pub struct CustomError {
 #[derive(Debug, Fail)]
 /// Error handler for JiraGen-related errors. Encapsulates CSV, Reqwest, File, and Serialization errors.
 pub enum Error {
-  #[fail(display = "An error occurred when parsing csv file: {}", _0)]
   CsvError(#[fail(cause)] csvError),
-  #[fail(display = "An error occurred when sending request: {}", _0)]
   ReqError(#[fail(cause)] reqwestError),
-  #[fail(display = "An error occurred during file operation: {}", _0)]
   IoError(#[fail(cause)] ioError),
-  #[fail(display = "An error occurred when parsing json: {}", _0)]
   JsonError(#[fail(cause)] jsonError),
-  #[fail(display = "An error occurred: {}", _0)]
   CustomError(CustomError),
+  /// JIRA responded with a non-2xx status. The messages are parsed from JIRA's error body, and
+  /// displayed one per line, with the field each field error is about.
+  /// ```
+  /// use jiragen::Error;
+  /// use std::collections::BTreeMap;
+  ///
+  /// let error = Error::Api {
+  ///   status: 400,
+  ///   error_messages: vec!["Issue could not be created.".to_string()],
+  ///   field_errors: BTreeMap::from([
+  ///     ("priority".to_string(), "Priority name 'Urgent' is not valid".to_string()),
+  ///     ("summary".to_string(), "You must specify a summary of the issue.".to_string()),
+  ///   ]),
+  ///   request_id: None,
+  /// };
+  ///
+  /// assert_eq!(
+  ///   error.to_string(),
+  ///   "JIRA responded with status 400:
+  ///   - Issue could not be created.
+  ///   - priority: Priority name 'Urgent' is not valid
+  ///   - summary: You must specify a summary of the issue."
+  /// );
+  /// ```
+  Api {
+    /// The HTTP status, e.g. `400`.
+    status: u16,
+    /// Messages about the request as a whole.
+    error_messages: Vec<String>,
+    /// Messages about the fields that could not be set, keyed by field id.
+    field_errors: BTreeMap<String, String>,
+    /// The id JIRA logged the request with, if it sent one.
+    request_id: Option<String>,
+  },
+}
+
+// `failure`'s display attribute only formats fields, so the field errors are listed by hand.
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::CsvError(e) => write!(f, "An error occurred when parsing csv file: {}", e),
+      Error::ReqError(e) => write!(f, "An error occurred when sending request: {}", e),
+      Error::IoError(e) => write!(f, "An error occurred during file operation: {}", e),
+      Error::JsonError(e) => write!(f, "An error occurred when parsing json: {}", e),
+      Error::CustomError(e) => write!(f, "An error occurred: {}", e),
+      Error::Api {
+        status,
+        error_messages,
+        field_errors,
+        ..
+      } => {
+        write!(f, "JIRA responded with status {}:", status)?;
+        for message in error_messages {
+          write!(f, "\n  - {}", message)?;
+        }
+        for (field, message) in field_errors {
+          write!(f, "\n  - {}: {}", field, message)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+impl Error {
+  /// Whether JIRA rejected the credentials (`401`), or the user is not allowed to make the
+  /// request (`403`).
+  pub fn is_auth(&self) -> bool {
+    matches!(
+      self,
+      Error::Api {
+        status: 401 | 403,
+        ..
+      }
+    )
+  }
+
+  /// Whether JIRA could not find what was requested (`404`), e.g. a project or issue.
+  pub fn is_not_found(&self) -> bool {
+    matches!(self, Error::Api { status: 404, .. })
+  }
+
+  /// Whether JIRA rate limited the request (`429`), even after any retries.
+  pub fn is_rate_limited(&self) -> bool {
+    matches!(self, Error::Api { status: 429, .. })
+  }
 }
 
 impl From<csvError> for Error {
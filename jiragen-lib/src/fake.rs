@@ -0,0 +1,856 @@
+// An in-memory JIRA site, to test code written against `JiraApi` without HTTP.
+use crate::adf;
+use crate::config::ApiVersion;
+use crate::deployment::{self, Adapter};
+use crate::endpoints;
+use crate::error::Error;
+use crate::jira_api::JiraApi;
+use crate::models::{
+    BulkCreateError, BulkCreateResponse, CreateMeta, CreateMetaIssueType, CreateMetaProject,
+    CreatedIssue, DeploymentType, ElementErrors, Field, FieldMeta, FieldSchema, Issue, IssueType,
+    JiraIssue, Project, SearchPage, SearchResults, ServerInfo, User,
+};
+use crate::search::SearchOptions;
+use crate::validate::validate_fields;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::sync::{Mutex, MutexGuard};
+
+// The base URL of the fake site, in the API URLs of its issues.
+const BASE_URL: &str = "https://fake-jira.example.com";
+
+// The fields every issue type requires.
+const REQUIRED_FIELDS: [&str; 3] = ["project", "issuetype", "summary"];
+
+// The format of the `created` and `updated` fields of issues.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f%z";
+
+// The fields a `text ~` search looks in.
+const TEXT_FIELDS: [&str; 3] = ["summary", "description", "environment"];
+
+/// A JIRA site in memory, implementing [`JiraApi`](trait.JiraApi.html) without sending requests.
+/// It starts as JIRA Cloud without projects, with the system fields that
+/// [`IssueFields`](struct.IssueFields.html) sets. Add projects, fields and users, create issues
+/// through the trait, and check them with [`issues`](#method.issues).
+///
+/// Issues are validated like [`validate_fields`](fn.validate_fields.html) validates them: every
+/// field is on the create screen of every issue type, `project`, `issuetype` and `summary` are
+/// required, and an issue with problems fails with a `400` [`Error::Api`](enum.Error.html). Like
+/// JIRA, issues are stored as they are sent, unless
+/// [adapted](struct.JiraClient.html#method.adapt_issues) to the deployment first. Their `update`
+/// is not applied, and `created` and `updated` are set to when they were created.
+///
+/// Searches support the clauses [`Jql`](struct.Jql.html) builds, joined with `AND`: `=`, `!=`,
+/// `in` and `not in` on any field, by id or name, `~` and `!~` on text fields and `text`, and
+/// `>=`, `>`, `<=` and `<` on `created`, `updated` and `duedate`, with dates such as `2026-01-01`,
+/// `2026-01-01 09:00` or `-7d`. Values may be quoted. Any other query, e.g. with `OR` or a
+/// function, fails with a `400` `Error::Api`, as do unknown fields. `ORDER BY` is ignored, and
+/// searches only return the fields asked for; expansions are ignored.
+#[derive(Debug)]
+pub struct FakeJira {
+    api_version: ApiVersion,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    server_info: ServerInfo,
+    projects: Vec<Project>,
+    fields: Vec<Field>,
+    users: Vec<User>,
+    issues: Vec<Issue>,
+    next_id: u64,
+}
+
+impl Default for FakeJira {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeJira {
+    /// A JIRA Cloud site without projects or users, whose requests are to version 2 of the API.
+    pub fn new() -> Self {
+        let fields = [
+            ("project", "Project", "project", None),
+            ("issuetype", "Issue Type", "issuetype", None),
+            ("summary", "Summary", "string", None),
+            ("description", "Description", "string", None),
+            ("priority", "Priority", "priority", None),
+            ("labels", "Labels", "array", Some("string")),
+            ("components", "Component/s", "array", Some("component")),
+            ("fixVersions", "Fix Version/s", "array", Some("version")),
+            ("assignee", "Assignee", "user", None),
+            ("duedate", "Due Date", "date", None),
+            ("parent", "Parent", "issuelink", None),
+            ("timetracking", "Time tracking", "timetracking", None),
+        ]
+        .into_iter()
+        .map(|(id, name, field_type, items)| Field {
+            id: id.to_string(),
+            name: name.to_string(),
+            custom: false,
+            schema: Some(FieldSchema {
+                field_type: field_type.to_string(),
+                items: items.map(str::to_string),
+                system: Some(id.to_string()),
+                custom: None,
+            }),
+        })
+        .collect();
+
+        Self {
+            api_version: ApiVersion::V2,
+            state: Mutex::new(State {
+                server_info: ServerInfo {
+                    base_url: BASE_URL.to_string(),
+                    version: "1001.0.0-SNAPSHOT".to_string(),
+                    version_numbers: vec![1001, 0, 0],
+                    deployment_type: DeploymentType::Cloud,
+                    server_title: "Fake JIRA".to_string(),
+                },
+                projects: Vec::new(),
+                fields,
+                users: Vec::new(),
+                issues: Vec::new(),
+                next_id: 0,
+            }),
+        }
+    }
+
+    /// Answers as if requests were sent to `api_version`: rich text is stored as
+    /// [ADF](adf/index.html) with version 3.
+    pub fn with_api_version(mut self, api_version: ApiVersion) -> Self {
+        self.api_version = api_version;
+        self
+    }
+
+    /// Reports and adapts issues to another deployment, e.g. Server, whose users are referred to
+    /// by name.
+    pub fn set_deployment(&self, deployment: DeploymentType) -> &Self {
+        self.state().server_info.deployment_type = deployment;
+        self
+    }
+
+    /// Adds a project, by key, with issue types by name.
+    pub fn add_project(&self, key: &str, issue_types: &[&str]) -> &Self {
+        let mut state = self.state();
+        let id = 10000 + state.projects.len() as u64;
+        let issue_types = issue_types
+            .iter()
+            .enumerate()
+            .map(|(i, name)| IssueType {
+                id: format!("{}{}", id, i + 1),
+                name: name.to_string(),
+                subtask: false,
+            })
+            .collect();
+        state.projects.push(Project {
+            id: id.to_string(),
+            key: key.to_string(),
+            name: key.to_string(),
+            components: Vec::new(),
+            issue_types,
+            roles: BTreeMap::new(),
+        });
+        self
+    }
+
+    /// Adds a field, e.g. a custom field, to the create screen of every issue type.
+    pub fn add_field(&self, field: Field) -> &Self {
+        self.state().fields.push(field);
+        self
+    }
+
+    /// Adds a user that [`find_users`](trait.JiraApi.html#tymethod.find_users) can find by name,
+    /// display name or email address.
+    pub fn add_user(&self, user: User) -> &Self {
+        self.state().users.push(user);
+        self
+    }
+
+    /// The issues created so far, in order.
+    pub fn issues(&self) -> Vec<Issue> {
+        self.state().issues.clone()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    // Validates an adapted issue and stores it.
+    fn create(&self, issue: &JiraIssue) -> Result<CreatedIssue, Error> {
+        let mut state = self.state();
+        let createmeta = state.createmeta(None);
+        let violations = validate_fields(std::slice::from_ref(&issue.fields), &createmeta);
+        if !violations.is_empty() {
+            return Err(Error::Api {
+                status: 400,
+                error_messages: Vec::new(),
+                field_errors: violations
+                    .into_iter()
+                    .map(|v| (v.field, v.problem))
+                    .collect(),
+                request_id: None,
+            });
+        }
+
+        let issue = match self.api_version {
+            ApiVersion::V2 => issue.clone(),
+            ApiVersion::V3 => issue.to_adf(),
+        };
+        let project = &issue.fields["project"];
+        let project_key = state
+            .projects
+            .iter()
+            .find(|p| project["key"] == p.key.as_str() || project["id"] == p.id.as_str())
+            .map(|p| p.key.clone())
+            .unwrap_or_default();
+        let number = state
+            .issues
+            .iter()
+            .filter(|i| i.key.starts_with(&format!("{}-", project_key)))
+            .count()
+            + 1;
+
+        state.next_id += 1;
+        let id = (10000 + state.next_id).to_string();
+        let created = CreatedIssue {
+            id: id.clone(),
+            key: format!("{}-{}", project_key, number),
+            self_url: format!("{}/rest/api/2/issue/{}", BASE_URL, id),
+        };
+        let mut fields: BTreeMap<String, Value> = match issue.fields {
+            Value::Object(fields) => fields.into_iter().collect(),
+            _ => BTreeMap::new(),
+        };
+        fields.insert("status".to_string(), json!({ "name": "To Do" }));
+        let now = Utc::now().format(TIMESTAMP_FORMAT).to_string();
+        fields.insert("created".to_string(), json!(now));
+        fields.insert("updated".to_string(), json!(now));
+        state.issues.push(Issue {
+            id: created.id.clone(),
+            key: created.key.clone(),
+            self_url: created.self_url.clone(),
+            fields,
+        });
+
+        Ok(created)
+    }
+}
+
+impl State {
+    // The create metadata of the projects with a key or id, or of every project: every field is on
+    // every issue type's screen.
+    fn createmeta(&self, key_or_id: Option<&str>) -> CreateMeta {
+        let screen: BTreeMap<String, FieldMeta> = self
+            .fields
+            .iter()
+            .map(|field| {
+                let meta = FieldMeta {
+                    required: REQUIRED_FIELDS.contains(&field.id.as_str()),
+                    name: field.name.clone(),
+                    schema: field.schema.clone().unwrap_or_default(),
+                    has_default_value: false,
+                    allowed_values: Vec::new(),
+                };
+                (field.id.clone(), meta)
+            })
+            .collect();
+
+        let projects = self
+            .projects
+            .iter()
+            .filter(|p| key_or_id.is_none_or(|k| p.key == k || p.id == k))
+            .map(|p| CreateMetaProject {
+                id: p.id.clone(),
+                key: p.key.clone(),
+                name: p.name.clone(),
+                issuetypes: p
+                    .issue_types
+                    .iter()
+                    .map(|t| CreateMetaIssueType {
+                        id: t.id.clone(),
+                        name: t.name.clone(),
+                        fields: screen.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        CreateMeta { projects }
+    }
+}
+
+impl JiraApi for FakeJira {
+    fn api_version(&self) -> ApiVersion {
+        self.api_version
+    }
+
+    fn create_issue(&self, issue: &JiraIssue) -> Result<CreatedIssue, Error> {
+        self.create(issue)
+    }
+
+    fn bulk_create_chunked(
+        &self,
+        issues: &[JiraIssue],
+        chunk_size: usize,
+    ) -> Result<BulkCreateResponse, Error> {
+        if chunk_size == 0 {
+            return Err(endpoints::invalid_chunk_size());
+        }
+
+        let mut response = BulkCreateResponse::default();
+        for (i, issue) in issues.iter().enumerate() {
+            match self.create(issue) {
+                Ok(created) => response.issues.push(created),
+                Err(Error::Api {
+                    status,
+                    error_messages,
+                    field_errors,
+                    ..
+                }) => response.errors.push(BulkCreateError {
+                    status,
+                    element_errors: ElementErrors {
+                        error_messages,
+                        errors: field_errors,
+                    },
+                    failed_element_number: i,
+                }),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(response)
+    }
+
+    fn get_project(&self, project: &str) -> Result<Project, Error> {
+        self.state()
+            .projects
+            .iter()
+            .find(|p| p.key == project || p.id == project)
+            .cloned()
+            .ok_or_else(|| not_found(format!("No project could be found with key '{}'.", project)))
+    }
+
+    fn get_issue(&self, issue: &str) -> Result<Issue, Error> {
+        self.state()
+            .issues
+            .iter()
+            .find(|i| i.key == issue || i.id == issue)
+            .cloned()
+            .ok_or_else(|| {
+                not_found("Issue does not exist or you do not have permission to see it.")
+            })
+    }
+
+    fn search(&self, jql: &str, start_at: u64, max_results: u64) -> Result<SearchResults, Error> {
+        let clauses = parse_jql(jql)?;
+        let state = self.state();
+        for clause in clauses.iter() {
+            clause.check(&state.fields)?;
+        }
+
+        let mut matching: Vec<&Issue> = Vec::new();
+        for issue in state.issues.iter() {
+            if clauses
+                .iter()
+                .all(|clause| clause.matches(issue, &state.fields))
+            {
+                matching.push(issue);
+            }
+        }
+
+        Ok(SearchResults {
+            start_at,
+            max_results,
+            total: matching.len() as u64,
+            issues: matching
+                .into_iter()
+                .skip(start_at as usize)
+                .take(max_results as usize)
+                .cloned()
+                .collect(),
+        })
+    }
+
+    fn search_page_with(
+        &self,
+        jql: &str,
+        page_token: Option<&str>,
+        options: &SearchOptions,
+    ) -> Result<SearchPage, Error> {
+        let start_at = endpoints::start_at(page_token)?;
+        let mut results = self.search(jql, start_at, options.page_size)?;
+
+        let all = ["*all", "*navigable"];
+        if !options.fields.is_empty() && !options.fields.iter().any(|f| all.contains(&f.as_str())) {
+            for issue in results.issues.iter_mut() {
+                issue.fields.retain(|id, _| options.fields.contains(id));
+            }
+        }
+        Ok(endpoints::search_page(results))
+    }
+
+    fn find_users(&self, query: &str) -> Result<Vec<User>, Error> {
+        let query = query.to_lowercase();
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .filter(|user| {
+                [
+                    user.name.as_deref(),
+                    user.email_address.as_deref(),
+                    Some(user.display_name.as_str()),
+                ]
+                .iter()
+                .flatten()
+                .any(|s| s.to_lowercase().contains(&query))
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn get_fields(&self) -> Result<Vec<Field>, Error> {
+        Ok(self.state().fields.clone())
+    }
+
+    fn get_createmeta(&self, project: &str) -> Result<CreateMeta, Error> {
+        Ok(self.state().createmeta(Some(project)))
+    }
+
+    fn server_info(&self) -> Result<ServerInfo, Error> {
+        Ok(self.state().server_info.clone())
+    }
+
+    fn adapt_issues(&self, issues: &[JiraIssue]) -> Result<Vec<JiraIssue>, Error> {
+        if !deployment::needs_adapting(issues, self.api_version) {
+            return Ok(issues.to_vec());
+        }
+
+        let deployment = self.state().server_info.deployment_type;
+        let fields = self.get_fields()?;
+        let adapter = Adapter {
+            deployment,
+            api_version: self.api_version,
+            fields: &fields,
+        };
+        let mut users = BTreeMap::new();
+        for query in adapter.user_queries(issues) {
+            let found = self.find_users(&query)?;
+            users.insert(query, found);
+        }
+
+        Ok(adapter.adapt_all(issues, &users))
+    }
+}
+
+// The operators of a search clause.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Equals,
+    NotEquals,
+    In,
+    NotIn,
+    Contains,
+    NotContains,
+    GreaterOrEqual,
+    Greater,
+    LessOrEqual,
+    Less,
+}
+
+// A clause of a search, e.g. `status in ("To Do", "Done")`.
+#[derive(Debug)]
+struct Clause {
+    // The field as it was written, e.g. `labels`, `Story Points` or `cf[10010]`.
+    field: String,
+    operator: Operator,
+    values: Vec<String>,
+}
+
+// A token of a query: a word, e.g. a field, keyword or unquoted value, a quoted value, an
+// operator, or punctuation.
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Operator(&'static str),
+    Open,
+    Close,
+    Comma,
+}
+
+impl Clause {
+    // Checks that the field exists, and that the operator applies to it.
+    fn check(&self, fields: &[Field]) -> Result<(), Error> {
+        let id = match field_id(&self.field, fields) {
+            Some(id) => id,
+            None => {
+                return Err(jql_error(format!(
+                    "Field '{}' does not exist or you do not have permission to view it.",
+                    self.field
+                )))
+            }
+        };
+
+        let supported = match self.operator {
+            Operator::Contains | Operator::NotContains => {
+                id == "text" || field_type(&id, fields) == Some("string")
+            }
+            Operator::GreaterOrEqual
+            | Operator::Greater
+            | Operator::LessOrEqual
+            | Operator::Less => matches!(id.as_str(), "created" | "updated" | "duedate"),
+            _ => id != "text",
+        };
+        if !supported {
+            return Err(jql_error(format!(
+                "The operator '{}' is not supported by the '{}' field.",
+                operator_name(self.operator),
+                self.field
+            )));
+        }
+
+        match self.operator {
+            Operator::GreaterOrEqual
+            | Operator::Greater
+            | Operator::LessOrEqual
+            | Operator::Less => match date(&self.values[0], Utc::now()) {
+                Some(_) => Ok(()),
+                None => Err(jql_error(format!(
+                    "Date value '{}' for field '{}' is invalid.",
+                    self.values[0], self.field
+                ))),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    // Whether an issue matches the clause, which has been checked.
+    fn matches(&self, issue: &Issue, fields: &[Field]) -> bool {
+        let id = field_id(&self.field, fields).unwrap_or_default();
+        let equals = |values: &[String]| {
+            values
+                .iter()
+                .any(|v| self.values.iter().any(|c| v.eq_ignore_ascii_case(c)))
+        };
+
+        match self.operator {
+            Operator::Equals | Operator::In => equals(&field_values(issue, &id)),
+            // JIRA leaves out issues without a value
+            Operator::NotEquals | Operator::NotIn => {
+                let values = field_values(issue, &id);
+                !values.is_empty() && !equals(&values)
+            }
+            Operator::Contains => contains_words(&field_text(issue, &id), &self.values[0]),
+            Operator::NotContains => !contains_words(&field_text(issue, &id), &self.values[0]),
+            operator => {
+                let now = Utc::now();
+                let (value, bound) = match (issue_date(issue, &id), date(&self.values[0], now)) {
+                    (Some(value), Some(bound)) => (value, bound),
+                    _ => return false,
+                };
+                match operator {
+                    Operator::GreaterOrEqual => value >= bound,
+                    Operator::Greater => value > bound,
+                    Operator::LessOrEqual => value <= bound,
+                    _ => value < bound,
+                }
+            }
+        }
+    }
+}
+
+// The clauses of a query, joined with `AND`, up to its `ORDER BY`, which is ignored. Queries that
+// aren't only such clauses are an error.
+fn parse_jql(jql: &str) -> Result<Vec<Clause>, Error> {
+    let invalid = || jql_error(format!("Error in the JQL Query: '{}'", jql.trim()));
+    let is = |token: &Option<Token>, keyword: &str| matches!(token, Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword));
+
+    let mut tokens = tokens(jql)?.into_iter().peekable();
+    let mut clauses = Vec::new();
+    loop {
+        let field = match tokens.next() {
+            None => break,
+            token if is(&token, "order") && clauses.is_empty() => break,
+            Some(Token::Word(field)) | Some(Token::Quoted(field)) => field,
+            Some(_) => return Err(invalid()),
+        };
+
+        let token = tokens.next();
+        let operator = match token {
+            Some(Token::Operator(op)) => match op {
+                "=" => Operator::Equals,
+                "!=" => Operator::NotEquals,
+                "~" => Operator::Contains,
+                "!~" => Operator::NotContains,
+                ">=" => Operator::GreaterOrEqual,
+                ">" => Operator::Greater,
+                "<=" => Operator::LessOrEqual,
+                _ => Operator::Less,
+            },
+            _ if is(&token, "in") => Operator::In,
+            _ if is(&token, "not") && is(&tokens.next(), "in") => Operator::NotIn,
+            _ => return Err(invalid()),
+        };
+
+        let values = match operator {
+            Operator::In | Operator::NotIn => list(&mut tokens).ok_or_else(invalid)?,
+            _ => vec![value(tokens.next()).ok_or_else(invalid)?],
+        };
+        clauses.push(Clause {
+            field,
+            operator,
+            values,
+        });
+
+        let token = tokens.next();
+        match token {
+            None => break,
+            _ if is(&token, "and") => continue,
+            _ if is(&token, "order") => break,
+            _ => return Err(invalid()),
+        }
+    }
+
+    Ok(clauses)
+}
+
+// The values of an `in` list, e.g. `("To Do", Done)`.
+fn list(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Option<Vec<String>> {
+    if tokens.next() != Some(Token::Open) {
+        return None;
+    }
+    let mut values = Vec::new();
+    loop {
+        values.push(value(tokens.next())?);
+        match tokens.next()? {
+            Token::Comma => continue,
+            Token::Close => return Some(values),
+            _ => return None,
+        }
+    }
+}
+
+fn value(token: Option<Token>) -> Option<String> {
+    match token? {
+        Token::Word(value) | Token::Quoted(value) => Some(value),
+        _ => None,
+    }
+}
+
+// Splits a query into tokens. Quoted values may be in single or double quotes, in which a
+// backslash escapes the next character.
+fn tokens(jql: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = jql.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            '"' | '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => value.extend(chars.next()),
+                        Some(quote) if quote == c => break,
+                        Some(other) => value.push(other),
+                        None => {
+                            return Err(jql_error(format!(
+                                "Error in the JQL Query: the quoted string {}{} is not closed.",
+                                c, value
+                            )))
+                        }
+                    }
+                }
+                Token::Quoted(value)
+            }
+            '=' | '!' | '~' | '<' | '>' => {
+                let followed = chars.next_if_eq(&'=').or_else(|| chars.next_if_eq(&'~'));
+                Token::Operator(match (c, followed) {
+                    ('=', None) => "=",
+                    ('~', None) => "~",
+                    ('<', None) => "<",
+                    ('>', None) => ">",
+                    ('!', Some('=')) => "!=",
+                    ('!', Some('~')) => "!~",
+                    ('<', Some('=')) => "<=",
+                    ('>', Some('=')) => ">=",
+                    _ => {
+                        return Err(jql_error(format!(
+                            "Error in the JQL Query: '{}' is not an operator.",
+                            c
+                        )))
+                    }
+                })
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|c| !c.is_whitespace() && !"\"'(),=!~<>".contains(*c))
+                {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+// The id of a field a clause refers to, by its id, an alias, its name, or `cf[<number>]`.
+fn field_id(field: &str, fields: &[Field]) -> Option<String> {
+    let lower = field.to_lowercase();
+    let id = match lower.as_str() {
+        "issuekey" => "key".to_string(),
+        "type" => "issuetype".to_string(),
+        "key" | "text" | "status" | "created" | "updated" => lower,
+        _ => match lower
+            .strip_prefix("cf[")
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            Some(number) => format!("customfield_{}", number),
+            None => fields
+                .iter()
+                .find(|f| f.id.eq_ignore_ascii_case(field) || f.name.eq_ignore_ascii_case(field))?
+                .id
+                .clone(),
+        },
+    };
+    match id.starts_with("customfield_") && !fields.iter().any(|f| f.id == id) {
+        true => None,
+        false => Some(id),
+    }
+}
+
+fn field_type<'a>(id: &str, fields: &'a [Field]) -> Option<&'a str> {
+    let field = fields.iter().find(|f| f.id == id)?;
+    Some(field.schema.as_ref()?.field_type.as_str())
+}
+
+// The values of an issue's field that `=` and `in` compare with: strings and numbers as they
+// are, and the key, id, name, value or account id of objects, e.g. projects and users.
+fn field_values(issue: &Issue, id: &str) -> Vec<String> {
+    if id == "key" {
+        return vec![issue.key.clone(), issue.id.clone()];
+    }
+
+    let values = match issue.fields.get(id) {
+        Some(Value::Array(values)) => values.iter().collect(),
+        Some(value) => vec![value],
+        None => Vec::new(),
+    };
+    values
+        .into_iter()
+        .flat_map(|value| match value {
+            Value::String(s) => vec![s.clone()],
+            Value::Number(n) => vec![n.to_string()],
+            Value::Object(o) => ["key", "id", "name", "value", "accountId"]
+                .iter()
+                .filter_map(|k| o.get(*k).and_then(Value::as_str).map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+// The text of an issue's field, or of its summary, description and environment for `text`, with
+// ADF documents as their text.
+fn field_text(issue: &Issue, id: &str) -> String {
+    let ids: Vec<&str> = match id {
+        "text" => TEXT_FIELDS.to_vec(),
+        id => vec![id],
+    };
+    ids.iter()
+        .filter_map(|id| issue.fields.get(*id))
+        .map(adf::to_text)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Whether a text has every word of a text search, in any case. The escapes of JIRA's text
+// operators, e.g. `\-`, are removed, and a trailing `*` matches any ending.
+fn contains_words(text: &str, search: &str) -> bool {
+    let text = text.to_lowercase();
+    search
+        .replace('\\', "")
+        .to_lowercase()
+        .split_whitespace()
+        .all(|word| text.contains(word.trim_end_matches('*')))
+}
+
+// The date and time of an issue's `created`, `updated` or `duedate` field.
+fn issue_date(issue: &Issue, id: &str) -> Option<DateTime<Utc>> {
+    let value = issue.fields.get(id)?.as_str()?;
+    match DateTime::parse_from_str(value, TIMESTAMP_FORMAT) {
+        Ok(timestamp) => Some(timestamp.with_timezone(&Utc)),
+        Err(_) => date(value, Utc::now()),
+    }
+}
+
+// A date of a clause, in UTC: `2026-01-01`, `2026/01/01`, `2026-01-01 09:00`, or relative to
+// `now`, e.g. `-7d`, `2w`, `-4h` or `-30m`.
+fn date(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    for format in ["%Y-%m-%d %H:%M", "%Y/%m/%d %H:%M"] {
+        if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(date.and_utc());
+        }
+    }
+    for format in ["%Y-%m-%d", "%Y/%m/%d"] {
+        if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+            return Some(date.and_hms_opt(0, 0, 0)?.and_utc());
+        }
+    }
+
+    let (amount, unit) = value.split_at(value.char_indices().last()?.0);
+    let amount: i64 = amount.parse().ok()?;
+    let offset = match unit {
+        "w" => Duration::weeks(amount),
+        "d" => Duration::days(amount),
+        "h" => Duration::hours(amount),
+        "m" => Duration::minutes(amount),
+        _ => return None,
+    };
+    Some(now + offset)
+}
+
+fn operator_name(operator: Operator) -> &'static str {
+    match operator {
+        Operator::Equals => "=",
+        Operator::NotEquals => "!=",
+        Operator::In => "in",
+        Operator::NotIn => "not in",
+        Operator::Contains => "~",
+        Operator::NotContains => "!~",
+        Operator::GreaterOrEqual => ">=",
+        Operator::Greater => ">",
+        Operator::LessOrEqual => "<=",
+        Operator::Less => "<",
+    }
+}
+
+fn jql_error(message: String) -> Error {
+    Error::Api {
+        status: 400,
+        error_messages: vec![message],
+        field_errors: BTreeMap::new(),
+        request_id: None,
+    }
+}
+
+fn not_found(message: impl Into<String>) -> Error {
+    Error::Api {
+        status: 404,
+        error_messages: vec![message.into()],
+        field_errors: BTreeMap::new(),
+        request_id: None,
+    }
+}
@@ -0,0 +1,164 @@
+// The `fields` of an issue, built with typed setters rather than by hand.
+use crate::models::JiraIssue;
+use crate::update::IssueUpdate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// The fields of an issue to create, keyed by field id, with a setter for each common system field
+/// and [`custom`](#method.custom) for any other field. Serializes to the JSON that the equivalent
+/// template columns would, and converts into a [`JiraIssue`](struct.JiraIssue.html) or its
+/// `fields`.
+/// ```
+/// use jiragen::{IssueFields, JiraIssue};
+/// use serde_json::json;
+///
+/// let fields = IssueFields::new()
+///   .project("REL")
+///   .issue_type("Task")
+///   .summary("Tag the release")
+///   .labels(&["release"])
+///   .components(&["Backend"])
+///   .assignee("5b10a2844c20165700ede21g")
+///   .time_tracking("2h", None)
+///   .custom("customfield_10010", 3);
+///
+/// assert_eq!(serde_json::to_value(&fields)?, json!({
+///   "project": { "key": "REL" },
+///   "issuetype": { "name": "Task" },
+///   "summary": "Tag the release",
+///   "labels": ["release"],
+///   "components": [{ "name": "Backend" }],
+///   "assignee": { "accountId": "5b10a2844c20165700ede21g" },
+///   "timetracking": { "originalEstimate": "2h" },
+///   "customfield_10010": 3,
+/// }));
+///
+/// let issue: JiraIssue = fields.into();
+/// assert_eq!(issue.fields["summary"], "Tag the release");
+/// # Ok::<(), jiragen::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct IssueFields {
+    fields: Map<String, Value>,
+}
+
+impl IssueFields {
+    /// Fields without any values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The project, by key.
+    pub fn project(self, key: &str) -> Self {
+        self.custom("project", json!({ "key": key }))
+    }
+
+    /// The issue type, by name, e.g. `Task`.
+    pub fn issue_type(self, name: &str) -> Self {
+        self.custom("issuetype", json!({ "name": name }))
+    }
+
+    pub fn summary(self, summary: &str) -> Self {
+        self.custom("summary", summary)
+    }
+
+    /// The description, in JIRA's wiki markup, or plain text or Markdown that is converted to
+    /// [ADF](adf/index.html) with version 3 of the API.
+    pub fn description(self, description: &str) -> Self {
+        self.custom("description", description)
+    }
+
+    /// The priority, by name, e.g. `High`.
+    pub fn priority(self, name: &str) -> Self {
+        self.custom("priority", json!({ "name": name }))
+    }
+
+    pub fn labels(self, labels: &[&str]) -> Self {
+        self.custom("labels", json!(labels))
+    }
+
+    /// The components, by name.
+    pub fn components(self, names: &[&str]) -> Self {
+        self.custom("components", named(names))
+    }
+
+    /// The versions the issue is fixed in, by name.
+    pub fn fix_versions(self, names: &[&str]) -> Self {
+        self.custom("fixVersions", named(names))
+    }
+
+    /// The assignee, by account id. On JIRA Server, the clients refer to the user by name instead.
+    pub fn assignee(self, account_id: &str) -> Self {
+        self.custom("assignee", json!({ "accountId": account_id }))
+    }
+
+    /// The assignee, by name or email address. On JIRA Cloud,
+    /// [`adapt_issues`](struct.JiraClient.html#method.adapt_issues) looks up the user's account id.
+    pub fn assignee_name(self, name: &str) -> Self {
+        self.custom("assignee", json!({ "name": name }))
+    }
+
+    /// The due date, as `YYYY-MM-DD`.
+    pub fn due_date(self, date: &str) -> Self {
+        self.custom("duedate", date)
+    }
+
+    /// The parent issue, by key: the epic of a standard issue on JIRA Cloud, or the issue a
+    /// sub-task belongs to. Use [`EPIC_FIELD`](constant.EPIC_FIELD.html) to set an epic on any
+    /// deployment.
+    pub fn parent(self, key: &str) -> Self {
+        self.custom("parent", json!({ "key": key }))
+    }
+
+    /// The original and remaining estimates, in JIRA's duration format, e.g. `1h 30m`.
+    pub fn time_tracking(self, original_estimate: &str, remaining_estimate: Option<&str>) -> Self {
+        let mut time_tracking = json!({ "originalEstimate": original_estimate });
+        if let Some(remaining_estimate) = remaining_estimate {
+            time_tracking["remainingEstimate"] = json!(remaining_estimate);
+        }
+        self.custom("timetracking", time_tracking)
+    }
+
+    /// Sets any field, by id, e.g. `custom("customfield_10010", 3)`, replacing its value.
+    pub fn custom(mut self, id: &str, value: impl Into<Value>) -> Self {
+        self.fields.insert(id.to_string(), value.into());
+        self
+    }
+
+    /// The value of a field, by id.
+    pub fn get(&self, id: &str) -> Option<&Value> {
+        self.fields.get(id)
+    }
+
+    /// An issue with these fields and an update.
+    pub fn with_update(self, update: IssueUpdate) -> JiraIssue {
+        JiraIssue {
+            update: match update.is_empty() {
+                true => None,
+                false => Some(update.into()),
+            },
+            fields: self.into(),
+        }
+    }
+}
+
+impl From<IssueFields> for Value {
+    fn from(fields: IssueFields) -> Self {
+        Value::Object(fields.fields)
+    }
+}
+
+impl From<IssueFields> for JiraIssue {
+    fn from(fields: IssueFields) -> Self {
+        JiraIssue {
+            update: None,
+            fields: fields.into(),
+        }
+    }
+}
+
+// References to values by name, e.g. components.
+fn named(names: &[&str]) -> Value {
+    names.iter().map(|name| json!({ "name": name })).collect()
+}
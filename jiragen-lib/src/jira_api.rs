@@ -0,0 +1,166 @@
+// The operations of a JIRA site, as a trait that `JiraClient` and `FakeJira` implement.
+use crate::api::{JiraClient, BULK_CREATE_LIMIT};
+use crate::config::ApiVersion;
+use crate::error::Error;
+use crate::models::{
+    BulkCreateResponse, CreateMeta, CreatedIssue, Field, Issue, JiraIssue, Project, SearchPage,
+    SearchResults, ServerInfo, User,
+};
+use crate::search::{SearchIter, SearchOptions};
+
+/// The typed operations of a JIRA site. [`JiraClient`](struct.JiraClient.html) sends them to
+/// JIRA, and [`FakeJira`](struct.FakeJira.html) answers them in memory, so that code written
+/// against the trait, e.g. `fn release(jira: &impl JiraApi)` or `Box<dyn JiraApi>`, can be tested
+/// without HTTP. See each method of `JiraClient` for how it behaves.
+/// ```
+/// use jiragen::{FakeJira, IssueFields, JiraApi};
+///
+/// // Creates the release's issue, and returns its key.
+/// fn release(jira: &dyn JiraApi, version: &str) -> Result<String, jiragen::Error> {
+///   let fields = IssueFields::new()
+///     .project("REL")
+///     .issue_type("Task")
+///     .summary(&format!("Release {}", version));
+///   Ok(jira.create_issue(&fields.into())?.key)
+/// }
+///
+/// let jira = FakeJira::new();
+/// jira.add_project("REL", &["Task"]);
+///
+/// assert_eq!(release(&jira, "1.0")?, "REL-1");
+/// assert_eq!(jira.issues()[0].fields["summary"], "Release 1.0");
+/// # Ok::<(), jiragen::Error>(())
+/// ```
+pub trait JiraApi {
+    /// The version of the REST API requests are sent to.
+    fn api_version(&self) -> ApiVersion;
+
+    fn create_issue(&self, issue: &JiraIssue) -> Result<CreatedIssue, Error>;
+
+    fn bulk_create(&self, issues: &[JiraIssue]) -> Result<BulkCreateResponse, Error> {
+        self.bulk_create_chunked(issues, BULK_CREATE_LIMIT)
+    }
+
+    fn bulk_create_chunked(
+        &self,
+        issues: &[JiraIssue],
+        chunk_size: usize,
+    ) -> Result<BulkCreateResponse, Error>;
+
+    fn get_project(&self, project: &str) -> Result<Project, Error>;
+
+    fn get_issue(&self, issue: &str) -> Result<Issue, Error>;
+
+    fn search(&self, jql: &str, start_at: u64, max_results: u64) -> Result<SearchResults, Error>;
+
+    fn search_page(
+        &self,
+        jql: &str,
+        page_token: Option<&str>,
+        max_results: u64,
+    ) -> Result<SearchPage, Error> {
+        let options = SearchOptions {
+            page_size: max_results,
+            ..Default::default()
+        };
+        self.search_page_with(jql, page_token, &options)
+    }
+
+    fn search_page_with(
+        &self,
+        jql: &str,
+        page_token: Option<&str>,
+        options: &SearchOptions,
+    ) -> Result<SearchPage, Error>;
+
+    /// Iterates over every issue matching a JQL query; see
+    /// [`JiraClient::search_iter`](struct.JiraClient.html#method.search_iter).
+    fn search_iter(&self, jql: impl Into<String>, options: SearchOptions) -> SearchIter<'_>
+    where
+        Self: Sized,
+    {
+        SearchIter::new(self, jql, options)
+    }
+
+    fn find_users(&self, query: &str) -> Result<Vec<User>, Error>;
+
+    fn get_fields(&self) -> Result<Vec<Field>, Error>;
+
+    fn get_createmeta(&self, project: &str) -> Result<CreateMeta, Error>;
+
+    fn server_info(&self) -> Result<ServerInfo, Error>;
+
+    fn adapt_issues(&self, issues: &[JiraIssue]) -> Result<Vec<JiraIssue>, Error>;
+}
+
+impl JiraApi for JiraClient {
+    fn api_version(&self) -> ApiVersion {
+        self.config().api_version
+    }
+
+    fn create_issue(&self, issue: &JiraIssue) -> Result<CreatedIssue, Error> {
+        JiraClient::create_issue(self, issue)
+    }
+
+    fn bulk_create(&self, issues: &[JiraIssue]) -> Result<BulkCreateResponse, Error> {
+        JiraClient::bulk_create(self, issues)
+    }
+
+    fn bulk_create_chunked(
+        &self,
+        issues: &[JiraIssue],
+        chunk_size: usize,
+    ) -> Result<BulkCreateResponse, Error> {
+        JiraClient::bulk_create_chunked(self, issues, chunk_size)
+    }
+
+    fn get_project(&self, project: &str) -> Result<Project, Error> {
+        JiraClient::get_project(self, project)
+    }
+
+    fn get_issue(&self, issue: &str) -> Result<Issue, Error> {
+        JiraClient::get_issue(self, issue)
+    }
+
+    fn search(&self, jql: &str, start_at: u64, max_results: u64) -> Result<SearchResults, Error> {
+        JiraClient::search(self, jql, start_at, max_results)
+    }
+
+    fn search_page(
+        &self,
+        jql: &str,
+        page_token: Option<&str>,
+        max_results: u64,
+    ) -> Result<SearchPage, Error> {
+        JiraClient::search_page(self, jql, page_token, max_results)
+    }
+
+    fn search_page_with(
+        &self,
+        jql: &str,
+        page_token: Option<&str>,
+        options: &SearchOptions,
+    ) -> Result<SearchPage, Error> {
+        JiraClient::search_page_with(self, jql, page_token, options)
+    }
+
+    fn find_users(&self, query: &str) -> Result<Vec<User>, Error> {
+        JiraClient::find_users(self, query)
+    }
+
+    fn get_fields(&self) -> Result<Vec<Field>, Error> {
+        JiraClient::get_fields(self)
+    }
+
+    fn get_createmeta(&self, project: &str) -> Result<CreateMeta, Error> {
+        JiraClient::get_createmeta(self, project)
+    }
+
+    fn server_info(&self) -> Result<ServerInfo, Error> {
+        JiraClient::server_info(self)
+    }
+
+    fn adapt_issues(&self, issues: &[JiraIssue]) -> Result<Vec<JiraIssue>, Error> {
+        JiraClient::adapt_issues(self, issues)
+    }
+}
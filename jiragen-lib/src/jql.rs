@@ -0,0 +1,202 @@
+// Building JQL queries, with values quoted and escaped.
+use std::fmt;
+
+// The characters JIRA's text search treats as operators, escaped in `text ~` values.
+const TEXT_OPERATORS: &str = "+-&|!(){}[]^~*?\\:/";
+
+/// The direction of an `ORDER BY` field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+/// A JQL query, built from clauses that are joined with `AND`, and the fields to order issues by.
+/// Values are quoted, so names with spaces and reserved words are safe, and text searches are
+/// escaped. Pass it to [`search_iter`](struct.JiraClient.html#method.search_iter) or convert it to
+/// a `String`.
+/// ```
+/// use jiragen::{Jql, Order};
+///
+/// let jql = Jql::new()
+///   .project("REL")
+///   .status_in(&["To Do", "In Progress"])
+///   .label("release")
+///   .text("crash on \"start\"")
+///   .created_after("2026-01-01")
+///   .created_before("-1d")
+///   .order_by("priority", Order::Desc)
+///   .order_by("created", Order::Asc);
+///
+/// assert_eq!(
+///   jql.to_string(),
+///   r#"project = "REL" AND status in ("To Do", "In Progress") AND labels = "release" AND text ~ "crash on \"start\"" AND created >= "2026-01-01" AND created < "-1d" ORDER BY priority DESC, created ASC"#
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Jql {
+    clauses: Vec<String>,
+    order_by: Vec<(String, Order)>,
+}
+
+impl Jql {
+    /// A query without clauses, which matches every issue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issues in a project, by key or name.
+    pub fn project(self, project: &str) -> Self {
+        self.field("project", project)
+    }
+
+    /// Issues in a status, by name.
+    pub fn status(self, status: &str) -> Self {
+        self.field("status", status)
+    }
+
+    /// Issues in any of the statuses, by name.
+    pub fn status_in(self, statuses: &[&str]) -> Self {
+        self.field_in("status", statuses)
+    }
+
+    /// Issues of an issue type, by name.
+    pub fn issue_type(self, issue_type: &str) -> Self {
+        self.field("issuetype", issue_type)
+    }
+
+    /// Issues with a label.
+    pub fn label(self, label: &str) -> Self {
+        self.field("labels", label)
+    }
+
+    /// Issues with any of the labels.
+    pub fn labels_in(self, labels: &[&str]) -> Self {
+        self.field_in("labels", labels)
+    }
+
+    /// Issues whose summary, description, environment or comments contain the words of a text
+    /// search. JIRA's text operators, e.g. `-` and `*`, are escaped, so they match literally.
+    pub fn text(self, text: &str) -> Self {
+        let mut escaped = String::new();
+        for c in text.chars() {
+            if TEXT_OPERATORS.contains(c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        self.clause(&format!("text ~ {}", quote(&escaped)))
+    }
+
+    /// Issues created on or after a date, e.g. `2026-01-01`, `2026-01-01 09:00` or `-7d`.
+    pub fn created_after(self, date: &str) -> Self {
+        self.clause(&format!("created >= {}", quote(date)))
+    }
+
+    /// Issues created before a date, e.g. `2026-02-01` or `-1d`.
+    pub fn created_before(self, date: &str) -> Self {
+        self.clause(&format!("created < {}", quote(date)))
+    }
+
+    /// Issues updated on or after a date, e.g. `2026-01-01` or `-7d`.
+    pub fn updated_after(self, date: &str) -> Self {
+        self.clause(&format!("updated >= {}", quote(date)))
+    }
+
+    /// Issues updated before a date, e.g. `2026-02-01` or `-1d`.
+    pub fn updated_before(self, date: &str) -> Self {
+        self.clause(&format!("updated < {}", quote(date)))
+    }
+
+    /// Issues whose field, by name or id, e.g. `"Story Points"` or `cf[10010]`, is a value.
+    pub fn field(self, field: &str, value: &str) -> Self {
+        self.clause(&format!("{} = {}", field_name(field), quote(value)))
+    }
+
+    /// Issues whose field, by name or id, is any of the values.
+    pub fn field_in(self, field: &str, values: &[&str]) -> Self {
+        let values: Vec<String> = values.iter().map(|value| quote(value)).collect();
+        self.clause(&format!("{} in ({})", field_name(field), values.join(", ")))
+    }
+
+    /// Adds a clause of JQL as it is, e.g. `assignee = currentUser() OR reporter = currentUser()`,
+    /// which is put in parentheses when the query has other clauses.
+    pub fn clause(mut self, clause: &str) -> Self {
+        let clause = clause.trim();
+        if clause.is_empty() {
+            return self;
+        }
+        self.clauses.push(clause.to_string());
+        self
+    }
+
+    /// Orders issues by a field, after the fields already ordered by.
+    pub fn order_by(mut self, field: &str, order: Order) -> Self {
+        self.order_by.push((field_name(field), order));
+        self
+    }
+}
+
+impl fmt::Display for Jql {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let clauses: Vec<String> = match self.clauses.len() {
+            1 => self.clauses.clone(),
+            _ => self.clauses.iter().map(|c| parenthesize(c)).collect(),
+        };
+        f.write_str(&clauses.join(" AND "))?;
+
+        if !self.order_by.is_empty() {
+            let order_by: Vec<String> = self
+                .order_by
+                .iter()
+                .map(|(field, order)| match order {
+                    Order::Asc => format!("{} ASC", field),
+                    Order::Desc => format!("{} DESC", field),
+                })
+                .collect();
+            if !self.clauses.is_empty() {
+                f.write_str(" ")?;
+            }
+            write!(f, "ORDER BY {}", order_by.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Jql> for String {
+    fn from(jql: Jql) -> Self {
+        jql.to_string()
+    }
+}
+
+// A value as a JQL string, in double quotes, with quotes and backslashes escaped.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// A field name as it is when it is a single word or id, e.g. `labels` or `cf[10010]`, and quoted
+// otherwise, e.g. `"Story Points"`.
+fn field_name(field: &str) -> String {
+    match field
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_.[]".contains(c))
+    {
+        true => field.to_string(),
+        false => quote(field),
+    }
+}
+
+// A clause in parentheses when it may have more than one term, e.g. `a = 1 OR b = 2`, so that it
+// is one term of the query's `AND`.
+fn parenthesize(clause: &str) -> String {
+    let lower = clause.to_lowercase();
+    let compound = [" and ", " or ", " not "]
+        .iter()
+        .any(|op| lower.contains(op))
+        || lower.starts_with("not ");
+    match compound {
+        true => format!("({})", clause),
+        false => clause.to_string(),
+    }
+}
@@ -1,20 +1,31 @@
 //! `jiragen` is a collection of utilities for sending requests to JIRA. It includes functionality for sending bulk issue creation from a .csv file.
-//! ```no_run
+//! ```
 //! use csv::{Reader, StringRecord};
-//! use jiragen::{Config, csv_to_json, JiraClient, JiraIssue};
+//! use jiragen::{AuthType, Config, csv_to_json, JiraClient, JiraIssue};
 //! use serde_json::json;
 //!
+//! # let mut server = mockito::Server::new();
+//! # let mock = server
+//! #   .mock("POST", "/rest/api/2/issue/bulk")
+//! #   .match_header("authorization", "Basic dXNlcjprZXk=")
+//! #   .with_status(201)
+//! #   .with_body(r#"{"issues":[{"id":"10000","key":"REL-1","self":""}],"errors":[]}"#)
+//! #   .create();
+//! # let my_jira_url = server.url();
 //! // Configure & initialize JIRA client
 //! let config = Config {
-//!   jira_url: "my-jira.com".to_string(),
+//!   jira_url: my_jira_url, // e.g. "https://my-jira.com"
 //!   jira_user: "user".to_string(),
 //!   jira_key: "key".to_string(),
+//!   auth: AuthType::Basic,
+//!   ..Default::default()
 //! };
-//! let jira = JiraClient::new(config);
+//! let jira = JiraClient::new(config)?;
 //!
 //! // Prepare CSV data
-//! let mut csv_reader = Reader::from_path("jiragen_issues.csv").unwrap();
-//! let ids_record = csv_reader.headers().unwrap().clone();
+//! let issues_csv = "summary,project.key\nSummary,Project\nA Summary,REL\n";
+//! let mut csv_reader = Reader::from_reader(issues_csv.as_bytes());
+//! let ids_record = csv_reader.headers()?.clone();
 //! let ids: Vec<&str> = ids_record.iter().collect();
 //!
 //! let mut csv_records = csv_reader.into_records();
@@ -22,12 +33,9 @@
 //!
 //! // create bulk issues to send starting on line 3
 //! let filtered_csv_records: Vec<StringRecord> = csv_records
-//!   .filter_map(|record_result| match record_result {
-//!     Ok(result) => Some(result),
-//!     Err(_) => None,
-//!   })
+//!   .filter_map(|record_result| record_result.ok())
 //!   .collect();
-//! let json_values = csv_to_json(ids, filtered_csv_records).unwrap();
+//! let json_values = csv_to_json(ids, filtered_csv_records)?;
 //!
 //! let issues_to_create: Vec<JiraIssue> = json_values
 //!   .into_iter()
@@ -37,25 +45,99 @@
 //!   })
 //!   .collect();
 //!
-//! let bulk_issue_create_request = jira.init_request("POST", "/rest/api/2/issue/bulk");
+//! let bulk_issue_create_request = jira.init_request("POST", "/rest/api/2/issue/bulk")?;
 //! let request_json = json!({ "issueUpdates": issues_to_create });
 //!
-//! let mut response = bulk_issue_create_request
+//! let response = bulk_issue_create_request
 //!   .body(request_json.to_string())
-//!   .send();
+//!   .send()?;
+//! assert!(response.status().is_success());
+//! # mock.assert();
+//! # Ok::<(), jiragen::Error>(())
 //! ```
+//!
+//! The same request can be sent with [`JiraClient::bulk_create`](struct.JiraClient.html#method.bulk_create),
+//! which also deserializes JIRA's response.
+//!
+//! Issues can also be built without a template, with [`IssueFields`](struct.IssueFields.html) and
+//! [`IssueUpdate`](struct.IssueUpdate.html).
 
 #[macro_use]
 extern crate lazy_static;
 
+pub mod adf;
+
 mod api;
-pub use api::{JiraClient, JiraIssue};
+pub use api::{JiraClient, BULK_CREATE_LIMIT};
+
+mod deployment;
+pub use deployment::EPIC_FIELD;
+
+mod endpoints;
+
+mod cache;
+pub use cache::DEFAULT_CACHE_TTL_SECS;
+
+mod client;
 
 mod config;
-pub use config::Config;
+pub use config::{ApiVersion, AuthType, CacheOptions, ClientOptions, Config};
 
 mod error;
 pub use error::{CustomError, Error};
 
+mod fake;
+pub use fake::FakeJira;
+
+mod fields;
+pub use fields::IssueFields;
+
+mod jira_api;
+pub use jira_api::JiraApi;
+
+mod jql;
+pub use jql::{Jql, Order};
+
+mod lint;
+pub use lint::{lint, Lint, Severity, MAX_SUMMARY_LENGTH};
+
+mod logging;
+
+#[cfg(feature = "async")]
+pub mod nonblocking;
+
+mod models;
+pub use models::{
+    BulkCreateError, BulkCreateResponse, Component, CreateMeta, CreateMetaIssueType,
+    CreateMetaProject, CreatedIssue, DeploymentType, ElementErrors, Field, FieldMeta, FieldSchema,
+    Issue, IssueType, JiraIssue, Project, SearchPage, SearchResults, ServerInfo, User,
+};
+
+mod oauth;
+pub use oauth::{OAuthConfig, OAuthToken};
+
+mod recording;
+pub use recording::{Exchange, RecordedRequest, RecordedResponse, Recording};
+
+mod redact;
+pub use redact::REDACTED;
+
+mod retry;
+pub use retry::RetryPolicy;
+
+mod search;
+pub use search::{SearchIter, SearchOptions, DEFAULT_PAGE_SIZE};
+
 mod serialize;
 pub use serialize::csv_to_json;
+
+mod template;
+pub use template::{Dataset, Template, TemplateRow, FOREACH_COLUMN, INCLUDE_DIRECTIVE};
+
+mod transport;
+
+mod update;
+pub use update::{IssueUpdate, UpdateOperation};
+
+mod validate;
+pub use validate::{validate_fields, Violation};
@@ -0,0 +1,232 @@
+// Offline checks of an issues template for mistakes that JIRA (or `csv_to_json`) would not report.
+use crate::error::Error;
+use crate::template::{FOREACH_COLUMN, INCLUDE_DIRECTIVE};
+use csv::{ReaderBuilder, StringRecord};
+use serde::Serialize;
+use std::io::Read;
+
+/// The longest summary JIRA accepts.
+pub const MAX_SUMMARY_LENGTH: usize = 255;
+
+/// How serious a [`Lint`](struct.Lint.html) is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The template is wrong and pushing it fails or creates the wrong issues.
+    Error,
+    /// The template is probably not what was intended.
+    Warning,
+}
+
+/// A problem found in an issues template by [`lint`](fn.lint.html).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Lint {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// A short, stable identifier of the check that failed, e.g. `duplicate-column`.
+    pub rule: &'static str,
+    /// The line of the template the problem is on.
+    pub line: u64,
+    /// The field id of the column the problem is in, if it concerns a single column.
+    pub column: Option<String>,
+    /// A description of the problem.
+    pub message: String,
+}
+
+/// Checks an issues template without contacting JIRA. Reports scalar columns that appear more
+/// than once (only the last value would be sent), columns used both as an array and as a scalar,
+/// labels containing whitespace, summaries longer than 255 characters, columns that are blank in
+/// every row, empty rows, and rows whose width differs from the header row.
+/// ```
+/// use jiragen::{lint, Severity};
+///
+/// let csv = "\
+/// summary,labels[],labels,summary
+/// Summary,Labels,Labels
+/// Ship it,needs review,,Ship it again
+/// ";
+/// let rules: Vec<(Severity, &str, u64)> = lint(csv.as_bytes())
+///   .unwrap()
+///   .into_iter()
+///   .map(|l| (l.severity, l.rule, l.line))
+///   .collect();
+///
+/// assert_eq!(
+///   rules,
+///   vec![
+///     (Severity::Error, "duplicate-column", 1),
+///     (Severity::Error, "mixed-array-column", 1),
+///     (Severity::Error, "names-row-width", 2),
+///     (Severity::Error, "label-whitespace", 3),
+///     (Severity::Warning, "blank-column", 1),
+///   ]
+/// );
+/// ```
+pub fn lint<R: Read>(rdr: R) -> Result<Vec<Lint>, Error> {
+    let mut csv_reader = ReaderBuilder::new().flexible(true).from_reader(rdr);
+    let headers = csv_reader.headers()?.clone();
+    let records = csv_reader.records().collect::<Result<Vec<_>, _>>()?;
+    let mut lints = Vec::new();
+
+    lint_headers(&headers, &mut lints);
+
+    let mut records = records.iter();
+    if let Some(names) = records.next() {
+        if names.len() != headers.len() {
+            lints.push(Lint {
+                severity: Severity::Error,
+                rule: "names-row-width",
+                line: line_of(names),
+                column: None,
+                message: format!(
+                    "the field names row has {} columns, but the field ids row has {}",
+                    names.len(),
+                    headers.len()
+                ),
+            });
+        }
+    }
+
+    let rows: Vec<&StringRecord> = records
+        .filter(|row| {
+            !row.get(0)
+                .unwrap_or_default()
+                .starts_with(INCLUDE_DIRECTIVE)
+        })
+        .collect();
+
+    for row in rows.iter() {
+        lint_row(&headers, row, &mut lints);
+    }
+
+    if !rows.is_empty() {
+        for (i, id) in headers.iter().enumerate() {
+            if rows.iter().all(|row| is_blank(row.get(i))) {
+                lints.push(Lint {
+                    severity: Severity::Warning,
+                    rule: "blank-column",
+                    line: line_of(&headers),
+                    column: Some(id.to_string()),
+                    message: format!("column {} is blank in every row", i + 1),
+                });
+            }
+        }
+    }
+
+    Ok(lints)
+}
+
+fn lint_headers(headers: &StringRecord, lints: &mut Vec<Lint>) {
+    let line = line_of(headers);
+
+    for (i, id) in headers.iter().enumerate() {
+        let scalar = !id.contains("[]") && id != FOREACH_COLUMN;
+        // report each duplicated column once, at its second occurrence
+        if scalar && headers.iter().take(i).filter(|h| *h == id).count() == 1 {
+            lints.push(Lint {
+                severity: Severity::Error,
+                rule: "duplicate-column",
+                line,
+                column: Some(id.to_string()),
+                message: "column appears more than once; only the last value would be sent"
+                    .to_string(),
+            });
+        }
+    }
+
+    let mut roots: Vec<&str> = Vec::new();
+    for id in headers.iter() {
+        let root = root_of(id);
+        if roots.contains(&root) {
+            continue;
+        }
+        roots.push(root);
+
+        let array = headers.iter().any(|h| root_of(h) == root && is_array(h));
+        let other = headers.iter().any(|h| root_of(h) == root && !is_array(h));
+        if array && other {
+            lints.push(Lint {
+                severity: Severity::Error,
+                rule: "mixed-array-column",
+                line,
+                column: Some(root.to_string()),
+                message: format!(
+                    "`{}` is used both as an array (`{}[]`) and as a single value",
+                    root, root
+                ),
+            });
+        }
+    }
+}
+
+fn lint_row(headers: &StringRecord, row: &StringRecord, lints: &mut Vec<Lint>) {
+    let line = line_of(row);
+
+    if row.iter().all(|cell| cell.trim().is_empty()) {
+        lints.push(Lint {
+            severity: Severity::Warning,
+            rule: "empty-row",
+            line,
+            column: None,
+            message: "row is empty and would create an issue without fields".to_string(),
+        });
+        return;
+    }
+
+    if row.len() != headers.len() {
+        lints.push(Lint {
+            severity: Severity::Error,
+            rule: "row-width",
+            line,
+            column: None,
+            message: format!(
+                "row has {} columns, but the field ids row has {}",
+                row.len(),
+                headers.len()
+            ),
+        });
+    }
+
+    for (id, cell) in headers.iter().zip(row.iter()) {
+        if root_of(id) == "labels" && cell.trim().contains(char::is_whitespace) {
+            lints.push(Lint {
+                severity: Severity::Error,
+                rule: "label-whitespace",
+                line,
+                column: Some(id.to_string()),
+                message: format!("label `{}` contains whitespace", cell),
+            });
+        }
+
+        if id == "summary" && cell.chars().count() > MAX_SUMMARY_LENGTH {
+            lints.push(Lint {
+                severity: Severity::Error,
+                rule: "summary-too-long",
+                line,
+                column: Some(id.to_string()),
+                message: format!(
+                    "summary is {} characters long; JIRA allows at most {}",
+                    cell.chars().count(),
+                    MAX_SUMMARY_LENGTH
+                ),
+            });
+        }
+    }
+}
+
+// The field name a column id sets, e.g. `fixVersions` for `fixVersions[].id`.
+fn root_of(id: &str) -> &str {
+    id.split(['.', '[']).next().unwrap_or_default()
+}
+
+fn is_array(id: &str) -> bool {
+    id[root_of(id).len()..].starts_with("[]")
+}
+
+fn is_blank(cell: Option<&str>) -> bool {
+    cell.unwrap_or_default().trim().is_empty()
+}
+
+fn line_of(record: &StringRecord) -> u64 {
+    record.position().map_or(0, |pos| pos.line())
+}
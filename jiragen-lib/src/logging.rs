@@ -0,0 +1,92 @@
+// What the clients log about the requests they send, with `tracing`. Each request is sent in a
+// `jira_request` span with its method and path:
+//
+// * `INFO`: the URL, status and duration of each response.
+// * `DEBUG`: the id JIRA logged the request with, its rate limit headers, and failed sends.
+// * `TRACE`: the request and response bodies, as pretty-printed JSON.
+//
+// Headers other than the rate limit ones are never logged, and credentials in bodies are redacted.
+use crate::endpoints::{ApiRequest, RawResponse};
+use crate::redact::{redact, redact_str};
+use reqwest::header::HeaderMap;
+use reqwest::{StatusCode, Url};
+use serde_json::Value;
+use std::fmt::Display;
+use std::time::Duration;
+use tracing::{debug, enabled, info, Level};
+
+// The headers JIRA says how close a client is to its rate limit with.
+const RATE_LIMIT_HEADERS: [&str; 5] = [
+    "retry-after",
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-reset",
+    "x-ratelimit-nearlimit",
+];
+
+/// Logs the body of a request about to be sent.
+pub(crate) fn log_request(request: &ApiRequest, jira_key: &str) {
+    if let Some(body) = request.body.as_ref() {
+        if enabled!(Level::TRACE) {
+            tracing::trace!("request body:\n{}", pretty(body.clone(), jira_key));
+        }
+    }
+}
+
+/// Logs the status and rate limit headers of a response, and how long it took.
+pub(crate) fn log_response(
+    url: &Url,
+    status: StatusCode,
+    headers: &HeaderMap,
+    elapsed: Duration,
+    jira_key: &str,
+) {
+    info!(
+        url = %redact_str(url.as_str(), &[jira_key]),
+        status = %status,
+        elapsed_ms = elapsed.as_millis() as u64,
+        "response"
+    );
+
+    if enabled!(Level::DEBUG) {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let rate_limits: Vec<String> = RATE_LIMIT_HEADERS
+            .iter()
+            .filter_map(|name| Some(format!("{}: {}", name, header(name)?)))
+            .collect();
+        let rate_limits = match rate_limits.is_empty() {
+            true => "none".to_string(),
+            false => rate_limits.join(", "),
+        };
+        debug!(
+            request_id = %header("x-arequestid").unwrap_or("none"),
+            rate_limits = %rate_limits,
+            "response headers"
+        );
+    }
+}
+
+/// Logs a request that could not be sent, or whose response could not be read.
+pub(crate) fn log_failure(error: &dyn Display, elapsed: Duration) {
+    debug!(
+        elapsed_ms = elapsed.as_millis() as u64,
+        "request failed: {}", error
+    );
+}
+
+/// Logs the body of a response.
+pub(crate) fn log_response_body(response: &RawResponse, jira_key: &str) {
+    if enabled!(Level::TRACE) {
+        let body = serde_json::from_str(&response.body)
+            .unwrap_or_else(|_| Value::String(response.body.clone()));
+        tracing::trace!("response body:\n{}", pretty(body, jira_key));
+    }
+}
+
+fn pretty(mut body: Value, jira_key: &str) -> String {
+    redact(&mut body, &[jira_key]);
+    match body {
+        Value::String(s) => s,
+        body => serde_json::to_string_pretty(&body).unwrap_or_default(),
+    }
+}
@@ -0,0 +1,358 @@
+// The request and response bodies of the JIRA API endpoints used by JiraGen.
+use crate::adf;
+use crate::error::Error;
+use crate::update::IssueUpdate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Clone, Debug, Serialize)]
+/// The object to send to JIRA’s "bulk issue creation" API endpoint
+pub struct JiraIssue {
+    /// Operations on the issue's fields, e.g. adding links or comments, usually built with
+    /// [`IssueUpdate`](struct.IssueUpdate.html), or `None`.
+    pub update: Option<Value>,
+    /// A `serde_json` [Value](https://docs.serde.rs/serde_json/enum.Value.html).
+    pub fields: Value,
+}
+
+// The system fields whose values are rich text, which version 3 of the API expects in ADF. Rich-text
+// custom fields are found from the site's fields, and converted when issues are adapted.
+const RICH_TEXT_FIELDS: [&str; 2] = ["description", "environment"];
+
+impl JiraIssue {
+    /// The issue of a template row, as [`csv_to_json`](fn.csv_to_json.html) converts it: its
+    /// `update.<field>.<operation>` columns, e.g. `update.labels.add`, are the issue's
+    /// [`update`](struct.IssueUpdate.html#method.from_columns), and its other columns its fields.
+    /// ```
+    /// use jiragen::JiraIssue;
+    /// use serde_json::json;
+    ///
+    /// let issue = JiraIssue::from_row(json!({
+    ///   "summary": "Tag it",
+    ///   "update": { "labels": { "add": "release" }, "comment": { "add": { "body": "" } } },
+    /// }))?;
+    ///
+    /// assert_eq!(issue.fields, json!({ "summary": "Tag it" }));
+    /// assert_eq!(issue.update, Some(json!({ "labels": [{ "add": "release" }] })));
+    /// # Ok::<(), jiragen::Error>(())
+    /// ```
+    pub fn from_row(mut row: Value) -> Result<JiraIssue, Error> {
+        let update = match row
+            .as_object_mut()
+            .and_then(|fields| fields.remove("update"))
+        {
+            Some(columns) => IssueUpdate::from_columns(&columns)?,
+            None => IssueUpdate::new(),
+        };
+
+        Ok(JiraIssue {
+            update: match update.is_empty() {
+                true => None,
+                false => Some(update.into()),
+            },
+            fields: row,
+        })
+    }
+
+    /// Adds the operations of an update after the issue's own.
+    pub fn add_update(&mut self, update: IssueUpdate) -> Result<(), Error> {
+        let current = match self.update.take() {
+            Some(current) => serde_json::from_value(current)?,
+            None => IssueUpdate::new(),
+        };
+        self.update = Some(current.merge(update).into());
+
+        Ok(())
+    }
+
+    /// The issue as version 3 of the API expects it: the plain text or Markdown of its rich-text
+    /// fields, i.e. `description`, `environment` and the body of each comment added or edited with
+    /// `update.comment`, converted to [ADF](adf/index.html). Values that aren't strings, e.g.
+    /// ADF documents, are kept as they are. Rich-text custom fields are converted by
+    /// [`adapt_issues`](struct.JiraClient.html#method.adapt_issues), which knows the site's
+    /// fields.
+    pub fn to_adf(&self) -> JiraIssue {
+        let mut issue = self.clone();
+
+        for field in RICH_TEXT_FIELDS {
+            if let Some(text) = issue.fields[field].as_str() {
+                issue.fields[field] = adf::from_markdown(text);
+            }
+        }
+
+        if let Some(comments) = issue
+            .update
+            .as_mut()
+            .and_then(|update| update["comment"].as_array_mut())
+        {
+            for comment in comments.iter_mut() {
+                for operation in ["add", "edit"] {
+                    if let Some(text) = comment[operation]["body"].as_str() {
+                        comment[operation]["body"] = adf::from_markdown(text);
+                    }
+                }
+            }
+        }
+
+        issue
+    }
+}
+
+/// A reference to an issue that JIRA created.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CreatedIssue {
+    pub id: String,
+    pub key: String,
+    /// The API URL of the issue.
+    #[serde(rename = "self")]
+    pub self_url: String,
+}
+
+/// The response of the bulk issue creation endpoint.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct BulkCreateResponse {
+    /// The issues that were created, in request order.
+    #[serde(default)]
+    pub issues: Vec<CreatedIssue>,
+    /// The issues that could not be created.
+    #[serde(default)]
+    pub errors: Vec<BulkCreateError>,
+}
+
+/// Why one of the issues of a bulk create could not be created.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkCreateError {
+    /// The HTTP status JIRA gave the issue, e.g. `400`.
+    #[serde(default)]
+    pub status: u16,
+    #[serde(default)]
+    pub element_errors: ElementErrors,
+    /// The index of the issue in the request.
+    pub failed_element_number: usize,
+}
+
+/// The errors JIRA reported for an issue: messages about the issue as a whole, and messages about
+/// each field that could not be set.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementErrors {
+    #[serde(default)]
+    pub error_messages: Vec<String>,
+    /// Error messages keyed by field id.
+    #[serde(default)]
+    pub errors: BTreeMap<String, String>,
+}
+
+/// An issue, with the fields JIRA returned for it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Issue {
+    pub id: String,
+    pub key: String,
+    /// The API URL of the issue.
+    #[serde(rename = "self")]
+    pub self_url: String,
+    /// The issue's fields, keyed by field id.
+    #[serde(default)]
+    pub fields: BTreeMap<String, Value>,
+}
+
+impl Issue {
+    /// The text of a field, e.g. `summary` or `description`, whether it is a string (API version
+    /// 2) or an [ADF](adf/index.html) document (API version 3).
+    pub fn text(&self, field: &str) -> Option<String> {
+        match self.fields.get(field)? {
+            Value::Null => None,
+            value => Some(adf::to_text(value)),
+        }
+    }
+}
+
+/// A JIRA user. JIRA Cloud identifies users by `account_id`, and JIRA Server by `name` and `key`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub display_name: String,
+    /// Hidden by JIRA Cloud, depending on the user's privacy settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email_address: Option<String>,
+    #[serde(default)]
+    pub active: bool,
+}
+
+/// One page of issues returned by a JQL search.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    pub start_at: u64,
+    pub max_results: u64,
+    /// The number of issues matching the query, across all pages.
+    pub total: u64,
+    pub issues: Vec<Issue>,
+}
+
+/// One page of the issues matching a JQL search, as paged by [`search_page`](struct.JiraClient.html#method.search_page).
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchPage {
+    #[serde(default)]
+    pub issues: Vec<Issue>,
+    /// The token to request the next page with, or `None` on the last page.
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+/// The version and deployment of a JIRA site, from `/rest/api/2/serverInfo`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerInfo {
+    #[serde(default)]
+    pub base_url: String,
+    /// The version, e.g. `9.12.2` on Server, or `1001.0.0-SNAPSHOT` on Cloud.
+    pub version: String,
+    #[serde(default)]
+    pub version_numbers: Vec<u64>,
+    #[serde(default)]
+    pub deployment_type: DeploymentType,
+    #[serde(default)]
+    pub server_title: String,
+}
+
+/// How a JIRA site is deployed. Cloud identifies users by account id and links issues to epics
+/// with `parent`; Server and Data Center identify users by name and link issues to epics with the
+/// Epic Link custom field. Sites too old to report a deployment type are Server.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DeploymentType {
+    Cloud,
+    DataCenter,
+    #[default]
+    #[serde(other)]
+    Server,
+}
+
+impl DeploymentType {
+    pub fn is_cloud(self) -> bool {
+        self == DeploymentType::Cloud
+    }
+}
+
+impl fmt::Display for DeploymentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DeploymentType::Cloud => "Cloud",
+            DeploymentType::Server => "Server",
+            DeploymentType::DataCenter => "Data Center",
+        })
+    }
+}
+
+/// A JIRA project.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub id: String,
+    pub key: String,
+    pub name: String,
+    #[serde(default)]
+    pub components: Vec<Component>,
+    #[serde(default)]
+    pub issue_types: Vec<IssueType>,
+    /// Project role names, mapped to the API URL of each role.
+    #[serde(default)]
+    pub roles: BTreeMap<String, String>,
+}
+
+/// A project component.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Component {
+    pub id: String,
+    pub name: String,
+}
+
+/// An issue type.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IssueType {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub subtask: bool,
+}
+
+/// A system or custom field.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Field {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub custom: bool,
+    pub schema: Option<FieldSchema>,
+}
+
+/// The type of a field's value.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct FieldSchema {
+    /// The value's type, e.g. `string`, `array`, `user` or `option`.
+    #[serde(rename = "type")]
+    pub field_type: String,
+    /// The type of the items of an `array` field.
+    pub items: Option<String>,
+    /// The id of a system field.
+    pub system: Option<String>,
+    /// The type of a custom field.
+    pub custom: Option<String>,
+}
+
+/// The create metadata returned by `/rest/api/2/issue/createmeta`: the fields available when
+/// creating each issue type of each project.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct CreateMeta {
+    #[serde(default)]
+    pub projects: Vec<CreateMetaProject>,
+}
+
+/// A project in the create metadata.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CreateMetaProject {
+    pub id: String,
+    pub key: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub issuetypes: Vec<CreateMetaIssueType>,
+}
+
+/// An issue type in the create metadata, with the fields on its create screen.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CreateMetaIssueType {
+    pub id: String,
+    pub name: String,
+    /// The fields on the create screen, keyed by field id.
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldMeta>,
+}
+
+/// How a field can be set when creating an issue.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldMeta {
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub schema: FieldSchema,
+    /// Whether JIRA fills in the field when it is left out.
+    #[serde(default)]
+    pub has_default_value: bool,
+    /// The values the field may be set to. Empty when any value is allowed.
+    #[serde(default)]
+    pub allowed_values: Vec<Value>,
+}
@@ -0,0 +1,430 @@
+//! An async `JiraClient`, for use from async runtimes such as tokio. It sends the same requests
+//! and returns the same models as the blocking [`JiraClient`](../struct.JiraClient.html).
+//! ```
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), jiragen::Error> {
+//! use futures::TryStreamExt;
+//! use jiragen::nonblocking::JiraClient;
+//! use jiragen::Config;
+//!
+//! # let mut server = mockito::Server::new_async().await;
+//! # let page = |start_at: u64, keys: &[&str]| {
+//! #   let issues: Vec<_> = keys
+//! #     .iter()
+//! #     .map(|k| serde_json::json!({ "id": "1", "key": k, "self": "" }))
+//! #     .collect();
+//! #   serde_json::json!({ "startAt": start_at, "maxResults": 2, "total": 3, "issues": issues })
+//! #     .to_string()
+//! # };
+//! # let first = server
+//! #   .mock("POST", "/rest/api/2/search")
+//! #   .match_body(mockito::Matcher::PartialJsonString(r#"{"startAt": 0}"#.to_string()))
+//! #   .with_body(page(0, &["ABC-1", "ABC-2"]))
+//! #   .create_async()
+//! #   .await;
+//! # let second = server
+//! #   .mock("POST", "/rest/api/2/search")
+//! #   .match_body(mockito::Matcher::PartialJsonString(r#"{"startAt": 2}"#.to_string()))
+//! #   .with_body(page(2, &["ABC-3"]))
+//! #   .create_async()
+//! #   .await;
+//! # let jira_url = server.url();
+//! let jira = JiraClient::new(Config {
+//!   jira_url,
+//!   jira_user: String::from("user@example.com"),
+//!   jira_key: String::from("API_KEY"),
+//!   ..Default::default()
+//! })?;
+//!
+//! let keys: Vec<String> = jira
+//!   .search_stream("project = ABC", 2)
+//!   .map_ok(|issue| issue.key)
+//!   .try_collect()
+//!   .await?;
+//!
+//! assert_eq!(keys, vec!["ABC-1", "ABC-2", "ABC-3"]);
+//! # first.assert_async().await;
+//! # second.assert_async().await;
+//! # Ok(())
+//! # }
+//! ```
+use crate::api::BULK_CREATE_LIMIT;
+use crate::client::{self, Authentication, ClientState};
+use crate::config::Config;
+use crate::deployment::{self, Adapter};
+use crate::endpoints::{self, ApiRequest, RawResponse};
+use crate::error::Error;
+use crate::models::{
+    BulkCreateResponse, CreateMeta, CreatedIssue, DeploymentType, Field, Issue, JiraIssue, Project,
+    SearchPage, SearchResults, ServerInfo, User,
+};
+use crate::retry::Attempts;
+use crate::search::SearchOptions;
+use crate::transport::Transport;
+use futures::stream::{self, Stream, TryStreamExt};
+use futures_timer::Delay;
+use reqwest::{Client, Method, RequestBuilder};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The async counterpart of the blocking [`JiraClient`](../struct.JiraClient.html), with the same
+/// typed methods and authentication.
+pub struct JiraClient {
+    client: Client,
+    state: ClientState,
+}
+
+// The client and `Config` (with the API key redacted); sessions and tokens are left out.
+impl fmt::Debug for JiraClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JiraClient")
+            .field("client", &self.client)
+            .field("config", &self.state.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JiraClient {
+    /// Creates a new async `reqwest` client with the `Config`'s
+    /// [`ClientOptions`](../struct.ClientOptions.html) and returns the `JiraClient` struct wrapper,
+    /// or the error of an invalid option.
+    pub fn new(config: Config) -> Result<Self, Error> {
+        let client = Transport::new(&config.client)?.async_client()?;
+        let state = ClientState::new(config)?;
+
+        Ok(Self { client, state })
+    }
+
+    /// The configuration the client sends requests with.
+    pub fn config(&self) -> &Config {
+        &self.state.config
+    }
+
+    /// Creates a single issue, as it is: [`adapt_issues`](#method.adapt_issues) adapts it to the
+    /// site's deployment first.
+    pub async fn create_issue(&self, issue: &JiraIssue) -> Result<CreatedIssue, Error> {
+        self.send(endpoints::create_issue(
+            self.state.config.api_version,
+            issue,
+        ))
+        .await
+    }
+
+    /// Creates issues with the bulk issue creation endpoint, in requests of at most
+    /// [`BULK_CREATE_LIMIT`](../constant.BULK_CREATE_LIMIT.html) issues.
+    pub async fn bulk_create(&self, issues: &[JiraIssue]) -> Result<BulkCreateResponse, Error> {
+        self.bulk_create_chunked(issues, BULK_CREATE_LIMIT).await
+    }
+
+    /// Creates issues with the bulk issue creation endpoint, sending them in order in requests of
+    /// at most `chunk_size` issues. The responses are aggregated into one, in which the
+    /// `failed_element_number` of each error is the index of the issue in `issues`. The issues are
+    /// sent as they are, like with [`create_issue`](#method.create_issue).
+    pub async fn bulk_create_chunked(
+        &self,
+        issues: &[JiraIssue],
+        chunk_size: usize,
+    ) -> Result<BulkCreateResponse, Error> {
+        if chunk_size == 0 {
+            return Err(endpoints::invalid_chunk_size());
+        }
+
+        let mut response = BulkCreateResponse::default();
+        for (i, chunk) in issues.chunks(chunk_size).enumerate() {
+            let offset = i * chunk_size;
+            let chunk_response = self
+                .send_raw(endpoints::bulk_create(self.state.config.api_version, chunk))
+                .await
+                .and_then(endpoints::bulk_create_response)
+                .map_err(|e| {
+                    endpoints::chunk_error(e, offset, chunk.len(), response.issues.len())
+                })?;
+            endpoints::merge_bulk_response(&mut response, offset, chunk_response);
+        }
+
+        Ok(response)
+    }
+
+    /// Gets a project, including its components, issue types and roles, by key or id.
+    pub async fn get_project(&self, project: &str) -> Result<Project, Error> {
+        self.send(endpoints::get_project(
+            self.state.config.api_version,
+            project,
+        ))
+        .await
+    }
+
+    /// Gets an issue by key or id.
+    pub async fn get_issue(&self, issue: &str) -> Result<Issue, Error> {
+        self.send(endpoints::get_issue(self.state.config.api_version, issue))
+            .await
+    }
+
+    /// Gets one page of the issues matching a JQL query.
+    pub async fn search(
+        &self,
+        jql: &str,
+        start_at: u64,
+        max_results: u64,
+    ) -> Result<SearchResults, Error> {
+        self.send(endpoints::search(
+            self.state.config.api_version,
+            jql,
+            start_at,
+            max_results,
+            &SearchOptions::default(),
+        ))
+        .await
+    }
+
+    /// The version and deployment of the JIRA site, requested once and then kept for the life of
+    /// the client.
+    pub async fn server_info(&self) -> Result<ServerInfo, Error> {
+        if let Some(info) = self.state.known_server_info() {
+            return Ok(info);
+        }
+
+        let info = self
+            .send(endpoints::get_server_info(self.state.config.api_version))
+            .await?;
+        Ok(self.state.detected(info))
+    }
+
+    /// The issues as the site's deployment expects them. Users are referred to by account id on
+    /// JIRA Cloud, looking up names and email addresses, and by name on Server and Data Center.
+    /// The `epic` pseudo-field, an epic's key, is replaced with `parent` on Cloud and with the
+    /// Epic Link field on Server. With version 3 of the API, the text of rich-text custom fields,
+    /// i.e. multi-line text fields, is converted to [ADF](adf/index.html). Issues are kept as they
+    /// are when the site doesn't report its deployment.
+    pub async fn adapt_issues(&self, issues: &[JiraIssue]) -> Result<Vec<JiraIssue>, Error> {
+        if !deployment::needs_adapting(issues, self.state.config.api_version) {
+            return Ok(issues.to_vec());
+        }
+        let deployment = match self.deployment().await? {
+            Some(deployment) => deployment,
+            None => return Ok(issues.to_vec()),
+        };
+
+        let fields = self.site_fields().await?;
+        let adapter = Adapter {
+            deployment,
+            api_version: self.state.config.api_version,
+            fields: &fields,
+        };
+        let mut users = BTreeMap::new();
+        for query in adapter.user_queries(issues) {
+            let found = self.find_users(&query).await?;
+            users.insert(query, found);
+        }
+
+        Ok(adapter.adapt_all(issues, &users))
+    }
+
+    /// Streams every issue matching a JQL query, requesting `page_size` issues at a time with
+    /// [`search_page`](#method.search_page) as the stream is polled.
+    pub fn search_stream<'a>(
+        &'a self,
+        jql: &'a str,
+        page_size: u64,
+    ) -> impl Stream<Item = Result<Issue, Error>> + 'a {
+        let options = SearchOptions {
+            page_size,
+            ..Default::default()
+        };
+        self.search_stream_with(jql, options)
+    }
+
+    /// Streams every issue matching a JQL query, like [`search_stream`](#method.search_stream),
+    /// with the fields, expansions and page size of the options.
+    pub fn search_stream_with<'a>(
+        &'a self,
+        jql: &'a str,
+        options: SearchOptions,
+    ) -> impl Stream<Item = Result<Issue, Error>> + 'a {
+        stream::try_unfold(Some(None), move |page_token: Option<Option<String>>| {
+            let options = options.clone();
+            async move {
+                let page_token = match page_token {
+                    Some(page_token) => page_token,
+                    None => return Ok::<_, Error>(None),
+                };
+
+                let page = self
+                    .search_page_with(jql, page_token.as_deref(), &options)
+                    .await?;
+                // an empty page is the last, whatever its token
+                let next = match page.issues.is_empty() {
+                    true => None,
+                    false => page.next_page_token.map(Some),
+                };
+
+                let issues = stream::iter(page.issues.into_iter().map(Ok::<_, Error>));
+                Ok(Some((issues, next)))
+            }
+        })
+        .try_flatten()
+    }
+
+    /// Gets one page of the issues matching a JQL query, given the `next_page_token` of the page
+    /// before it, or `None` for the first page. JIRA Cloud is searched with its enhanced search
+    /// endpoint, and Server and Data Center with `startAt`, whose tokens are the `startAt` of the
+    /// next page.
+    pub async fn search_page(
+        &self,
+        jql: &str,
+        page_token: Option<&str>,
+        max_results: u64,
+    ) -> Result<SearchPage, Error> {
+        let options = SearchOptions {
+            page_size: max_results,
+            ..Default::default()
+        };
+        self.search_page_with(jql, page_token, &options).await
+    }
+
+    /// Gets one page of the issues matching a JQL query, like [`search_page`](#method.search_page),
+    /// with the fields, expansions and page size of the options.
+    pub async fn search_page_with(
+        &self,
+        jql: &str,
+        page_token: Option<&str>,
+        options: &SearchOptions,
+    ) -> Result<SearchPage, Error> {
+        let deployment = self.deployment().await?;
+        let request = endpoints::page_request(
+            self.state.config.api_version,
+            deployment,
+            jql,
+            page_token,
+            options,
+        )?;
+        endpoints::page_response(deployment, self.send_raw(request).await?)
+    }
+
+    /// Finds the users whose name or email address match a query.
+    pub async fn find_users(&self, query: &str) -> Result<Vec<User>, Error> {
+        let deployment = self.deployment().await?;
+        self.send(endpoints::find_users(
+            self.state.config.api_version,
+            deployment,
+            query,
+        ))
+        .await
+    }
+
+    /// Gets every system and custom field.
+    pub async fn get_fields(&self) -> Result<Vec<Field>, Error> {
+        self.send(endpoints::get_fields(self.state.config.api_version))
+            .await
+    }
+
+    /// Gets the create metadata, including fields, of every issue type in a project, given its key
+    /// or numeric id.
+    pub async fn get_createmeta(&self, project: &str) -> Result<CreateMeta, Error> {
+        self.send(endpoints::get_createmeta(
+            self.state.config.api_version,
+            project,
+        ))
+        .await
+    }
+
+    // Creates an authenticated request to a path of the JIRA API.
+    async fn request(&self, method: Method, path: &str) -> Result<RequestBuilder, Error> {
+        let target = match self.state.authentication()? {
+            Authentication::Ready(target) => target,
+            Authentication::Login(login) => {
+                // the login request itself is sent without authentication
+                let builder = self
+                    .client
+                    .request(login.method.clone(), self.state.url(&login.path));
+                let response = self
+                    .execute(client::prepare(builder, &login), &login)
+                    .await?;
+                self.state.logged_in(response)?
+            }
+            Authentication::Refresh(oauth, token) => self.state.refreshed(
+                oauth
+                    .refresh_async(&self.state.config.client, &token)
+                    .await?,
+            )?,
+        };
+
+        Ok(target.authorize(self.client.request(method, target.url(path))))
+    }
+
+    // The site's deployment, or `None` when JIRA doesn't report it.
+    async fn deployment(&self) -> Result<Option<DeploymentType>, Error> {
+        client::deployment(self.server_info().await)
+    }
+
+    // The site's fields, requested once.
+    async fn site_fields(&self) -> Result<Vec<Field>, Error> {
+        match self.state.known_fields() {
+            Some(fields) => Ok(fields),
+            None => Ok(self.state.fields_received(self.get_fields().await?)),
+        }
+    }
+
+    // Sends an authenticated request to an endpoint, and deserializes the response.
+    async fn send<T: DeserializeOwned>(&self, request: ApiRequest) -> Result<T, Error> {
+        endpoints::parse_response(self.send_raw(request).await?)
+    }
+
+    // Sends an authenticated request to an endpoint, and returns the response as is, unless it is
+    // replayed or cached.
+    async fn send_raw(&self, request: ApiRequest) -> Result<RawResponse, Error> {
+        if let Some(response) = self.state.stored(&request)? {
+            return Ok(response);
+        }
+
+        let builder = self.request(request.method.clone(), &request.path).await?;
+        let mut response = self
+            .execute(client::prepare(builder, &request), &request)
+            .await?;
+        // a request rejected because the session has expired is sent once more, in a new session
+        if self.state.session_expired(&response) {
+            let builder = self.request(request.method.clone(), &request.path).await?;
+            response = self
+                .execute(client::prepare(builder, &request), &request)
+                .await?;
+        }
+        self.state.store(&request, &response);
+
+        Ok(response)
+    }
+
+    // Sends a request, retrying it as the `RetryPolicy` allows, and returns the last response.
+    #[tracing::instrument(
+        name = "jira_request",
+        skip_all,
+        fields(method = %request.method, path = %request.path)
+    )]
+    async fn execute(
+        &self,
+        builder: RequestBuilder,
+        request: &ApiRequest,
+    ) -> Result<RawResponse, Error> {
+        let mut attempts = Attempts::new(&self.state.config, request);
+        let mut builder = builder;
+
+        let response = loop {
+            let next = builder.try_clone();
+            attempts.start();
+            let result = builder.send().await;
+            let outcome = result.as_ref().map(|r| (r.url(), r.status(), r.headers()));
+            match (attempts.retry_delay(outcome, next.is_some()), next) {
+                (Some(delay), Some(next)) => {
+                    Delay::new(delay).await;
+                    builder = next;
+                }
+                _ => break result?,
+            }
+        };
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let response = RawResponse::new(status, &headers, response.text().await?);
+        self.state.received(request, response)
+    }
+}
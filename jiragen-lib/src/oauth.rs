@@ -0,0 +1,469 @@
+// OAuth 2.0 authorization code grants (3LO) for JIRA Cloud.
+use crate::config::ClientOptions;
+use crate::error::{CustomError, Error};
+use crate::redact::Secret;
+use crate::transport::Transport;
+use reqwest::blocking::Client;
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Access tokens are refreshed this many seconds before they expire.
+const EXPIRY_MARGIN_SECS: u64 = 60;
+
+/// The OAuth 2.0 app used to authorize JiraGen, and where its tokens are stored. The URLs default
+/// to Atlassian's, and can be pointed elsewhere for testing. Its `Debug` output redacts
+/// `client_secret`.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OAuthConfig {
+    /// The client id of the OAuth 2.0 app.
+    pub client_id: String,
+    /// The client secret of the OAuth 2.0 app.
+    pub client_secret: String,
+    /// The local port the browser is redirected to, i.e. `http://localhost:<port>/callback`. It
+    /// must match the callback URL registered for the app.
+    #[serde(default = "default_redirect_port")]
+    pub redirect_port: u16,
+    /// The scopes to request.
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<String>,
+    /// The authorization page the user is sent to.
+    #[serde(default = "default_auth_url")]
+    pub auth_url: String,
+    /// The endpoint authorization codes and refresh tokens are exchanged at.
+    #[serde(default = "default_token_url")]
+    pub token_url: String,
+    /// The API gateway requests are routed through, as `<api_url>/ex/jira/<cloud id>`.
+    #[serde(default = "default_api_url")]
+    pub api_url: String,
+    /// The file the token is stored in. Refreshed tokens are saved back to it.
+    pub token_file: PathBuf,
+}
+
+/// The tokens granted to JiraGen for a JIRA Cloud site. Its `Debug` output redacts the tokens.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OAuthToken {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// When the access token expires, in seconds since the Unix epoch.
+    pub expires_at: u64,
+    /// The id of the JIRA Cloud site the token grants access to.
+    pub cloud_id: String,
+}
+
+impl OAuthToken {
+    /// Reads a token saved by [`save`](#method.save).
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Saves the token as JSON, in a file only the user can read.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        write_private(path.as_ref(), &serde_json::to_string_pretty(self)?)
+    }
+
+    /// Whether the access token has expired, or is about to.
+    pub fn is_expired(&self) -> bool {
+        now() + EXPIRY_MARGIN_SECS >= self.expires_at
+    }
+}
+
+impl OAuthConfig {
+    /// Creates the settings of an OAuth 2.0 app, using Atlassian's URLs and the default redirect
+    /// port and scopes.
+    pub fn new<P: Into<PathBuf>>(client_id: &str, client_secret: &str, token_file: P) -> Self {
+        Self {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            redirect_port: default_redirect_port(),
+            scopes: default_scopes(),
+            auth_url: default_auth_url(),
+            token_url: default_token_url(),
+            api_url: default_api_url(),
+            token_file: token_file.into(),
+        }
+    }
+
+    /// Saves the settings as JSON, in a file only the user can read, as they include the client
+    /// secret.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        write_private(path.as_ref(), &serde_json::to_string_pretty(self)?)
+    }
+
+    /// The URL the browser is redirected to after authorizing.
+    pub fn redirect_uri(&self) -> String {
+        format!("http://localhost:{}/callback", self.redirect_port)
+    }
+
+    /// Runs the authorization code flow: `open` is called with the authorization page URL (to
+    /// open or print it), and the browser's redirect is received by a listener on
+    /// `redirect_port`. The code is exchanged for a token for `site` (a JIRA Cloud URL), or the
+    /// first site the user granted access to, and the token is saved to `token_file`. The
+    /// authorization server is sent requests with the proxy, certificates, timeouts and headers of
+    /// `options`, like JIRA is.
+    pub fn login<F: FnOnce(&str)>(
+        &self,
+        options: &ClientOptions,
+        site: Option<&str>,
+        open: F,
+    ) -> Result<OAuthToken, Error> {
+        let client = Transport::new(options)?.blocking_client()?;
+        let listener = TcpListener::bind(("127.0.0.1", self.redirect_port))?;
+        let state = random_state();
+        let scope = self.scopes.join(" ");
+        let auth_url = Url::parse_with_params(
+            &self.auth_url,
+            &[
+                ("audience", "api.atlassian.com"),
+                ("client_id", self.client_id.as_str()),
+                ("scope", scope.as_str()),
+                ("redirect_uri", self.redirect_uri().as_str()),
+                ("state", state.as_str()),
+                ("response_type", "code"),
+                ("prompt", "consent"),
+            ],
+        )
+        .map_err(|e| oauth_error("Invalid authorization URL", e.to_string()))?;
+
+        open(auth_url.as_str());
+
+        let code = receive_code(&listener, &state)?;
+        let grant: TokenResponse = self.token_request(
+            &client,
+            json!({
+                "grant_type": "authorization_code",
+                "client_id": self.client_id,
+                "client_secret": self.client_secret,
+                "code": code,
+                "redirect_uri": self.redirect_uri(),
+            }),
+        )?;
+
+        let cloud_id = self.cloud_id(&client, &grant.access_token, site)?;
+        let token = grant.into_token(cloud_id, None);
+        token.save(&self.token_file)?;
+
+        Ok(token)
+    }
+
+    /// Exchanges the refresh token for a new access token, and saves it to `token_file`. The
+    /// request is sent with the settings of `options`, as with [`login`](#method.login).
+    pub fn refresh(
+        &self,
+        options: &ClientOptions,
+        token: &OAuthToken,
+    ) -> Result<OAuthToken, Error> {
+        let client = Transport::new(options)?.blocking_client()?;
+        let grant = self.token_request(&client, self.refresh_body(token))?;
+        self.refreshed(grant, token)
+    }
+
+    /// Exchanges the refresh token for a new access token without blocking, and saves it to
+    /// `token_file`.
+    #[cfg(feature = "async")]
+    pub async fn refresh_async(
+        &self,
+        options: &ClientOptions,
+        token: &OAuthToken,
+    ) -> Result<OAuthToken, Error> {
+        let response = Transport::new(options)?
+            .async_client()?
+            .post(&self.token_url)
+            .json(&self.refresh_body(token))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(token_error(status, response.text().await?));
+        }
+
+        let grant = response.json().await?;
+        self.refreshed(grant, token)
+    }
+
+    fn refresh_body(&self, token: &OAuthToken) -> serde_json::Value {
+        json!({
+            "grant_type": "refresh_token",
+            "client_id": self.client_id,
+            "client_secret": self.client_secret,
+            "refresh_token": token.refresh_token,
+        })
+    }
+
+    fn refreshed(&self, grant: TokenResponse, token: &OAuthToken) -> Result<OAuthToken, Error> {
+        let token = grant.into_token(token.cloud_id.clone(), Some(&token.refresh_token));
+        token.save(&self.token_file)?;
+
+        Ok(token)
+    }
+
+    /// The base URL of the JIRA API of a token's site.
+    pub fn site_url(&self, token: &OAuthToken) -> String {
+        format!(
+            "{}/ex/jira/{}",
+            self.api_url.trim_end_matches('/'),
+            token.cloud_id
+        )
+    }
+
+    fn token_request(
+        &self,
+        client: &Client,
+        body: serde_json::Value,
+    ) -> Result<TokenResponse, Error> {
+        let response = client.post(&self.token_url).json(&body).send()?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(token_error(status, response.text()?));
+        }
+
+        Ok(response.json()?)
+    }
+
+    // Finds the cloud id of `site` among the sites the token grants access to.
+    fn cloud_id(
+        &self,
+        client: &Client,
+        access_token: &str,
+        site: Option<&str>,
+    ) -> Result<String, Error> {
+        let url = format!(
+            "{}/oauth/token/accessible-resources",
+            self.api_url.trim_end_matches('/')
+        );
+        let resources: Vec<AccessibleResource> = client
+            .get(&url)
+            .bearer_auth(access_token)
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        let site = site.map(|s| s.trim_end_matches('/'));
+        resources
+            .into_iter()
+            .find(|r| site.is_none() || site == Some(r.url.trim_end_matches('/')))
+            .map(|r| r.id)
+            .ok_or_else(|| {
+                oauth_error(
+                    "No JIRA site was authorized",
+                    format!(
+                        "The token does not grant access to {}",
+                        site.unwrap_or("any site")
+                    ),
+                )
+            })
+    }
+}
+
+impl fmt::Debug for OAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuthConfig")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &Secret(&self.client_secret))
+            .field("redirect_port", &self.redirect_port)
+            .field("scopes", &self.scopes)
+            .field("auth_url", &self.auth_url)
+            .field("token_url", &self.token_url)
+            .field("api_url", &self.api_url)
+            .field("token_file", &self.token_file)
+            .finish()
+    }
+}
+
+impl fmt::Debug for OAuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuthToken")
+            .field("access_token", &Secret(&self.access_token))
+            .field("refresh_token", &Secret(&self.refresh_token))
+            .field("expires_at", &self.expires_at)
+            .field("cloud_id", &self.cloud_id)
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: u64,
+}
+
+impl TokenResponse {
+    // Refresh tokens may rotate; the previous one is kept when no new one is issued.
+    fn into_token(self, cloud_id: String, previous_refresh_token: Option<&str>) -> OAuthToken {
+        OAuthToken {
+            access_token: self.access_token,
+            refresh_token: self
+                .refresh_token
+                .or_else(|| previous_refresh_token.map(str::to_string))
+                .unwrap_or_default(),
+            expires_at: now() + self.expires_in,
+            cloud_id,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AccessibleResource {
+    id: String,
+    url: String,
+}
+
+// How long a connection to the loopback listener may take to send its request line.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Waits for the browser's redirect to the loopback listener, and returns its authorization code.
+// Other requests, e.g. for `/favicon.ico`, are answered with a 404, and connections that send no
+// request, e.g. a browser's preconnects, are closed.
+fn receive_code(listener: &TcpListener, state: &str) -> Result<String, Error> {
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        let target = match request_target(&stream) {
+            Some(target) => target,
+            None => continue,
+        };
+        let url = match Url::parse(&format!("http://localhost{}", target)) {
+            Ok(url) if url.path() == "/callback" => url,
+            _ => {
+                // the browser may have given up on the request
+                let _ = respond(&mut stream, "404 Not Found", "Not found.");
+                continue;
+            }
+        };
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.into_owned())
+        };
+
+        let (status, page) = match (param("code"), param("state"), param("error")) {
+            (Some(_), Some(s), _) if s == state => (
+                "200 OK",
+                "JiraGen is authorized. You can close this window.",
+            ),
+            _ => ("400 Bad Request", "JiraGen was not authorized."),
+        };
+        respond(&mut stream, status, page)?;
+
+        return match (param("code"), param("state"), param("error")) {
+            (Some(code), Some(s), _) if s == state => Ok(code),
+            (_, _, Some(error)) => Err(oauth_error("Authorization was denied", error)),
+            _ => Err(oauth_error(
+                "Invalid redirect",
+                "The redirect had no authorization code, or an unexpected state".to_string(),
+            )),
+        };
+    }
+
+    unreachable!("TcpListener::incoming never ends")
+}
+
+// The target of a request, e.g. `/callback?code=...&state=...` for `GET /callback?... HTTP/1.1`,
+// or `None` when the connection sent no request line in time.
+fn request_target(stream: &TcpStream) -> Option<String> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT)).ok()?;
+    let mut request_line = String::new();
+    BufReader::new(stream).read_line(&mut request_line).ok()?;
+    request_line.split_whitespace().nth(1).map(str::to_string)
+}
+
+fn respond(stream: &mut TcpStream, status: &str, page: &str) -> Result<(), Error> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        page.len(),
+        page
+    )?;
+    Ok(())
+}
+
+// Writes a file of secrets. On Unix, it is created with mode 0600, and an existing file is
+// restricted to it before it is overwritten.
+fn write_private(path: &Path, contents: &str) -> Result<(), Error> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())?;
+
+    Ok(())
+}
+
+fn random_state() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos(),
+    );
+    format!("{:016x}", hasher.finish())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn token_error(status: StatusCode, body: String) -> Error {
+    oauth_error(
+        &format!(
+            "The authorization server responded with status {}:",
+            status.as_str()
+        ),
+        body,
+    )
+}
+
+fn oauth_error(message: &str, details: String) -> Error {
+    Error::CustomError(CustomError {
+        message: message.to_string(),
+        details,
+    })
+}
+
+fn default_redirect_port() -> u16 {
+    8910
+}
+
+fn default_scopes() -> Vec<String> {
+    vec![
+        "read:jira-work".to_string(),
+        "write:jira-work".to_string(),
+        "read:jira-user".to_string(),
+        "offline_access".to_string(),
+    ]
+}
+
+fn default_auth_url() -> String {
+    "https://auth.atlassian.com/authorize".to_string()
+}
+
+fn default_token_url() -> String {
+    "https://auth.atlassian.com/oauth/token".to_string()
+}
+
+fn default_api_url() -> String {
+    "https://api.atlassian.com".to_string()
+}
@@ -0,0 +1,220 @@
+// Recording the requests a client sends and the responses JIRA sends back, and replaying them
+// without network access.
+use crate::config::Config;
+use crate::endpoints::{ApiRequest, RawResponse};
+use crate::error::{CustomError, Error};
+use crate::redact::{redact, redact_str};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The requests a client sent and the responses JIRA sent back, in order, as written by
+/// [`ClientOptions.record`](struct.ClientOptions.html#structfield.record). Credentials are
+/// redacted: the API key, passwords, tokens and session cookies are replaced with
+/// [`REDACTED`](constant.REDACTED.html), and headers and the JIRA URL are not recorded at all.
+/// ```
+/// use jiragen::{Exchange, Recording};
+///
+/// let recording: Recording = serde_json::from_str(r#"{
+///   "exchanges": [{
+///     "request": { "method": "GET", "path": "/rest/api/2/field" },
+///     "response": { "status": 200, "body": [] }
+///   }]
+/// }"#)?;
+///
+/// let Exchange { request, response } = &recording.exchanges[0];
+/// assert_eq!((request.method.as_str(), response.status), ("GET", 200));
+/// # Ok::<(), jiragen::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Recording {
+    pub exchanges: Vec<Exchange>,
+}
+
+impl Recording {
+    /// Reads a recording from a JSON file.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+/// A request and the response JIRA sent to it.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Exchange {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// A request, without its headers.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RecordedRequest {
+    /// The HTTP method, e.g. `POST`.
+    pub method: String,
+    /// The path of the JIRA API, e.g. `/rest/api/2/issue/bulk`.
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub query: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+/// A response, without its headers.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    /// The id JIRA logged the request with, from the `X-AREQUESTID` header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// The body, as JSON, or as a string when it isn't JSON.
+    #[serde(default)]
+    pub body: Value,
+}
+
+/// Where a client's traffic goes besides JIRA: into a recording, or, instead of JIRA, from one.
+pub(crate) enum Tape {
+    Record {
+        path: PathBuf,
+        // The API key of the `Config`, redacted wherever it appears.
+        jira_key: String,
+        recording: Mutex<Recording>,
+    },
+    Replay {
+        path: PathBuf,
+        // The exchanges not replayed yet.
+        exchanges: Mutex<Vec<Exchange>>,
+    },
+}
+
+impl Tape {
+    /// The tape of the `Config`'s `record` or `replay` option. Recording starts with an empty
+    /// file, so that a path that can't be written is an error before any request is sent.
+    pub fn new(config: &Config) -> Result<Option<Self>, Error> {
+        let tape = match (&config.client.record, &config.client.replay) {
+            (None, None) => return Ok(None),
+            (Some(_), Some(_)) => {
+                return Err(Error::CustomError(CustomError {
+                    message: "Invalid client option `record`".to_string(),
+                    details: "`record` and `replay` can't be set together".to_string(),
+                }))
+            }
+            (Some(path), None) => Tape::Record {
+                path: path.clone(),
+                jira_key: config.jira_key.clone(),
+                recording: Mutex::new(Recording::default()),
+            },
+            (None, Some(path)) => Tape::Replay {
+                path: path.clone(),
+                exchanges: Mutex::new(Recording::from_path(path)?.exchanges),
+            },
+        };
+
+        if let Tape::Record { recording, .. } = &tape {
+            tape.save(&recording.lock().unwrap())?;
+        }
+
+        Ok(Some(tape))
+    }
+
+    /// Whether responses come from a recording rather than JIRA.
+    pub fn is_replay(&self) -> bool {
+        matches!(self, Tape::Replay { .. })
+    }
+
+    /// The recorded response to the first exchange not replayed yet with the same method, path and
+    /// query as the request.
+    pub fn replay(&self, request: &ApiRequest) -> Result<RawResponse, Error> {
+        let (path, exchanges) = match self {
+            Tape::Replay { path, exchanges } => (path, exchanges),
+            Tape::Record { .. } => unreachable!("only a replay tape replays"),
+        };
+
+        let mut exchanges = exchanges.lock().unwrap();
+        let method = request.method.as_str();
+        let i = exchanges
+            .iter()
+            .position(|e| {
+                e.request.method == method
+                    && e.request.path == request.path
+                    && e.request.query == request.query
+            })
+            .ok_or_else(|| {
+                Error::CustomError(CustomError {
+                    message: "Request not found in the recording".to_string(),
+                    details: format!(
+                        "{} has no response left to {} {}",
+                        path.display(),
+                        method,
+                        request.path
+                    ),
+                })
+            })?;
+
+        let response = exchanges.remove(i).response;
+        tracing::info!(status = response.status, "replayed response");
+        Ok(RawResponse {
+            status: StatusCode::from_u16(response.status).map_err(|_| {
+                Error::CustomError(CustomError {
+                    message: "Invalid recording".to_string(),
+                    details: format!("{} is not an HTTP status", response.status),
+                })
+            })?,
+            request_id: response.request_id,
+            body: match response.body {
+                Value::String(body) => body,
+                body => body.to_string(),
+            },
+        })
+    }
+
+    /// Adds an exchange to the recording, and writes the whole recording, so that it is complete
+    /// even if the client's work is cut short.
+    pub fn record(&self, request: &ApiRequest, response: &RawResponse) -> Result<(), Error> {
+        let (secrets, recording) = match self {
+            Tape::Record {
+                jira_key,
+                recording,
+                ..
+            } => ([jira_key.as_str()], recording),
+            Tape::Replay { .. } => return Ok(()),
+        };
+
+        let mut body = serde_json::from_str(&response.body)
+            .unwrap_or_else(|_| Value::String(response.body.clone()));
+        redact(&mut body, &secrets);
+        let mut request_body = request.body.clone();
+        if let Some(request_body) = request_body.as_mut() {
+            redact(request_body, &secrets);
+        }
+
+        let mut recording = recording.lock().unwrap();
+        recording.exchanges.push(Exchange {
+            request: RecordedRequest {
+                method: request.method.to_string(),
+                path: request.path.clone(),
+                query: request
+                    .query
+                    .iter()
+                    .map(|(name, value)| (name.clone(), redact_str(value, &secrets)))
+                    .collect(),
+                body: request_body,
+            },
+            response: RecordedResponse {
+                status: response.status.as_u16(),
+                request_id: response.request_id.clone(),
+                body,
+            },
+        });
+
+        self.save(&recording)
+    }
+
+    fn save(&self, recording: &Recording) -> Result<(), Error> {
+        if let Tape::Record { path, .. } = self {
+            fs::write(path, serde_json::to_string_pretty(recording)?)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+// Keeping credentials out of recordings, logs and `Debug` output.
+use serde_json::Value;
+use std::fmt;
+
+/// What credentials are replaced with in recordings, logs and `Debug` output.
+pub const REDACTED: &str = "<redacted>";
+
+// Names of object keys and headers whose values are credentials, in lowercase. They are matched
+// exactly, so that e.g. the `nextPageToken` of a search is kept.
+const SECRET_NAMES: [&str; 8] = [
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "client_secret",
+    "password",
+    "authorization",
+    "cookie",
+    "set-cookie",
+];
+
+/// Whether a key or header, e.g. `Authorization` or `client_secret`, names a credential.
+pub(crate) fn is_secret(name: &str) -> bool {
+    SECRET_NAMES.contains(&name.to_lowercase().as_str())
+}
+
+/// Replaces credentials in a body: the values of keys that name credentials, the session cookie
+/// JIRA logs in with, and the secrets wherever they appear.
+pub(crate) fn redact(value: &mut Value, secrets: &[&str]) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Value::Object(session) if key == "session" => {
+                        if let Some(cookie) = session.get_mut("value") {
+                            *cookie = Value::String(REDACTED.to_string());
+                        }
+                    }
+                    Value::Null => {}
+                    value if is_secret(key) => *value = Value::String(REDACTED.to_string()),
+                    value => redact(value, secrets),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(|v| redact(v, secrets)),
+        Value::String(s) => *s = redact_str(s, secrets),
+        _ => {}
+    }
+}
+
+/// Replaces every (non-empty) secret in a string.
+pub(crate) fn redact_str(s: &str, secrets: &[&str]) -> String {
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(s.to_string(), |s, secret| s.replace(secret, REDACTED))
+}
+
+/// Formats a credential for `Debug` output: `REDACTED`, or `""` when it isn't set.
+pub(crate) struct Secret<'a>(pub &'a str);
+
+impl fmt::Debug for Secret<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.is_empty() {
+            true => write!(f, "\"\""),
+            false => write!(f, "{}", REDACTED),
+        }
+    }
+}
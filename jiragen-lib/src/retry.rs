@@ -0,0 +1,221 @@
+// When and how long to wait before retrying a request that JIRA rate limited or failed to answer.
+use crate::config::Config;
+use crate::endpoints::ApiRequest;
+use crate::logging;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+
+/// How failed requests are retried. Reads (and other idempotent requests) are retried when JIRA
+/// rate limits them, is temporarily unavailable, or can't be reached. Other requests, e.g. issue
+/// creation, are only retried when JIRA signals it did not process them: when they are rate
+/// limited (`429 Too Many Requests`) or the connection could not be made.
+///
+/// The wait before each retry doubles from `initial_backoff_ms`, with random jitter, unless JIRA
+/// says how long to wait with a `Retry-After` or `X-RateLimit-Reset` header. Either way, it is at
+/// most `max_backoff_ms`.
+/// ```
+/// use jiragen::{Config, RetryPolicy};
+///
+/// let config = Config {
+///   retry: RetryPolicy { max_attempts: 6, ..Default::default() },
+///   ..Default::default()
+/// };
+/// # assert_eq!(config.retry.max_backoff_ms, 30_000);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// The most times a request is sent, including the first. `1` disables retries.
+    pub max_attempts: u32,
+    /// The wait before the first retry, in milliseconds.
+    pub initial_backoff_ms: u64,
+    /// The longest wait between attempts, in milliseconds, even when JIRA asks for a longer one.
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait before retrying a request that JIRA responded to, or `None` if it should
+    /// not be retried.
+    fn retry_response(
+        &self,
+        attempt: u32,
+        idempotent: bool,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Option<Duration> {
+        let retryable = match status {
+            StatusCode::TOO_MANY_REQUESTS => true,
+            StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT => idempotent,
+            _ => false,
+        };
+        if !retryable || attempt >= self.max_attempts {
+            return None;
+        }
+
+        let max_backoff = Duration::from_millis(self.max_backoff_ms);
+        Some(match server_delay(headers) {
+            Some(delay) => delay.min(max_backoff),
+            None => self.backoff(attempt),
+        })
+    }
+
+    /// How long to wait before retrying a request that failed without a response, or `None` if it
+    /// should not be retried.
+    fn retry_error(
+        &self,
+        attempt: u32,
+        idempotent: bool,
+        error: &reqwest::Error,
+    ) -> Option<Duration> {
+        // a request that failed to connect was never sent, so it is safe to send again
+        let retryable = error.is_connect() || (idempotent && error.is_timeout());
+        if !retryable || attempt >= self.max_attempts {
+            return None;
+        }
+
+        Some(self.backoff(attempt))
+    }
+
+    // Exponential backoff with jitter: a random wait between half and all of
+    // `initial_backoff_ms * 2^(attempt - 1)`, capped at `max_backoff_ms`.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .initial_backoff_ms
+            .saturating_mul(1 << (attempt - 1).min(32))
+            .min(self.max_backoff_ms);
+        let half = exponential / 2;
+        let jitter = match half {
+            0 => 0,
+            half => random() % (half + 1),
+        };
+
+        Duration::from_millis(exponential - half + jitter)
+    }
+}
+
+/// The attempts at sending a request, which are logged, and retried as the `RetryPolicy` allows.
+/// The clients send each attempt, and wait the delay this returns before sending the next.
+pub(crate) struct Attempts<'a> {
+    request: &'a ApiRequest,
+    policy: &'a RetryPolicy,
+    jira_key: &'a str,
+    attempt: u32,
+    started: Instant,
+}
+
+impl<'a> Attempts<'a> {
+    /// Logs the request that is about to be sent.
+    pub fn new(config: &'a Config, request: &'a ApiRequest) -> Self {
+        logging::log_request(request, &config.jira_key);
+        Self {
+            request,
+            policy: &config.retry,
+            jira_key: &config.jira_key,
+            attempt: 1,
+            started: Instant::now(),
+        }
+    }
+
+    /// Starts timing an attempt, as it is sent.
+    pub fn start(&mut self) {
+        self.started = Instant::now();
+    }
+
+    /// Logs how an attempt went, the URL, status and headers of its response or the error it
+    /// failed with, and returns how long to wait before the next attempt, or `None` if this
+    /// attempt is the last. A request whose body can't be sent again is never retried.
+    pub fn retry_delay(
+        &mut self,
+        outcome: Result<(&Url, StatusCode, &HeaderMap), &reqwest::Error>,
+        can_resend: bool,
+    ) -> Option<Duration> {
+        let elapsed = self.started.elapsed();
+        let idempotent = self.request.idempotent;
+        let (delay, reason) = match outcome {
+            Ok((url, status, headers)) => {
+                logging::log_response(url, status, headers, elapsed, self.jira_key);
+                let delay = self
+                    .policy
+                    .retry_response(self.attempt, idempotent, status, headers);
+                (delay, format!("responded with {}", status))
+            }
+            Err(e) => {
+                logging::log_failure(e, elapsed);
+                let delay = self.policy.retry_error(self.attempt, idempotent, e);
+                (delay, format!("failed: {}", e))
+            }
+        };
+        let delay = delay.filter(|_| can_resend)?;
+
+        tracing::warn!(
+            "{} {} {}; retrying in {:.1}s (attempt {} of {})",
+            self.request.method,
+            self.request.path,
+            reason,
+            delay.as_secs_f64(),
+            self.attempt + 1,
+            self.policy.max_attempts
+        );
+        self.attempt += 1;
+
+        Some(delay)
+    }
+}
+
+// The wait JIRA asked for: `Retry-After` in seconds or as an HTTP date, or the time
+// `X-RateLimit-Reset` is at.
+fn server_delay(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+
+    if let Some(retry_after) = header(RETRY_AFTER.as_str()).map(str::trim) {
+        if let Ok(seconds) = retry_after.parse() {
+            return Some(Duration::from_secs(seconds));
+        }
+        // e.g. `Wed, 21 Oct 2026 07:28:00 GMT`; a date that has passed means no wait
+        if let Ok(date) = DateTime::parse_from_rfc2822(retry_after) {
+            let wait = date.with_timezone(&Utc).signed_duration_since(Utc::now());
+            return Some(wait.to_std().unwrap_or_default());
+        }
+    }
+
+    let reset = parse_timestamp(header("x-ratelimit-reset")?)?;
+    let wait = reset.signed_duration_since(Utc::now()).to_std().ok()?;
+    Some(wait)
+}
+
+// JIRA sends `X-RateLimit-Reset` as an ISO 8601 timestamp, e.g. `2023-05-04T12:34Z`.
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+
+    let value = value.trim_end_matches('Z');
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|naive| DateTime::from_naive_utc_and_offset(naive, Utc))
+}
+
+fn random() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    hasher.finish()
+}
@@ -0,0 +1,85 @@
+// Iterating over every issue matching a JQL query, a page at a time.
+use crate::error::Error;
+use crate::jira_api::JiraApi;
+use crate::models::Issue;
+use std::vec;
+
+/// How many issues a search requests at a time, unless `SearchOptions.page_size` is set.
+pub const DEFAULT_PAGE_SIZE: u64 = 50;
+
+/// What a search requests of each issue, and how many issues it requests at a time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// The fields to return, by id, e.g. `summary` or `customfield_10010`, or `*all` or
+    /// `*navigable`. Empty for JIRA's default, the navigable fields.
+    pub fields: Vec<String>,
+    /// The extra information to return with each issue, e.g. `renderedFields`, `changelog` or
+    /// `transitions`.
+    pub expand: Vec<String>,
+    /// The issues requested per page.
+    pub page_size: u64,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            fields: Vec::new(),
+            expand: Vec::new(),
+            page_size: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+/// The issues matching a JQL query, returned by
+/// [`search_iter`](struct.JiraClient.html#method.search_iter). Pages are requested as the
+/// iterator needs them, with `startAt` or page tokens, whichever the site pages with. An error
+/// ends the iteration.
+pub struct SearchIter<'a> {
+    jira: &'a dyn JiraApi,
+    jql: String,
+    options: SearchOptions,
+    // The issues of the current page, not yet returned.
+    issues: vec::IntoIter<Issue>,
+    // The token of the next page, `Some(None)` for the first page, or `None` after the last.
+    next_page: Option<Option<String>>,
+}
+
+impl<'a> SearchIter<'a> {
+    /// Searches any [`JiraApi`](trait.JiraApi.html), e.g. a `Box<dyn JiraApi>`.
+    pub fn new(jira: &'a dyn JiraApi, jql: impl Into<String>, options: SearchOptions) -> Self {
+        Self {
+            jira,
+            jql: jql.into(),
+            options,
+            issues: Vec::new().into_iter(),
+            next_page: Some(None),
+        }
+    }
+}
+
+impl Iterator for SearchIter<'_> {
+    type Item = Result<Issue, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(issue) = self.issues.next() {
+                return Some(Ok(issue));
+            }
+
+            let page_token = self.next_page.take()?;
+            match self
+                .jira
+                .search_page_with(&self.jql, page_token.as_deref(), &self.options)
+            {
+                Ok(page) => {
+                    // an empty page is the last, whatever its token
+                    if !page.issues.is_empty() {
+                        self.next_page = page.next_page_token.map(Some);
+                    }
+                    self.issues = page.issues.into_iter();
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
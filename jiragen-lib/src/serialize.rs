@@ -75,6 +75,14 @@ use serde_json::{Map, Value};
 ///   vec![json!({ "timetracking": { "originalEstimate": "10", "remainingEstimate": "5" } })]
 /// );
 ///
+/// // nested objects, merged
+/// let headers = vec!["update.labels.add", "update.labels.remove"];
+/// let records = vec![StringRecord::from(vec!["release", "draft"])];
+/// assert_eq!(
+///   csv_to_json(headers, records).unwrap(),
+///   vec![json!({ "update": { "labels": { "add": "release", "remove": "draft" } } })]
+/// );
+///
 /// // an array with mutiple objects (each with 1 item)
 /// let headers = vec!["fixVersions[].id", "fixVersions[].id"];
 /// let records = vec![StringRecord::from(vec!["10000", "10001"])];
@@ -90,10 +98,7 @@ pub fn csv_to_json(headers: Vec<&str>, records: Vec<StringRecord>) -> Result<Vec
       let mut fields = Map::new();
 
       for (i, id) in headers.iter().enumerate() {
-        let record_field_value: &str = match record.get(i) {
-          Some(value) => value,
-          None => "",
-        };
+        let record_field_value: &str = record.get(i).unwrap_or_default();
 
         let (json_key, json_val) = csv_value_to_json(id, record_field_value);
 
@@ -128,7 +133,7 @@ fn csv_value_to_json(header: &str, value: &str) -> (String, Value) {
       match matching_delimiter.as_str() {
         // is array
         "[]" => {
-          let nested_val = match rest_header {
+          match rest_header {
             // an array of strings
             "" => Value::Array(vec![Value::String(value.to_string())]),
             // an array of object|arrays
@@ -136,9 +141,7 @@ fn csv_value_to_json(header: &str, value: &str) -> (String, Value) {
               let (_, recursive_val) = csv_value_to_json(rest_header, value);
               Value::Array(vec![recursive_val])
             }
-          };
-
-          nested_val
+          }
         }
 
         // is object
@@ -169,7 +172,8 @@ fn csv_value_to_json(header: &str, value: &str) -> (String, Value) {
 }
 
 // Merges json_val into fields, where fields already has a value for json_key.
-// If json_val is an object, then the key:val is appended to the existing object.
+// If json_val is an object, then its key:vals are merged into the existing object, recursively, so
+// that e.g. `update.labels.add` and `update.labels.remove` both end up in `update.labels`.
 // If json_val is an array, then each item in the array is appended to the existing array.
 fn merge_json(
   mut fields: Map<String, Value>,
@@ -179,10 +183,17 @@ fn merge_json(
   let existing_val = fields.get_mut(&json_key).unwrap();
   match existing_val {
     Value::Object(existing_map) => {
-      // append key:vals to the existing object
+      // merge key:vals into the existing object
       if let Value::Object(json_val_map) = json_val {
         for (key, val) in json_val_map.into_iter() {
-          existing_map.insert(key, val);
+          match existing_map.contains_key(&key) {
+            true => {
+              *existing_map = merge_json(std::mem::take(existing_map), key, val);
+            }
+            false => {
+              existing_map.insert(key, val);
+            }
+          }
         }
       }
     }
@@ -0,0 +1,549 @@
+// Reading of issues template files and the expansion of their `@` directive columns.
+use crate::error::{CustomError, Error};
+use crate::serialize::csv_to_json;
+use csv::{Reader, StringRecord};
+use regex::{Captures, Regex};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// The directive that splices the rows of another template file into a template.
+pub const INCLUDE_DIRECTIVE: &str = "@include";
+
+/// The header of the column that expands a single template row into many issues.
+pub const FOREACH_COLUMN: &str = "@foreach";
+
+/// A single issue row of a [`Template`](struct.Template.html).
+#[derive(Clone, Debug)]
+pub struct TemplateRow {
+  /// The template file the row was read from, if it was read from a file.
+  pub file: Option<PathBuf>,
+  /// The line of the template file the row was read from.
+  pub line: u64,
+  /// Values for the `{{name}}` placeholders used in the row's cells.
+  pub vars: BTreeMap<String, String>,
+  /// The raw, unrendered cell values.
+  pub record: StringRecord,
+  /// The columns the template the row was read from doesn't have, which are blank in `record`
+  /// and left out of the row's JSON.
+  pub missing: BTreeSet<usize>,
+}
+
+impl TemplateRow {
+  /// Returns the row's cells with every known `{{name}}` placeholder substituted.
+  pub fn render(&self) -> StringRecord {
+    self
+      .record
+      .iter()
+      .map(|cell| render_placeholders(cell, &self.vars))
+      .collect()
+  }
+}
+
+/// An issues template: the field id header row and the issue rows that follow the ignored,
+/// human-readable second row.
+/// ```
+/// use jiragen::Template;
+///
+/// let csv = "\
+/// @foreach,summary,labels[]
+/// Loop,Summary,Labels
+/// \"region in us,eu; platform in ios,web\",Verify {{region}} on {{platform}},deploy
+/// ";
+/// let template = Template::from_reader(csv.as_bytes()).unwrap().expand().unwrap();
+///
+/// assert_eq!(template.ids(), vec!["summary", "labels[]"]);
+/// let summaries: Vec<String> = template
+///   .records()
+///   .iter()
+///   .map(|record| record[0].to_string())
+///   .collect();
+/// assert_eq!(
+///   summaries,
+///   vec![
+///     "Verify us on ios",
+///     "Verify us on web",
+///     "Verify eu on ios",
+///     "Verify eu on web",
+///   ]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Template {
+  /// The field ids from the first row of the template.
+  pub headers: StringRecord,
+  /// The issue rows, starting on line 3 of the template.
+  pub rows: Vec<TemplateRow>,
+}
+
+impl Template {
+  /// Reads a template from a .csv file, splicing in the rows of any `@include`d templates.
+  ///
+  /// A row whose first cell is `@include path/to/shared.csv` is replaced by the rows of that
+  /// template, with the path relative to the including file. The row's other cells may hold
+  /// `name=value` overrides for the included rows' placeholders. Columns are matched by name, and
+  /// columns only one of the templates has are left blank in the other's rows, and out of their
+  /// JSON.
+  /// ```
+  /// use jiragen::Template;
+  /// use serde_json::json;
+  /// use std::fs;
+  ///
+  /// let dir = std::env::temp_dir().join("jiragen-include-doctest");
+  /// fs::create_dir_all(&dir).unwrap();
+  /// fs::write(
+  ///   dir.join("qa.csv"),
+  ///   "labels[],summary\nLabels,Summary\nqa,Test {{feature}}\n",
+  /// ).unwrap();
+  /// fs::write(
+  ///   dir.join("release.csv"),
+  ///   "summary,project.key\nSummary,Project\nShip it,REL\n@include qa.csv,feature=login\n",
+  /// ).unwrap();
+  ///
+  /// let template = Template::from_path(dir.join("release.csv")).unwrap();
+  /// assert_eq!(template.ids(), vec!["summary", "project.key", "labels[]"]);
+  /// assert_eq!(&template.records()[1], vec!["Test login", "", "qa"]);
+  /// assert_eq!(
+  ///   template.to_json().unwrap()[1],
+  ///   json!({ "summary": "Test login", "labels": ["qa"] })
+  /// );
+  /// ```
+  pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+    load(path.as_ref(), &mut Vec::new())
+  }
+
+  /// Reads a template from any .csv source. `@include` paths are relative to the current
+  /// directory.
+  pub fn from_reader<R: Read>(rdr: R) -> Result<Self, Error> {
+    from_csv(Reader::from_reader(rdr), None, &mut Vec::new())
+  }
+
+  /// Expands every row with a `@foreach` cell into one row per combination of its loop values,
+  /// and removes the `@foreach` columns.
+  ///
+  /// A `@foreach` cell holds one or more `name in value,value,...` lists separated by `;`. Each
+  /// list's current value is available to the row's cells as the `{{name}}` placeholder. When
+  /// several lists are given, the row is expanded into their cartesian product.
+  pub fn expand(self) -> Result<Self, Error> {
+    let foreach_cols: Vec<usize> = self
+      .headers
+      .iter()
+      .enumerate()
+      .filter(|(_, id)| *id == FOREACH_COLUMN)
+      .map(|(i, _)| i)
+      .collect();
+
+    if foreach_cols.is_empty() {
+      return Ok(self);
+    }
+
+    let headers = remove_columns(&self.headers, &foreach_cols);
+    let mut rows = Vec::new();
+
+    for row in self.rows {
+      let mut loops = Vec::new();
+      for i in foreach_cols.iter() {
+        let cell = row.record.get(*i).unwrap_or_default();
+        loops.extend(parse_foreach(cell, row.line)?);
+      }
+
+      let record = remove_columns(&row.record, &foreach_cols);
+      // the indices of the missing columns, once the `@foreach` columns before them are removed
+      let missing: BTreeSet<usize> = row
+        .missing
+        .iter()
+        .filter(|i| !foreach_cols.contains(i))
+        .map(|i| i - foreach_cols.iter().filter(|col| *col < i).count())
+        .collect();
+      for assignment in cartesian_product(&loops) {
+        let mut vars = row.vars.clone();
+        vars.extend(assignment);
+        rows.push(TemplateRow {
+          vars,
+          record: record.clone(),
+          missing: missing.clone(),
+          ..row.clone()
+        });
+      }
+    }
+
+    Ok(Self { headers, rows })
+  }
+
+  /// Returns a copy of the template with `vars` available to the placeholders of every row.
+  /// Variables already set on a row take precedence.
+  pub fn instantiate(&self, vars: &BTreeMap<String, String>) -> Self {
+    let rows = self
+      .rows
+      .iter()
+      .map(|row| {
+        let mut row_vars = vars.clone();
+        row_vars.extend(row.vars.clone());
+        TemplateRow {
+          vars: row_vars,
+          ..row.clone()
+        }
+      })
+      .collect();
+
+    Self {
+      headers: self.headers.clone(),
+      rows,
+    }
+  }
+
+  /// The field ids, in the form expected by [`csv_to_json`](fn.csv_to_json.html).
+  pub fn ids(&self) -> Vec<&str> {
+    self.headers.iter().collect()
+  }
+
+  /// The rendered issue rows, in the form expected by [`csv_to_json`](fn.csv_to_json.html).
+  pub fn records(&self) -> Vec<StringRecord> {
+    self.rows.iter().map(|row| row.render()).collect()
+  }
+
+  /// The rendered issue rows as JSON, converted by [`csv_to_json`](fn.csv_to_json.html) with only
+  /// the columns of the template each row was read from.
+  pub fn to_json(&self) -> Result<Vec<Value>, Error> {
+    self
+      .rows
+      .iter()
+      .map(|row| {
+        let record = row.render();
+        let mut ids = Vec::new();
+        let mut cells = StringRecord::new();
+        for (i, (id, cell)) in self.headers.iter().zip(record.iter()).enumerate() {
+          if !row.missing.contains(&i) {
+            ids.push(id);
+            cells.push_field(cell);
+          }
+        }
+
+        let mut json = csv_to_json(ids, vec![cells])?;
+        Ok(json.remove(0))
+      })
+      .collect()
+  }
+}
+
+/// A dataset to mail-merge with a [`Template`](struct.Template.html): a .csv file whose header
+/// row names the placeholders, followed by one row of values per template instance.
+/// ```
+/// use jiragen::{Dataset, Template};
+///
+/// let template = "\
+/// summary,assignee.name
+/// Summary,Assignee
+/// Create accounts for {{name}},{{manager}}
+/// Book onboarding with {{manager}},{{name}}
+/// ";
+/// let hires = "\
+/// name,manager
+/// alice,carol
+/// bob,dave
+/// ";
+/// let template = Template::from_reader(template.as_bytes()).unwrap();
+/// let dataset = Dataset::from_reader(hires.as_bytes()).unwrap();
+///
+/// let instances: Vec<Template> = dataset
+///   .vars()
+///   .iter()
+///   .map(|vars| template.instantiate(vars))
+///   .collect();
+/// assert_eq!(instances.len(), 2);
+/// assert_eq!(&instances[1].records()[0][0], "Create accounts for bob");
+/// assert_eq!(&instances[1].records()[1][1], "bob");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Dataset {
+  /// The placeholder names.
+  pub headers: StringRecord,
+  /// The rows of placeholder values.
+  pub rows: Vec<StringRecord>,
+}
+
+impl Dataset {
+  /// Reads a dataset from a .csv file.
+  pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+    Self::from_csv(Reader::from_path(path)?)
+  }
+
+  /// Reads a dataset from any .csv source.
+  pub fn from_reader<R: Read>(rdr: R) -> Result<Self, Error> {
+    Self::from_csv(Reader::from_reader(rdr))
+  }
+
+  fn from_csv<R: Read>(mut csv_reader: Reader<R>) -> Result<Self, Error> {
+    let headers = csv_reader.headers()?.clone();
+    let rows = csv_reader.records().collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Self { headers, rows })
+  }
+
+  /// The placeholder values of each row, keyed by column name.
+  pub fn vars(&self) -> Vec<BTreeMap<String, String>> {
+    self
+      .rows
+      .iter()
+      .map(|row| {
+        self
+          .headers
+          .iter()
+          .zip(row.iter())
+          .map(|(name, value)| (name.trim().to_string(), value.to_string()))
+          .collect()
+      })
+      .collect()
+  }
+}
+
+// Reads the template at `path`. `stack` holds the templates currently being included, and is used
+// to detect include cycles.
+fn load(path: &Path, stack: &mut Vec<PathBuf>) -> Result<Template, Error> {
+  let canonical = path.canonicalize()?;
+
+  if let Some(start) = stack.iter().position(|p| *p == canonical) {
+    let cycle: Vec<String> = stack[start..]
+      .iter()
+      .chain(std::iter::once(&canonical))
+      .map(|p| p.display().to_string())
+      .collect();
+    return Err(Error::CustomError(CustomError {
+      message: format!("Include cycle detected in {}", path.display()),
+      details: cycle.join("\n  -> "),
+    }));
+  }
+
+  stack.push(canonical);
+  let template = from_csv(Reader::from_path(path)?, Some(path), stack);
+  stack.pop();
+
+  template
+}
+
+// Reads a template, splicing in `@include`d templates. Header sets are merged by column name, so
+// an included template may order (or repeat) its columns differently than the including one.
+fn from_csv<R: Read>(
+  mut csv_reader: Reader<R>,
+  file: Option<&Path>,
+  stack: &mut Vec<PathBuf>,
+) -> Result<Template, Error> {
+  let own_headers = csv_reader.headers()?.clone();
+  let mut headers = own_headers.clone();
+  // rows are kept alongside the headers they were read with, until all headers are known
+  let mut segments: Vec<(StringRecord, Vec<TemplateRow>)> = vec![(own_headers, Vec::new())];
+  let mut csv_records = csv_reader.into_records();
+
+  // skip line 2, which contains human-readable field names
+  csv_records.next();
+
+  for record in csv_records.filter_map(|record_result| record_result.ok()) {
+    let line = record.position().map_or(0, |pos| pos.line());
+
+    match parse_include(&record, line)? {
+      Some(include) => {
+        let include_path = match file.and_then(|f| f.parent()) {
+          Some(dir) => dir.join(include.path),
+          None => include.path,
+        };
+        let mut included = load(&include_path, stack)?;
+        for row in included.rows.iter_mut() {
+          row.vars.extend(include.overrides.clone());
+        }
+
+        headers = merge_headers(&headers, &included.headers);
+        segments.push((included.headers, included.rows));
+        segments.push((segments[0].0.clone(), Vec::new()));
+      }
+      None => {
+        let row = TemplateRow {
+          file: file.map(|f| f.to_path_buf()),
+          line,
+          vars: BTreeMap::new(),
+          record,
+          missing: BTreeSet::new(),
+        };
+        segments.last_mut().unwrap().1.push(row);
+      }
+    }
+  }
+
+  let rows = segments
+    .into_iter()
+    .flat_map(|(from, rows)| {
+      let headers = &headers;
+      rows.into_iter().map(move |row| {
+        let (record, missing) = remap_record(&row, &from, headers);
+        TemplateRow {
+          record,
+          missing,
+          ..row
+        }
+      })
+    })
+    .collect();
+
+  Ok(Template { headers, rows })
+}
+
+// An `@include` directive row.
+struct Include {
+  path: PathBuf,
+  overrides: BTreeMap<String, String>,
+}
+
+// Parses an `@include path name=value ...` row. The path follows the directive in the first cell,
+// and each of the row's other non-empty cells holds a `name=value` placeholder override.
+fn parse_include(record: &StringRecord, line: u64) -> Result<Option<Include>, Error> {
+  let first = record.get(0).unwrap_or_default().trim();
+  let include_path = match first.strip_prefix(INCLUDE_DIRECTIVE) {
+    Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => rest.trim(),
+    _ => return Ok(None),
+  };
+
+  let invalid = |details: String| {
+    Error::CustomError(CustomError {
+      message: format!("Invalid {} directive on line {}", INCLUDE_DIRECTIVE, line),
+      details,
+    })
+  };
+
+  if include_path.is_empty() {
+    return Err(invalid(format!(
+      "Expected `{} path/to/template.csv`",
+      INCLUDE_DIRECTIVE
+    )));
+  }
+
+  let mut overrides = BTreeMap::new();
+  for cell in record.iter().skip(1).filter(|c| !c.trim().is_empty()) {
+    match cell.split_once('=') {
+      Some((name, value)) => {
+        overrides.insert(name.trim().to_string(), value.trim().to_string());
+      }
+      None => {
+        return Err(invalid(format!(
+          "Expected a `name=value` override, found `{}`",
+          cell
+        )))
+      }
+    }
+  }
+
+  Ok(Some(Include {
+    path: PathBuf::from(include_path),
+    overrides,
+  }))
+}
+
+// Appends the columns of `other` that `headers` lacks. Repeated columns (such as `labels[]`) are
+// kept as many times as they appear in either header row.
+fn merge_headers(headers: &StringRecord, other: &StringRecord) -> StringRecord {
+  let mut merged = headers.clone();
+  for (i, id) in other.iter().enumerate() {
+    let occurrence = other.iter().take(i).filter(|o| *o == id).count();
+    if merged.iter().filter(|m| *m == id).count() <= occurrence {
+      merged.push_field(id);
+    }
+  }
+
+  merged
+}
+
+// Reorders the cells of a row from the `from` header row to the `to` header row, matching
+// repeated columns by occurrence. Columns missing from `from`, or already missing from the row, are
+// left blank and returned as missing.
+fn remap_record(
+  row: &TemplateRow,
+  from: &StringRecord,
+  to: &StringRecord,
+) -> (StringRecord, BTreeSet<usize>) {
+  let mut record = StringRecord::new();
+  let mut missing = BTreeSet::new();
+  for (i, id) in to.iter().enumerate() {
+    let occurrence = to.iter().take(i).filter(|t| *t == id).count();
+    let column = from
+      .iter()
+      .enumerate()
+      .filter(|(_, f)| *f == id)
+      .nth(occurrence)
+      .map(|(j, _)| j)
+      .filter(|j| !row.missing.contains(j));
+    match column {
+      Some(j) => record.push_field(row.record.get(j).unwrap_or_default()),
+      None => {
+        record.push_field("");
+        missing.insert(i);
+      }
+    }
+  }
+
+  (record, missing)
+}
+
+// Substitutes `{{name}}` placeholders with their values. Unknown placeholders are left as is.
+fn render_placeholders(cell: &str, vars: &BTreeMap<String, String>) -> String {
+  lazy_static! {
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{\{\s*([\w.-]+)\s*\}\}").unwrap();
+  }
+
+  PLACEHOLDER
+    .replace_all(cell, |caps: &Captures| match vars.get(&caps[1]) {
+      Some(value) => value.to_owned(),
+      None => caps[0].to_owned(),
+    })
+    .into_owned()
+}
+
+// Parses a `@foreach` cell such as `region in us,eu; platform in ios,web` into its loops.
+fn parse_foreach(cell: &str, line: u64) -> Result<Vec<(String, Vec<String>)>, Error> {
+  lazy_static! {
+    static ref LOOP: Regex = Regex::new(r"^\s*([\w.-]+)\s+in\s+(.*\S)\s*$").unwrap();
+  }
+
+  cell
+    .split(';')
+    .filter(|expr| !expr.trim().is_empty())
+    .map(|expr| match LOOP.captures(expr) {
+      Some(caps) => Ok((
+        caps[1].to_string(),
+        caps[2].split(',').map(|v| v.trim().to_string()).collect(),
+      )),
+      None => Err(Error::CustomError(CustomError {
+        message: format!("Invalid {} expression on line {}", FOREACH_COLUMN, line),
+        details: format!(
+          "Expected `name in value,value,...` (separate lists with `;`), found `{}`",
+          expr.trim()
+        ),
+      })),
+    })
+    .collect()
+}
+
+// Every combination of loop values, with the first loop varying slowest.
+fn cartesian_product(loops: &[(String, Vec<String>)]) -> Vec<BTreeMap<String, String>> {
+  loops
+    .iter()
+    .fold(vec![BTreeMap::new()], |combinations, (name, values)| {
+      combinations
+        .iter()
+        .flat_map(|vars| {
+          values.iter().map(move |value| {
+            let mut vars = vars.clone();
+            vars.insert(name.to_owned(), value.to_owned());
+            vars
+          })
+        })
+        .collect()
+    })
+}
+
+fn remove_columns(record: &StringRecord, cols: &[usize]) -> StringRecord {
+  record
+    .iter()
+    .enumerate()
+    .filter(|(i, _)| !cols.contains(i))
+    .map(|(_, cell)| cell)
+    .collect()
+}
@@ -0,0 +1,142 @@
+// The HTTP client settings of `ClientOptions`, loaded and checked once so that the blocking and async
+// clients are built the same way.
+use crate::config::ClientOptions;
+use crate::error::{CustomError, Error};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use reqwest::{Certificate, Identity, Proxy};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const END_CERTIFICATE: &str = "-----END CERTIFICATE-----";
+
+// Applies the settings of a `Transport` to a blocking or async `ClientBuilder`, whose methods have
+// the same names but no trait in common.
+macro_rules! configure {
+    ($builder:expr, $transport:expr) => {{
+        let transport = $transport;
+        let mut builder = $builder.default_headers(transport.headers);
+        if let Some(timeout) = transport.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = transport.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(proxy) = transport.proxy {
+            builder = builder.proxy(proxy);
+        }
+        for certificate in transport.root_certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some(identity) = transport.identity {
+            builder = builder.identity(identity);
+        }
+        builder
+    }};
+}
+
+/// The loaded settings of a `ClientOptions`.
+pub(crate) struct Transport {
+    pub headers: HeaderMap,
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    pub proxy: Option<Proxy>,
+    pub root_certificates: Vec<Certificate>,
+    pub identity: Option<Identity>,
+}
+
+impl Transport {
+    /// Loads the proxy, certificates and headers of the options, or returns the error of the
+    /// first one that is invalid.
+    pub fn new(options: &ClientOptions) -> Result<Self, Error> {
+        let mut headers = default_headers();
+        for (name, value) in options.headers.iter() {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+                invalid_option("headers", format!("`{}` is not a header name", name))
+            })?;
+            let value = HeaderValue::from_str(value).map_err(|_| {
+                invalid_option("headers", format!("`{}` is not a header value", value))
+            })?;
+            headers.insert(name, value);
+        }
+
+        let proxy = match options.proxy.as_deref() {
+            Some(url) => Some(Proxy::all(url)?),
+            None => None,
+        };
+
+        let root_certificates = match options.ca_cert.as_deref() {
+            Some(path) => certificates(path)?,
+            None => Vec::new(),
+        };
+
+        let identity = match (&options.client_cert, &options.client_key) {
+            (Some(cert), Some(key)) => Some(Identity::from_pkcs8_pem(&read(cert)?, &read(key)?)?),
+            (None, None) => None,
+            _ => {
+                return Err(invalid_option(
+                    "client_cert",
+                    "`client_cert` and `client_key` must be set together".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            headers,
+            timeout: options.timeout_secs.map(Duration::from_secs),
+            connect_timeout: options.connect_timeout_secs.map(Duration::from_secs),
+            proxy,
+            root_certificates,
+            identity,
+        })
+    }
+
+    /// A blocking `reqwest` client with these settings.
+    pub fn blocking_client(self) -> Result<reqwest::blocking::Client, Error> {
+        Ok(configure!(reqwest::blocking::Client::builder(), self).build()?)
+    }
+
+    /// An async `reqwest` client with these settings.
+    #[cfg(feature = "async")]
+    pub fn async_client(self) -> Result<reqwest::Client, Error> {
+        Ok(configure!(reqwest::Client::builder(), self).build()?)
+    }
+}
+
+// The headers sent with every request.
+fn default_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    headers
+}
+
+// Every certificate of a PEM bundle.
+fn certificates(path: &Path) -> Result<Vec<Certificate>, Error> {
+    let bundle = String::from_utf8_lossy(&read(path)?).into_owned();
+    let certificates = bundle
+        .split_inclusive(END_CERTIFICATE)
+        .filter(|pem| pem.contains(END_CERTIFICATE))
+        .map(|pem| Certificate::from_pem(pem.trim().as_bytes()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if certificates.is_empty() {
+        return Err(invalid_option(
+            "ca_cert",
+            format!("{} contains no PEM certificates", path.display()),
+        ));
+    }
+
+    Ok(certificates)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read(path).map_err(|e| invalid_option("file", format!("{}: {}", path.display(), e)))
+}
+
+fn invalid_option(option: &str, details: String) -> Error {
+    Error::CustomError(CustomError {
+        message: format!("Invalid client option `{}`", option),
+        details,
+    })
+}
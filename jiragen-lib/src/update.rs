@@ -0,0 +1,252 @@
+// The `update` of an issue: JIRA's `add`, `set`, `remove` and `edit` operations on its fields.
+use crate::error::{CustomError, Error};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// An operation on a field, in an issue's `update`. Serializes as JIRA expects it, e.g.
+/// `{"add": "a-label"}`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateOperation {
+    /// Adds a value to a field, e.g. a label or component, or adds a comment, link or worklog.
+    Add(Value),
+    /// Replaces the field's value.
+    Set(Value),
+    /// Removes a value from a field.
+    Remove(Value),
+    /// Edits a value of the field, e.g. a worklog by id.
+    Edit(Value),
+}
+
+/// The operations of an issue's `update`, by field, which JIRA applies in order when it creates
+/// the issue. Build one with the methods for labels, components, links, comments and worklogs,
+/// or [`operation`](#method.operation) for any field, and set it as a
+/// [`JiraIssue`](struct.JiraIssue.html)'s `update`.
+/// ```
+/// use jiragen::IssueUpdate;
+/// use serde_json::json;
+///
+/// let update = IssueUpdate::new()
+///   .add_label("release")
+///   .remove_label("draft")
+///   .add_link("Relates", "REL-1")
+///   .add_comment("Created by *JiraGen*")
+///   .add_worklog("1h 30m");
+///
+/// assert_eq!(serde_json::to_value(&update)?, json!({
+///   "labels": [{ "add": "release" }, { "remove": "draft" }],
+///   "issuelinks": [{ "add": { "type": { "name": "Relates" }, "outwardIssue": { "key": "REL-1" } } }],
+///   "comment": [{ "add": { "body": "Created by *JiraGen*" } }],
+///   "worklog": [{ "add": { "timeSpent": "1h 30m" } }],
+/// }));
+/// # Ok::<(), jiragen::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct IssueUpdate {
+    operations: BTreeMap<String, Vec<UpdateOperation>>,
+}
+
+impl IssueUpdate {
+    /// An update without any operations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an operation on a field, after the field's other operations.
+    pub fn operation(mut self, field: &str, operation: UpdateOperation) -> Self {
+        self.operations
+            .entry(field.to_string())
+            .or_default()
+            .push(operation);
+        self
+    }
+
+    pub fn add_label(self, label: &str) -> Self {
+        self.operation("labels", UpdateOperation::Add(json!(label)))
+    }
+
+    pub fn remove_label(self, label: &str) -> Self {
+        self.operation("labels", UpdateOperation::Remove(json!(label)))
+    }
+
+    /// Replaces every label with `labels`.
+    pub fn set_labels(self, labels: &[&str]) -> Self {
+        self.operation("labels", UpdateOperation::Set(json!(labels)))
+    }
+
+    /// Adds a component, by name.
+    pub fn add_component(self, name: &str) -> Self {
+        self.operation("components", UpdateOperation::Add(json!({ "name": name })))
+    }
+
+    /// Removes a component, by name.
+    pub fn remove_component(self, name: &str) -> Self {
+        self.operation(
+            "components",
+            UpdateOperation::Remove(json!({ "name": name })),
+        )
+    }
+
+    /// Replaces every component with the components named `names`.
+    pub fn set_components(self, names: &[&str]) -> Self {
+        let components: Vec<Value> = names.iter().map(|name| json!({ "name": name })).collect();
+        self.operation("components", UpdateOperation::Set(json!(components)))
+    }
+
+    /// Links the issue to another, e.g. `add_link("Blocks", "REL-1")` for "blocks REL-1", with
+    /// the name of a link type.
+    pub fn add_link(self, link_type: &str, outward_issue: &str) -> Self {
+        self.operation(
+            "issuelinks",
+            UpdateOperation::Add(json!({
+                "type": { "name": link_type },
+                "outwardIssue": { "key": outward_issue },
+            })),
+        )
+    }
+
+    /// Removes a link, by the id of the link.
+    pub fn remove_link(self, id: &str) -> Self {
+        self.operation("issuelinks", UpdateOperation::Remove(json!({ "id": id })))
+    }
+
+    /// Adds a comment. With version 3 of the API, the body is converted from plain text or
+    /// Markdown to [ADF](adf/index.html).
+    pub fn add_comment(self, body: &str) -> Self {
+        self.operation("comment", UpdateOperation::Add(json!({ "body": body })))
+    }
+
+    /// Replaces the body of a comment, by id. The body is converted like
+    /// [`add_comment`](#method.add_comment)'s.
+    pub fn edit_comment(self, id: &str, body: &str) -> Self {
+        self.operation(
+            "comment",
+            UpdateOperation::Edit(json!({ "id": id, "body": body })),
+        )
+    }
+
+    /// Removes a comment, by id.
+    pub fn remove_comment(self, id: &str) -> Self {
+        self.operation("comment", UpdateOperation::Remove(json!({ "id": id })))
+    }
+
+    /// Logs work, in JIRA's duration format, e.g. `1h 30m`.
+    pub fn add_worklog(self, time_spent: &str) -> Self {
+        self.operation(
+            "worklog",
+            UpdateOperation::Add(json!({ "timeSpent": time_spent })),
+        )
+    }
+
+    /// Replaces the time spent of a worklog, by id.
+    pub fn edit_worklog(self, id: &str, time_spent: &str) -> Self {
+        self.operation(
+            "worklog",
+            UpdateOperation::Edit(json!({ "id": id, "timeSpent": time_spent })),
+        )
+    }
+
+    /// Removes a worklog, by id.
+    pub fn remove_worklog(self, id: &str) -> Self {
+        self.operation("worklog", UpdateOperation::Remove(json!({ "id": id })))
+    }
+
+    /// Adds the operations of another update, after the operations on the same fields.
+    pub fn merge(mut self, other: IssueUpdate) -> Self {
+        for (field, operations) in other.operations {
+            self.operations.entry(field).or_default().extend(operations);
+        }
+        self
+    }
+
+    /// The operations on a field, in order.
+    pub fn operations(&self, field: &str) -> &[UpdateOperation] {
+        self.operations
+            .get(field)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.values().all(Vec::is_empty)
+    }
+
+    /// The update of the `update.<field>.<operation>` columns of a template row, e.g.
+    /// `update.labels.add` or `update.comment.add.body`, as [`csv_to_json`](fn.csv_to_json.html)
+    /// converts them: `{"labels": {"add": "a-label"}}`. An `add` or `remove` of an array, e.g.
+    /// from `update.labels.add[]`, is one operation per item. The text of an `add` or `edit` of
+    /// `comment` or `worklog`, e.g. from `update.comment.add`, is its body or time spent, as if
+    /// from `update.comment.add.body` or `update.worklog.add.timeSpent`. Blank cells are left out.
+    pub fn from_columns(columns: &Value) -> Result<Self, Error> {
+        let fields = columns.as_object().ok_or_else(|| {
+            invalid_columns(format!("`update` must be an object, not `{}`", columns))
+        })?;
+
+        let mut update = Self::new();
+        for (field, operations) in fields {
+            let operations = operations.as_object().ok_or_else(|| {
+                invalid_columns(format!(
+                    "`update.{}` needs an operation, e.g. `update.{}.add`",
+                    field, field
+                ))
+            })?;
+
+            for (verb, value) in operations {
+                let operation: fn(Value) -> UpdateOperation = match verb.as_str() {
+                    "add" => UpdateOperation::Add,
+                    "set" => UpdateOperation::Set,
+                    "remove" => UpdateOperation::Remove,
+                    "edit" => UpdateOperation::Edit,
+                    _ => {
+                        return Err(invalid_columns(format!(
+                            "`update.{}.{}`: the operations are add, set, remove and edit",
+                            field, verb
+                        )))
+                    }
+                };
+                let values = match (verb.as_str(), value) {
+                    ("add" | "remove", Value::Array(items)) => items.clone(),
+                    _ => vec![value.clone()],
+                };
+                for value in values.into_iter().filter(|v| !is_blank(v)) {
+                    let value = match (field.as_str(), verb.as_str(), value) {
+                        ("comment", "add" | "edit", Value::String(body)) => json!({ "body": body }),
+                        ("worklog", "add" | "edit", Value::String(time_spent)) => {
+                            json!({ "timeSpent": time_spent })
+                        }
+                        (_, _, value) => value,
+                    };
+                    update = update.operation(field, operation(value));
+                }
+            }
+        }
+
+        Ok(update)
+    }
+}
+
+impl From<IssueUpdate> for Value {
+    fn from(update: IssueUpdate) -> Self {
+        json!(update)
+    }
+}
+
+// Whether a cell, or every cell of an object or array of cells, is blank.
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::String(s) => s.trim().is_empty(),
+        Value::Object(o) => o.values().all(is_blank),
+        Value::Array(a) => a.iter().all(is_blank),
+        Value::Null => true,
+        _ => false,
+    }
+}
+
+fn invalid_columns(details: String) -> Error {
+    Error::CustomError(CustomError {
+        message: "Invalid update columns".to_string(),
+        details,
+    })
+}
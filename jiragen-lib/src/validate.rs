@@ -0,0 +1,274 @@
+// Local validation of issue fields against JIRA's create metadata.
+use crate::adf;
+use crate::models::{CreateMeta, CreateMetaIssueType, CreateMetaProject, FieldSchema};
+use serde_json::{Map, Value};
+
+/// A problem found with one field of an issue before it is sent to JIRA.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// The index of the issue in the validated list.
+    pub index: usize,
+    /// The field id, e.g. `summary` or `customfield_10010`.
+    pub field: String,
+    /// A description of the problem.
+    pub problem: String,
+}
+
+/// Validates the `fields` of each issue against the create metadata returned by
+/// [`JiraClient::get_createmeta`](struct.JiraClient.html#method.get_createmeta). Checks that the project and
+/// issue type exist, that required fields are set, that every field is on the create screen, and
+/// that values have the expected type and are among the allowed values.
+/// ```
+/// use jiragen::{validate_fields, CreateMeta};
+/// use serde_json::json;
+///
+/// let createmeta: CreateMeta = serde_json::from_value(json!({ "projects": [{
+///   "id": "10000",
+///   "key": "REL",
+///   "issuetypes": [{
+///     "id": "3",
+///     "name": "Task",
+///     "fields": {
+///       "project": { "required": true, "schema": { "type": "project" } },
+///       "issuetype": { "required": true, "schema": { "type": "issuetype" } },
+///       "summary": { "required": true, "schema": { "type": "string" } },
+///       "priority": {
+///         "required": false,
+///         "schema": { "type": "priority" },
+///         "allowedValues": [{ "id": "1", "name": "High" }, { "id": "2", "name": "Low" }]
+///       }
+///     }
+///   }]
+/// }]})).unwrap();
+///
+/// let issues = vec![json!({
+///   "project": { "key": "REL" },
+///   "issuetype": { "name": "Task" },
+///   "priority": { "name": "Urgent" },
+///   "labels": ["release"]
+/// })];
+///
+/// let problems: Vec<(usize, String)> = validate_fields(&issues, &createmeta)
+///   .into_iter()
+///   .map(|v| (v.index, v.field))
+///   .collect();
+/// assert_eq!(
+///   problems,
+///   vec![
+///     (0, "summary".to_string()),
+///     (0, "labels".to_string()),
+///     (0, "priority".to_string()),
+///   ]
+/// );
+/// ```
+pub fn validate_fields(issues: &[Value], createmeta: &CreateMeta) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    for (index, fields) in issues.iter().enumerate() {
+        let mut violation = |field: &str, problem: String| {
+            violations.push(Violation {
+                index,
+                field: field.to_string(),
+                problem,
+            })
+        };
+
+        let project = match find_project(&fields["project"], createmeta) {
+            Some(project) => project,
+            None => {
+                violation(
+                    "project",
+                    format!(
+                        "project {} does not exist or you cannot create issues in it",
+                        describe(&fields["project"])
+                    ),
+                );
+                continue;
+            }
+        };
+
+        let meta_fields = match find_issuetype(&fields["issuetype"], project) {
+            Some(issuetype) => &issuetype.fields,
+            None => {
+                violation(
+                    "issuetype",
+                    format!(
+                        "issue type {} is not available in project {}",
+                        describe(&fields["issuetype"]),
+                        project.key
+                    ),
+                );
+                continue;
+            }
+        };
+
+        for (id, meta) in meta_fields.iter() {
+            let required = meta.required && !meta.has_default_value;
+            let missing = match fields.get(id) {
+                Some(value) => is_blank(value),
+                None => true,
+            };
+            if required && missing {
+                violation(id, "required field is missing".to_string());
+            }
+        }
+
+        for (id, value) in fields.as_object().into_iter().flat_map(Map::iter) {
+            let meta = match meta_fields.get(id) {
+                Some(meta) => meta,
+                None => {
+                    violation(id, "field is not on the create screen".to_string());
+                    continue;
+                }
+            };
+
+            if is_blank(value) {
+                continue;
+            }
+
+            if let Some(problem) = check_type(value, &meta.schema) {
+                violation(id, problem);
+                continue;
+            }
+
+            if let Some(problem) = check_allowed_values(value, &meta.allowed_values) {
+                violation(id, problem);
+            }
+        }
+    }
+
+    violations
+}
+
+fn find_project<'a>(project: &Value, createmeta: &'a CreateMeta) -> Option<&'a CreateMetaProject> {
+    createmeta
+        .projects
+        .iter()
+        .find(|meta| identifies(project, "id", &meta.id) || identifies(project, "key", &meta.key))
+}
+
+fn find_issuetype<'a>(
+    issuetype: &Value,
+    project: &'a CreateMetaProject,
+) -> Option<&'a CreateMetaIssueType> {
+    project.issuetypes.iter().find(|meta| {
+        identifies(issuetype, "id", &meta.id) || identifies(issuetype, "name", &meta.name)
+    })
+}
+
+// Whether `value` (an object such as `{"key": "REL"}` or a bare string) has the property `prop`
+// set to `expected`.
+fn identifies(value: &Value, prop: &str, expected: &str) -> bool {
+    match value {
+        Value::String(s) => s == expected,
+        _ => value[prop].as_str() == Some(expected),
+    }
+}
+
+// Checks the JSON shape of a value against the field's schema type. Template cells are always
+// strings, so numbers may be given as strings that parse as numbers.
+fn check_type(value: &Value, schema: &FieldSchema) -> Option<String> {
+    lazy_static! {
+        static ref DATE: regex::Regex = regex::Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
+        static ref DATETIME: regex::Regex = regex::Regex::new(r"^\d{4}-\d{2}-\d{2}T").unwrap();
+    }
+
+    let schema_type = schema.field_type.as_str();
+    let ok = match schema_type {
+        // rich text is a string with version 2 of the API, and an ADF document with version 3
+        "string" => value.is_string() || adf::is_document(value),
+        "number" => {
+            value.is_number()
+                || matches!(value.as_str(), Some(s) if s.trim().parse::<f64>().is_ok())
+        }
+        "array" => value.is_array(),
+        "date" => matches!(value.as_str(), Some(s) if DATE.is_match(s)),
+        "datetime" => matches!(value.as_str(), Some(s) if DATETIME.is_match(s)),
+        "any" | "" => true,
+        // user, option, priority, version, component, timetracking, ...
+        _ => value.is_object(),
+    };
+
+    match ok {
+        true => None,
+        false => Some(format!(
+            "expected {} for a field of type `{}`, found {}",
+            expected_shape(schema_type),
+            schema_type,
+            value
+        )),
+    }
+}
+
+fn expected_shape(schema_type: &str) -> &'static str {
+    match schema_type {
+        "string" => "a string",
+        "number" => "a number",
+        "array" => "an array",
+        "date" => "a YYYY-MM-DD date",
+        "datetime" => "an ISO 8601 date-time",
+        _ => "an object",
+    }
+}
+
+// Checks that a value (or each item of an array value) is one of the field's allowed values.
+fn check_allowed_values(value: &Value, allowed: &[Value]) -> Option<String> {
+    if allowed.is_empty() {
+        return None;
+    }
+    let items = match value {
+        Value::Array(items) => items.iter().collect(),
+        _ => vec![value],
+    };
+
+    let not_allowed: Vec<String> = items
+        .into_iter()
+        .filter(|item| {
+            !allowed.iter().any(|a| {
+                ["id", "name", "value", "key"]
+                    .iter()
+                    .any(|prop| matches!(a[*prop].as_str(), Some(e) if identifies(item, prop, e)))
+            })
+        })
+        .map(describe)
+        .collect();
+
+    if not_allowed.is_empty() {
+        return None;
+    }
+
+    let choices: Vec<String> = allowed
+        .iter()
+        .filter_map(|a| {
+            ["name", "value", "key", "id"]
+                .iter()
+                .find_map(|prop| a[*prop].as_str())
+                .map(str::to_string)
+        })
+        .collect();
+
+    Some(format!(
+        "{} is not an allowed value (allowed: {})",
+        not_allowed.join(", "),
+        choices.join(", ")
+    ))
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(items) => items.iter().all(is_blank),
+        Value::Object(map) => map.values().all(is_blank),
+        _ => false,
+    }
+}
+
+// A short, human-readable form of an identifying value, e.g. `"REL"` for `{"key": "REL"}`.
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Object(map) if map.len() == 1 => map.values().next().unwrap().to_string(),
+        Value::Null => "(none)".to_string(),
+        _ => value.to_string(),
+    }
+}
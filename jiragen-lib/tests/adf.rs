@@ -0,0 +1,76 @@
+use jiragen::adf::{from_markdown, to_text};
+use serde_json::json;
+
+#[test]
+fn plain_text_keeps_its_line_breaks() {
+    let doc = from_markdown("First line\nsecond line\n\nNext paragraph");
+
+    assert_eq!(
+        doc,
+        json!({
+            "type": "doc",
+            "version": 1,
+            "content": [
+                {
+                    "type": "paragraph",
+                    "content": [
+                        { "type": "text", "text": "First line" },
+                        { "type": "hardBreak" },
+                        { "type": "text", "text": "second line" },
+                    ],
+                },
+                { "type": "paragraph", "content": [{ "type": "text", "text": "Next paragraph" }] },
+            ],
+        })
+    );
+}
+
+#[test]
+fn markdown_blocks_are_converted() {
+    let doc =
+        from_markdown("## Notes\n> quoted\n- one\n- two\n---\n```rust\nfn main() {}\n```\nThe end");
+
+    let types: Vec<&str> = doc["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|block| block["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        types,
+        [
+            "heading",
+            "blockquote",
+            "bulletList",
+            "rule",
+            "codeBlock",
+            "paragraph"
+        ]
+    );
+    assert_eq!(doc["content"][0]["attrs"]["level"], 2);
+    assert_eq!(doc["content"][2]["content"].as_array().unwrap().len(), 2);
+    assert_eq!(doc["content"][4]["attrs"]["language"], "rust");
+    assert_eq!(doc["content"][4]["content"][0]["text"], "fn main() {}");
+}
+
+#[test]
+fn inline_formatting_becomes_marks() {
+    let doc =
+        from_markdown("Run `cargo test` on my_crate_name, see [docs](https://docs.rs) ~~now~~");
+    let nodes = &doc["content"][0]["content"];
+
+    assert_eq!(
+        nodes[1],
+        json!({ "type": "text", "text": "cargo test", "marks": [{ "type": "code" }] })
+    );
+    assert_eq!(nodes[2]["text"], " on my_crate_name, see ");
+    assert_eq!(
+        nodes[3]["marks"][0],
+        json!({ "type": "link", "attrs": { "href": "https://docs.rs" } })
+    );
+    assert_eq!(nodes[5]["marks"][0]["type"], "strike");
+    assert_eq!(
+        to_text(&doc),
+        "Run cargo test on my_crate_name, see docs now"
+    );
+}
@@ -0,0 +1,87 @@
+use jiragen::{Config, Error, JiraClient, RetryPolicy};
+use jiragen_testkit::config;
+use mockito::Server;
+
+fn client(server: &Server) -> JiraClient {
+    JiraClient::new(Config {
+        retry: RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        },
+        ..config(&server.url())
+    })
+    .unwrap()
+}
+
+#[test]
+fn error_bodies_are_parsed_into_api_errors() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/rest/api/2/issue")
+        .with_status(400)
+        .with_header("x-arequestid", "123x456x1")
+        .with_body(
+            r#"{"errorMessages":["Something went wrong."],
+                "errors":{"summary":"You must specify a summary of the issue."}}"#,
+        )
+        .create();
+
+    let issue = jiragen::JiraIssue {
+        update: None,
+        fields: serde_json::json!({}),
+    };
+    let error = client(&server).create_issue(&issue).unwrap_err();
+
+    mock.assert();
+    match error {
+        Error::Api {
+            status,
+            error_messages,
+            field_errors,
+            request_id,
+        } => {
+            assert_eq!(status, 400);
+            assert_eq!(error_messages, vec!["Something went wrong."]);
+            assert_eq!(
+                field_errors["summary"],
+                "You must specify a summary of the issue."
+            );
+            assert_eq!(request_id.as_deref(), Some("123x456x1"));
+        }
+        e => panic!("expected an API error, got {:?}", e),
+    }
+}
+
+#[test]
+fn api_errors_are_classified_by_status() {
+    let mut server = Server::new();
+    server
+        .mock("GET", "/rest/api/2/project/NOPE")
+        .with_status(404)
+        .with_body(r#"{"errorMessages":["No project could be found with key 'NOPE'."]}"#)
+        .create();
+    server
+        .mock("GET", "/rest/api/2/field")
+        .with_status(401)
+        .with_body("<html>Unauthorized</html>")
+        .create();
+    server
+        .mock("GET", "/rest/api/2/issue/ABC-1")
+        .with_status(429)
+        .create();
+    let jira = client(&server);
+
+    let not_found = jira.get_project("NOPE").unwrap_err();
+    assert!(not_found.is_not_found() && !not_found.is_auth());
+
+    let unauthorized = jira.get_fields().unwrap_err();
+    assert!(unauthorized.is_auth());
+    match unauthorized {
+        Error::Api { error_messages, .. } => {
+            assert_eq!(error_messages, vec!["<html>Unauthorized</html>"])
+        }
+        e => panic!("expected an API error, got {:?}", e),
+    }
+
+    assert!(jira.get_issue("ABC-1").unwrap_err().is_rate_limited());
+}
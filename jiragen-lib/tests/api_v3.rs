@@ -0,0 +1,139 @@
+use jiragen::{
+    adf, ApiVersion, Config, FakeJira, Field, FieldSchema, JiraApi, JiraClient, JiraIssue,
+};
+use jiragen_testkit::MockJira;
+use mockito::{Matcher, Server};
+use serde_json::json;
+
+fn config(jira_url: String, api_version: ApiVersion) -> Config {
+    Config {
+        jira_url,
+        jira_user: "user".to_string(),
+        jira_key: "key".to_string(),
+        api_version,
+        ..Default::default()
+    }
+}
+
+fn issue(description: &str) -> JiraIssue {
+    JiraIssue {
+        update: Some(json!({ "comment": [{ "add": { "body": "Created by *JiraGen*" } }] })),
+        fields: json!({
+            "project": { "key": "ABC" },
+            "issuetype": { "name": "Task" },
+            "summary": "Release",
+            "description": description,
+        }),
+    }
+}
+
+#[test]
+fn v3_sends_rich_text_as_adf() {
+    let jira_mock = MockJira::with_sample_data();
+    let v2 = JiraClient::new(config(jira_mock.url(), ApiVersion::V2)).unwrap();
+    let v3 = JiraClient::new(config(jira_mock.url(), ApiVersion::V3)).unwrap();
+
+    v2.create_issue(&issue("Plain text")).unwrap();
+    let created = v3
+        .bulk_create(&[issue("# Steps\n\n1. Tag **1.0**\n2. Publish")])
+        .unwrap();
+
+    assert!(created.errors.is_empty(), "{:?}", created.errors);
+    let issues = jira_mock.issues();
+    assert_eq!(issues[0].fields["description"], "Plain text");
+    let description = &issues[1].fields["description"];
+    assert!(adf::is_document(description), "{}", description);
+    assert_eq!(description["content"][0]["type"], "heading");
+    assert_eq!(description["content"][1]["type"], "orderedList");
+
+    let fetched = v3.get_issue(&created.issues[0].key).unwrap();
+    assert_eq!(
+        fetched.text("description").unwrap(),
+        "Steps\n\n- Tag 1.0\n- Publish"
+    );
+    assert_eq!(
+        v2.get_issue("ABC-1").unwrap().text("description").unwrap(),
+        "Plain text"
+    );
+}
+
+#[test]
+fn v3_converts_comments_and_keeps_adf() {
+    let document = adf::from_markdown("Already *ADF*");
+    let converted = JiraIssue {
+        update: issue("").update,
+        fields: json!({ "description": document.clone(), "summary": "Not rich text" }),
+    }
+    .to_adf();
+
+    assert_eq!(converted.fields["description"], document);
+    assert_eq!(converted.fields["summary"], "Not rich text");
+    let comment = &converted.update.unwrap()["comment"][0]["add"]["body"];
+    assert_eq!(adf::to_text(comment), "Created by JiraGen");
+}
+
+#[test]
+fn v3_sends_rich_text_custom_fields_as_adf() {
+    let custom = |id: &str, name: &str, custom_type: &str| Field {
+        id: id.to_string(),
+        name: name.to_string(),
+        custom: true,
+        schema: Some(FieldSchema {
+            field_type: "string".to_string(),
+            custom: Some(format!(
+                "com.atlassian.jira.plugin.system.customfieldtypes:{}",
+                custom_type
+            )),
+            ..Default::default()
+        }),
+    };
+    let jira = FakeJira::new().with_api_version(ApiVersion::V3);
+    jira.add_project("ABC", &["Task"])
+        .add_field(custom("customfield_10050", "Release Notes", "textarea"))
+        .add_field(custom("customfield_10060", "Build", "textfield"));
+    let mut issue = issue("Plain text");
+    issue.fields["customfield_10050"] = json!("Tagged **1.0**");
+    issue.fields["customfield_10060"] = json!("1.0.42");
+
+    let adapted = jira.adapt_issues(&[issue]).unwrap();
+    jira.create_issue(&adapted[0]).unwrap();
+
+    let fields = &jira.issues()[0].fields;
+    assert!(adf::is_document(&fields["customfield_10050"]));
+    assert_eq!(adf::to_text(&fields["customfield_10050"]), "Tagged 1.0");
+    assert_eq!(fields["customfield_10060"], "1.0.42");
+}
+
+#[test]
+fn users_are_found_the_way_each_version_expects() {
+    let mut server = Server::new();
+    let user = r#"[{ "accountId": "5b10a2844c20165700ede21g", "displayName": "Jane Doe", "active": true }]"#;
+    let v2 = server
+        .mock("GET", "/rest/api/2/user/search")
+        .match_query(Matcher::UrlEncoded("username".into(), "jane".into()))
+        .with_body(r#"[{ "name": "jane", "key": "JIRAUSER10100", "displayName": "Jane Doe" }]"#)
+        .create();
+    let v3 = server
+        .mock("GET", "/rest/api/3/user/search")
+        .match_query(Matcher::UrlEncoded("query".into(), "jane".into()))
+        .with_body(user)
+        .create();
+
+    let server_users = JiraClient::new(config(server.url(), ApiVersion::V2))
+        .unwrap()
+        .find_users("jane")
+        .unwrap();
+    let cloud_users = JiraClient::new(config(server.url(), ApiVersion::V3))
+        .unwrap()
+        .find_users("jane")
+        .unwrap();
+
+    v2.assert();
+    v3.assert();
+    assert_eq!(server_users[0].name.as_deref(), Some("jane"));
+    assert_eq!(
+        cloud_users[0].account_id.as_deref(),
+        Some("5b10a2844c20165700ede21g")
+    );
+    assert_eq!(cloud_users[0].display_name, "Jane Doe");
+}
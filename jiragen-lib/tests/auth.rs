@@ -0,0 +1,134 @@
+use jiragen::{AuthType, Config, JiraClient};
+use mockito::{Matcher, Server};
+use serde_json::json;
+
+fn client(server: &Server, auth: AuthType) -> JiraClient {
+    JiraClient::new(Config {
+        jira_url: server.url(),
+        jira_user: "user".to_string(),
+        jira_key: "secret".to_string(),
+        auth,
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+fn session(value: &str) -> String {
+    json!({ "session": { "name": "JSESSIONID", "value": value } }).to_string()
+}
+
+#[test]
+fn basic_auth_sends_the_user_and_key() {
+    let mut server = Server::new();
+    // base64 of `user:secret`
+    let fields = server
+        .mock("GET", "/rest/api/2/field")
+        .match_header("authorization", "Basic dXNlcjpzZWNyZXQ=")
+        .with_body("[]")
+        .create();
+
+    client(&server, AuthType::Basic).get_fields().unwrap();
+
+    fields.assert();
+}
+
+#[test]
+fn bearer_auth_sends_the_key_as_a_token() {
+    let mut server = Server::new();
+    let fields = server
+        .mock("GET", "/rest/api/2/field")
+        .match_header("authorization", "Bearer secret")
+        .match_header("cookie", Matcher::Missing)
+        .with_body("[]")
+        .create();
+
+    client(&server, AuthType::Bearer).get_fields().unwrap();
+
+    fields.assert();
+}
+
+#[test]
+fn cookie_auth_logs_in_once_and_sends_the_session_cookie() {
+    let mut server = Server::new();
+    let login = server
+        .mock("POST", "/rest/auth/1/session")
+        .match_header("authorization", Matcher::Missing)
+        .match_body(Matcher::Json(
+            json!({ "username": "user", "password": "secret" }),
+        ))
+        .with_body(session("abc"))
+        .expect(1)
+        .create();
+    let fields = server
+        .mock("GET", "/rest/api/2/field")
+        .match_header("cookie", "JSESSIONID=abc")
+        .match_header("authorization", Matcher::Missing)
+        .with_body("[]")
+        .expect(2)
+        .create();
+    let jira = client(&server, AuthType::Cookie);
+
+    jira.get_fields().unwrap();
+    jira.get_fields().unwrap();
+
+    login.assert();
+    fields.assert();
+}
+
+#[test]
+fn cookie_auth_logs_in_again_when_the_session_expires() {
+    let mut server = Server::new();
+    let first_login = server
+        .mock("POST", "/rest/auth/1/session")
+        .with_body(session("expired"))
+        .expect(1)
+        .create();
+    let second_login = server
+        .mock("POST", "/rest/auth/1/session")
+        .with_body(session("fresh"))
+        .expect(1)
+        .create();
+    let rejected = server
+        .mock("GET", "/rest/api/2/field")
+        .match_header("cookie", "JSESSIONID=expired")
+        .with_status(401)
+        .expect(1)
+        .create();
+    let fields = server
+        .mock("GET", "/rest/api/2/field")
+        .match_header("cookie", "JSESSIONID=fresh")
+        .with_body("[]")
+        .expect(1)
+        .create();
+
+    client(&server, AuthType::Cookie).get_fields().unwrap();
+
+    first_login.assert();
+    rejected.assert();
+    second_login.assert();
+    fields.assert();
+}
+
+#[test]
+fn cookie_auth_logs_in_again_only_once() {
+    let mut server = Server::new();
+    let login = server
+        .mock("POST", "/rest/auth/1/session")
+        .with_body(session("abc"))
+        .expect(2)
+        .create();
+    let rejected = server
+        .mock("GET", "/rest/api/2/field")
+        .with_status(401)
+        .expect(2)
+        .create();
+
+    let result = client(&server, AuthType::Cookie).get_fields();
+
+    match result {
+        Err(jiragen::Error::Api { status, .. }) => assert_eq!(status, 401),
+        other => panic!("expected a 401 error, found {:?}", other),
+    }
+    login.assert();
+    rejected.assert();
+}
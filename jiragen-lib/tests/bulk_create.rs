@@ -0,0 +1,115 @@
+use jiragen::{JiraClient, JiraIssue};
+use jiragen_testkit::config;
+use mockito::{Matcher, Server};
+use serde_json::json;
+
+fn issues(count: usize) -> Vec<JiraIssue> {
+    (0..count)
+        .map(|i| JiraIssue {
+            update: None,
+            fields: json!({ "summary": format!("Issue {}", i) }),
+        })
+        .collect()
+}
+
+fn client(server: &Server) -> JiraClient {
+    JiraClient::new(config(&server.url())).unwrap()
+}
+
+#[test]
+fn bulk_create_sends_chunks_of_at_most_50_issues_in_order() {
+    let mut server = Server::new();
+    let mocks: Vec<_> = [(0, 50), (50, 50), (100, 20)]
+        .iter()
+        .map(|&(first, len)| {
+            let created: Vec<_> = (first..first + len)
+                .map(|i| json!({ "id": i.to_string(), "key": format!("ABC-{}", i), "self": "" }))
+                .collect();
+            server
+                .mock("POST", "/rest/api/2/issue/bulk")
+                .match_body(Matcher::PartialJson(json!({
+                    "issueUpdates": [{ "fields": { "summary": format!("Issue {}", first) } }]
+                })))
+                .with_status(201)
+                .with_body(json!({ "issues": created, "errors": [] }).to_string())
+                .create()
+        })
+        .collect();
+
+    let response = client(&server).bulk_create(&issues(120)).unwrap();
+
+    for mock in mocks {
+        mock.assert();
+    }
+    let keys: Vec<String> = response.issues.into_iter().map(|i| i.key).collect();
+    let expected: Vec<String> = (0..120).map(|i| format!("ABC-{}", i)).collect();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn bulk_create_chunked_offsets_failed_element_numbers() {
+    let mut server = Server::new();
+    let first = server
+        .mock("POST", "/rest/api/2/issue/bulk")
+        .match_body(Matcher::PartialJson(json!({
+            "issueUpdates": [{ "fields": { "summary": "Issue 0" } }]
+        })))
+        .with_status(201)
+        .with_body(
+            r#"{"issues":[{"id":"1","key":"ABC-1","self":""}],
+                "errors":[{"status":400,"elementErrors":{},"failedElementNumber":1}]}"#,
+        )
+        .create();
+    let second = server
+        .mock("POST", "/rest/api/2/issue/bulk")
+        .match_body(Matcher::PartialJson(json!({
+            "issueUpdates": [{ "fields": { "summary": "Issue 2" } }]
+        })))
+        .with_status(201)
+        .with_body(
+            r#"{"issues":[{"id":"2","key":"ABC-2","self":""}],
+                "errors":[{"status":400,"elementErrors":{},"failedElementNumber":0}]}"#,
+        )
+        .create();
+
+    let response = client(&server).bulk_create_chunked(&issues(4), 2).unwrap();
+
+    first.assert();
+    second.assert();
+    assert_eq!(response.issues.len(), 2);
+    let failed: Vec<usize> = response
+        .errors
+        .iter()
+        .map(|e| e.failed_element_number)
+        .collect();
+    assert_eq!(failed, vec![1, 2]);
+}
+
+#[test]
+fn bulk_create_parses_the_errors_of_a_chunk_that_failed_entirely() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/rest/api/2/issue/bulk")
+        .with_status(400)
+        .with_body(
+            r#"{"issues":[],"errors":[{"status":400,"failedElementNumber":0,
+                "elementErrors":{"errorMessages":[],"errors":{"summary":"Summary is required."}}}]}"#,
+        )
+        .create();
+
+    let response = client(&server).bulk_create(&issues(1)).unwrap();
+
+    mock.assert();
+    assert!(response.issues.is_empty());
+    assert_eq!(
+        response.errors[0].element_errors.errors["summary"],
+        "Summary is required."
+    );
+}
+
+#[test]
+fn bulk_create_chunked_rejects_a_chunk_size_of_zero() {
+    let server = Server::new();
+
+    assert!(client(&server).bulk_create_chunked(&issues(1), 0).is_err());
+}
@@ -0,0 +1,165 @@
+use jiragen::{CacheOptions, Config, Error, JiraClient};
+use jiragen_testkit::{config, MockField, MockJira};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn cache_dir(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("jiragen-{}-cache-{}", std::process::id(), name))
+}
+
+fn client(jira_url: &str, cache: CacheOptions) -> JiraClient {
+    JiraClient::new(Config {
+        cache,
+        ..config(jira_url)
+    })
+    .unwrap()
+}
+
+fn field_ids(jira: &JiraClient) -> Vec<String> {
+    jira.get_fields()
+        .unwrap()
+        .into_iter()
+        .map(|f| f.id)
+        .collect()
+}
+
+#[test]
+fn metadata_is_cached_until_refreshed() {
+    let jira_mock = MockJira::with_sample_data();
+    let cache = CacheOptions {
+        dir: Some(cache_dir("refresh")),
+        ..Default::default()
+    };
+
+    let cached = field_ids(&client(&jira_mock.url(), cache.clone()));
+    jira_mock.add_field(MockField::custom(
+        "customfield_10020",
+        "Team",
+        "string",
+        None,
+    ));
+
+    assert_eq!(field_ids(&client(&jira_mock.url(), cache.clone())), cached);
+    let refreshed = field_ids(&client(
+        &jira_mock.url(),
+        CacheOptions {
+            refresh: true,
+            ..cache.clone()
+        },
+    ));
+    assert!(refreshed.contains(&"customfield_10020".to_string()));
+    assert_eq!(field_ids(&client(&jira_mock.url(), cache)), refreshed);
+}
+
+#[test]
+fn expired_metadata_is_requested_again() {
+    let jira_mock = MockJira::with_sample_data();
+    let cache = CacheOptions {
+        dir: Some(cache_dir("ttl")),
+        ttl_secs: Some(0),
+        ..Default::default()
+    };
+
+    field_ids(&client(&jira_mock.url(), cache.clone()));
+    jira_mock.add_field(MockField::custom(
+        "customfield_10020",
+        "Team",
+        "string",
+        None,
+    ));
+
+    assert!(field_ids(&client(&jira_mock.url(), cache)).contains(&"customfield_10020".to_string()));
+}
+
+#[test]
+fn offline_clients_only_use_the_cache() {
+    let jira_mock = MockJira::with_sample_data();
+    let jira_url = jira_mock.url();
+    let cache = CacheOptions {
+        dir: Some(cache_dir("offline")),
+        ..Default::default()
+    };
+    let online = client(&jira_url, cache.clone());
+    online.get_project("ABC").unwrap();
+    drop(jira_mock);
+
+    let offline = client(
+        &jira_url,
+        CacheOptions {
+            offline: true,
+            ..cache.clone()
+        },
+    );
+
+    assert_eq!(offline.get_project("ABC").unwrap().name, "Alphabet");
+    let error = |e: Error| match e {
+        Error::CustomError(e) => e.message,
+        e => panic!("unexpected error: {:?}", e),
+    };
+    assert_eq!(
+        error(offline.get_fields().unwrap_err()),
+        "Not cached for offline use"
+    );
+    assert_eq!(
+        error(offline.get_issue("ABC-1").unwrap_err()),
+        "JIRA can't be contacted offline"
+    );
+
+    cache.clear().unwrap();
+    assert_eq!(
+        error(offline.get_project("ABC").unwrap_err()),
+        "Not cached for offline use"
+    );
+}
+
+#[test]
+fn requests_are_cached_apart_however_alike_or_long() {
+    let jira_mock = MockJira::with_sample_data();
+    let jira_url = jira_mock.url();
+    let cache = CacheOptions {
+        dir: Some(cache_dir("keys")),
+        ..Default::default()
+    };
+    let long_query = "jane".repeat(100);
+    let online = client(&jira_url, cache.clone());
+    online.find_users("jane doe").unwrap();
+    online.find_users(&long_query).unwrap();
+    drop(jira_mock);
+
+    let offline = client(
+        &jira_url,
+        CacheOptions {
+            offline: true,
+            ..cache
+        },
+    );
+
+    assert!(offline.find_users("jane doe").is_ok());
+    assert!(offline.find_users(&long_query).is_ok());
+    // it would share a file name with `jane doe` if names were only sanitized
+    assert!(offline.find_users("jane_doe").is_err());
+}
+
+#[test]
+fn clearing_only_removes_what_the_cache_created() {
+    let jira_mock = MockJira::with_sample_data();
+    let dir = cache_dir("clear");
+    let other_dir = dir.join("notes");
+    fs::create_dir_all(&other_dir).unwrap();
+    fs::write(dir.join("settings.json"), "{}").unwrap();
+    fs::write(other_dir.join("todo.json"), "[]").unwrap();
+    let cache = CacheOptions {
+        dir: Some(dir.clone()),
+        ..Default::default()
+    };
+    field_ids(&client(&jira_mock.url(), cache.clone()));
+    let sites = || fs::read_dir(&dir).unwrap().count();
+    assert_eq!(sites(), 3);
+
+    cache.clear().unwrap();
+
+    assert_eq!(sites(), 2);
+    assert!(dir.join("settings.json").exists());
+    assert!(other_dir.join("todo.json").exists());
+}
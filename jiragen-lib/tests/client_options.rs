@@ -0,0 +1,61 @@
+use jiragen::{ClientOptions, Config, JiraClient};
+use mockito::Server;
+use std::path::PathBuf;
+
+fn config(url: String, client: ClientOptions) -> Config {
+    Config {
+        jira_url: url,
+        jira_user: "user".to_string(),
+        jira_key: "key".to_string(),
+        client,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn extra_headers_are_sent_with_every_request() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("GET", "/rest/api/2/field")
+        .match_header("x-team", "platform")
+        .match_header("accept", "application/json")
+        .with_body("[]")
+        .create();
+    let options = ClientOptions {
+        headers: [("X-Team".to_string(), "platform".to_string())].into(),
+        timeout_secs: Some(5),
+        ..Default::default()
+    };
+
+    let jira = JiraClient::new(config(server.url(), options)).unwrap();
+    jira.get_fields().unwrap();
+
+    mock.assert();
+}
+
+#[test]
+fn invalid_options_are_errors() {
+    let invalid = [
+        ClientOptions {
+            headers: [("Bad Header".to_string(), "value".to_string())].into(),
+            ..Default::default()
+        },
+        ClientOptions {
+            ca_cert: Some(PathBuf::from("does-not-exist.pem")),
+            ..Default::default()
+        },
+        ClientOptions {
+            client_cert: Some(PathBuf::from("client.pem")),
+            ..Default::default()
+        },
+        ClientOptions {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        },
+    ];
+
+    for options in invalid {
+        let result = JiraClient::new(config("https://example.com".to_string(), options.clone()));
+        assert!(result.is_err(), "{:?} should be invalid", options);
+    }
+}
@@ -0,0 +1,115 @@
+use jiragen::{DeploymentType, JiraIssue};
+use jiragen_testkit::{client, MockJira};
+use serde_json::{json, Value};
+
+fn issue(issue_type: &str, fields: Value) -> JiraIssue {
+    let mut issue = json!({ "project": { "key": "ABC" }, "issuetype": { "name": issue_type } });
+    issue
+        .as_object_mut()
+        .unwrap()
+        .extend(fields.as_object().unwrap().clone());
+    JiraIssue {
+        update: None,
+        fields: issue,
+    }
+}
+
+#[test]
+fn server_info_is_detected() {
+    let cloud = MockJira::with_sample_data();
+    let server = MockJira::with_sample_server_data();
+
+    let cloud_info = client(&cloud).server_info().unwrap();
+    let server_info = client(&server).server_info().unwrap();
+
+    assert_eq!(cloud_info.deployment_type, DeploymentType::Cloud);
+    assert_eq!(cloud_info.base_url, cloud.url());
+    assert_eq!(server_info.deployment_type, DeploymentType::Server);
+    assert_eq!(server_info.version, "9.12.2");
+    assert_eq!(server_info.version_numbers, [9, 12, 2]);
+}
+
+#[test]
+fn cloud_issues_refer_to_users_by_account_id_and_epics_by_parent() {
+    let jira_mock = MockJira::with_sample_data();
+    let jira = client(&jira_mock);
+    jira.create_issue(&issue("Epic", json!({ "summary": "Release" })))
+        .unwrap();
+
+    let issues = jira
+        .adapt_issues(&[
+            issue(
+                "Task",
+                json!({ "summary": "Tag it", "assignee": { "name": "jane@example.com" }, "epic": "ABC-1" }),
+            ),
+            issue(
+                "Bug",
+                json!({ "summary": "Fix it", "assignee": { "accountId": "5b10a2844c20165700ede21g" } }),
+            ),
+        ])
+        .unwrap();
+    let response = jira.bulk_create(&issues).unwrap();
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let issues = jira_mock.issues();
+    for issue in &issues[1..] {
+        assert_eq!(
+            issue.fields["assignee"],
+            json!({ "accountId": "5b10a2844c20165700ede21g" })
+        );
+    }
+    assert_eq!(issues[1].fields["parent"], json!({ "key": "ABC-1" }));
+    assert!(!issues[1].fields.contains_key("epic"));
+}
+
+#[test]
+fn server_issues_refer_to_users_by_name_and_epics_by_epic_link() {
+    let jira_mock = MockJira::with_sample_server_data();
+    let jira = client(&jira_mock);
+    jira.create_issue(&issue("Epic", json!({ "summary": "Release" })))
+        .unwrap();
+
+    let task = jira
+        .adapt_issues(&[issue(
+            "Task",
+            json!({ "summary": "Tag it", "assignee": { "accountId": "jane" }, "epic": "ABC-1" }),
+        )])
+        .unwrap();
+    let created = jira.create_issue(&task[0]).unwrap();
+
+    assert_eq!(created.key, "ABC-2");
+    let task = &jira_mock.issues()[1];
+    assert_eq!(task.fields["assignee"], json!({ "name": "jane" }));
+    assert_eq!(task.fields["customfield_10014"], "ABC-1");
+}
+
+#[test]
+fn searches_are_paged_on_both_deployments() {
+    for jira_mock in [
+        MockJira::with_sample_data(),
+        MockJira::with_sample_server_data(),
+    ] {
+        let jira = client(&jira_mock);
+        let summaries = ["One", "Two", "Three"];
+        let issues: Vec<JiraIssue> = summaries
+            .iter()
+            .map(|s| issue("Task", json!({ "summary": s })))
+            .collect();
+        jira.bulk_create(&issues).unwrap();
+
+        let mut keys = Vec::new();
+        let mut page_token = None;
+        loop {
+            let page = jira
+                .search_page("project = ABC", page_token.as_deref(), 2)
+                .unwrap();
+            keys.extend(page.issues.into_iter().map(|i| i.key));
+            page_token = match page.next_page_token {
+                Some(token) => Some(token),
+                None => break,
+            };
+        }
+
+        assert_eq!(keys, ["ABC-1", "ABC-2", "ABC-3"]);
+    }
+}
@@ -0,0 +1,197 @@
+use jiragen::{
+    DeploymentType, Error, FakeJira, IssueFields, JiraApi, JiraIssue, Jql, Order, User, EPIC_FIELD,
+};
+use jiragen_testkit::task;
+use serde_json::json;
+
+fn jane() -> User {
+    User {
+        account_id: Some("5b10a2844c20165700ede21g".to_string()),
+        name: Some("jane".to_string()),
+        key: None,
+        display_name: "Jane Doe".to_string(),
+        email_address: Some("jane@example.com".to_string()),
+        active: true,
+    }
+}
+
+// Code under test only knows the trait.
+fn create_all(jira: &dyn JiraApi, summaries: &[&str]) -> Result<Vec<String>, Error> {
+    let issues: Vec<JiraIssue> = summaries.iter().map(|s| task(s)).collect();
+    let response = jira.bulk_create(&issues)?;
+    Ok(response.issues.into_iter().map(|i| i.key).collect())
+}
+
+#[test]
+fn issues_are_created_and_found() {
+    let jira = FakeJira::new();
+    jira.add_project("ABC", &["Task", "Bug"]);
+
+    let keys = create_all(&jira, &["One", "Two", "Three"]).unwrap();
+
+    assert_eq!(keys, ["ABC-1", "ABC-2", "ABC-3"]);
+    assert_eq!(jira.get_issue("ABC-2").unwrap().fields["summary"], "Two");
+    assert!(jira.get_issue("ABC-4").unwrap_err().is_not_found());
+    assert_eq!(jira.get_project("ABC").unwrap().issue_types.len(), 2);
+
+    let first = jira.search_page("project = ABC", None, 2).unwrap();
+    let second = jira
+        .search_page("project = ABC", first.next_page_token.as_deref(), 2)
+        .unwrap();
+    assert_eq!(first.issues.len(), 2);
+    assert_eq!(second.issues[0].key, "ABC-3");
+    assert_eq!(second.next_page_token, None);
+    assert_eq!(jira.search("key = ABC-1", 0, 50).unwrap().total, 1);
+}
+
+#[test]
+fn invalid_issues_are_rejected() {
+    let jira = FakeJira::new();
+    jira.add_project("ABC", &["Task"]);
+    let issues = [
+        task("Valid"),
+        JiraIssue::from(IssueFields::new().project("ABC").issue_type("Task")),
+        JiraIssue::from(
+            IssueFields::new()
+                .project("ABC")
+                .issue_type("Story")
+                .summary("Unknown type"),
+        ),
+    ];
+
+    let response = jira.bulk_create(&issues).unwrap();
+
+    assert_eq!(response.issues.len(), 1);
+    let failed: Vec<usize> = response
+        .errors
+        .iter()
+        .map(|e| e.failed_element_number)
+        .collect();
+    assert_eq!(failed, [1, 2]);
+    assert!(response.errors[0]
+        .element_errors
+        .errors
+        .contains_key("summary"));
+    match jira.create_issue(&task("")) {
+        Err(Error::Api { status: 400, .. }) => {}
+        other => panic!("expected a 400, got {:?}", other),
+    }
+    assert_eq!(jira.issues().len(), 1);
+}
+
+#[test]
+fn users_are_only_resolved_by_exact_email_address_or_name() {
+    let jira = FakeJira::new();
+    jira.add_project("ABC", &["Task"]).add_user(jane());
+    let assigned = |assignee: &str| {
+        let mut issue = task("Tag it");
+        issue.fields["assignee"] = json!({ "name": assignee });
+        jira.adapt_issues(&[issue]).unwrap().remove(0).fields["assignee"].clone()
+    };
+
+    let account_id = json!({ "accountId": "5b10a2844c20165700ede21g" });
+    assert_eq!(assigned("Jane@Example.com"), account_id);
+    assert_eq!(assigned("jane"), account_id);
+    // the only user found, but only by part of their name or email address
+    assert_eq!(assigned("jan"), json!({ "name": "jan" }));
+    assert_eq!(assigned("Jane Doe"), json!({ "name": "Jane Doe" }));
+}
+
+#[test]
+fn issues_are_adapted_to_the_deployment() {
+    let jira = FakeJira::new();
+    jira.add_project("ABC", &["Epic", "Task"]).add_user(jane());
+    let epic: JiraIssue = IssueFields::new()
+        .project("ABC")
+        .issue_type("Epic")
+        .summary("Release")
+        .into();
+    jira.create_issue(&epic).unwrap();
+
+    let cloud_task: JiraIssue = IssueFields::new()
+        .project("ABC")
+        .issue_type("Task")
+        .summary("Tag it")
+        .assignee_name("jane@example.com")
+        .custom(EPIC_FIELD, "ABC-1")
+        .into();
+    let cloud_task = jira.adapt_issues(&[cloud_task]).unwrap();
+    jira.create_issue(&cloud_task[0]).unwrap();
+    jira.set_deployment(DeploymentType::Server);
+    let mut server_task = task("Publish");
+    server_task.fields["assignee"] = json!({ "accountId": "jane" });
+    let server_task = jira.adapt_issues(&[server_task]).unwrap();
+
+    let issues = jira.issues();
+    assert_eq!(
+        issues[1].fields["assignee"],
+        json!({ "accountId": "5b10a2844c20165700ede21g" })
+    );
+    assert_eq!(issues[1].fields["parent"], json!({ "key": "ABC-1" }));
+    assert_eq!(server_task[0].fields["assignee"], json!({ "name": "jane" }));
+    assert_eq!(
+        jira.server_info().unwrap().deployment_type,
+        DeploymentType::Server
+    );
+}
+
+#[test]
+fn searches_answer_the_queries_jql_builds() {
+    let jira = FakeJira::new();
+    jira.add_project("ABC", &["Task", "Bug"]);
+    let bug = IssueFields::new()
+        .project("ABC")
+        .issue_type("Bug")
+        .summary("Crash on start")
+        .description("The app crashes and burns on start-up")
+        .labels(&["release and qa"]);
+    jira.bulk_create(&[task("Tag the release"), bug.into()])
+        .unwrap();
+    let keys = |jql: Jql| -> Vec<String> {
+        let results = jira.search(&jql.to_string(), 0, 50).unwrap();
+        results.issues.into_iter().map(|i| i.key).collect()
+    };
+
+    assert_eq!(keys(Jql::new().status_in(&["Done", "To Do"])).len(), 2);
+    assert_eq!(keys(Jql::new().text("crashes start-up")), ["ABC-2"]);
+    assert_eq!(keys(Jql::new().label("release and qa")), ["ABC-2"]);
+    assert_eq!(
+        keys(Jql::new().clause("issuetype != Bug").project("ABC")),
+        ["ABC-1"]
+    );
+    assert_eq!(keys(Jql::new().labels_in(&["qa"])), Vec::<String>::new());
+    assert_eq!(keys(Jql::new().created_after("-1d")).len(), 2);
+    assert_eq!(keys(Jql::new().created_before("2000-01-01")).len(), 0);
+    assert_eq!(
+        keys(
+            Jql::new()
+                .updated_after("2000-01-01 09:00")
+                .order_by("created", Order::Desc)
+        )
+        .len(),
+        2
+    );
+}
+
+#[test]
+fn unsupported_searches_are_rejected() {
+    let jira = FakeJira::new();
+    jira.add_project("ABC", &["Task"]);
+
+    for jql in [
+        "project = ABC OR project = DEF",
+        "assignee = currentUser()",
+        "(project = ABC)",
+        "\"Story Points\" = 3",
+        "summary >= \"A\"",
+        "created >= \"yesterday\"",
+        "created >= \"1é\"",
+        "summary ~ \"unclosed",
+        "project",
+    ] {
+        match jira.search(jql, 0, 50) {
+            Err(Error::Api { status: 400, .. }) => {}
+            other => panic!("expected a 400 for {}, got {:?}", jql, other),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use jiragen::{csv_to_json, Config, IssueFields, IssueUpdate, JiraClient, JiraIssue};
+use jiragen_testkit::MockJira;
+use serde_json::json;
+
+#[test]
+fn fields_serialize_as_template_columns_do() {
+    let headers = vec![
+        "project.key",
+        "issuetype.name",
+        "summary",
+        "priority.name",
+        "fixVersions[].name",
+        "fixVersions[].name",
+        "duedate",
+        "parent.key",
+        "timetracking.originalEstimate",
+        "timetracking.remainingEstimate",
+    ];
+    let records = vec![csv::StringRecord::from(vec![
+        "ABC",
+        "Task",
+        "Tag it",
+        "High",
+        "1.0",
+        "1.1",
+        "2026-11-02",
+        "ABC-1",
+        "1d",
+        "4h",
+    ])];
+
+    let fields = IssueFields::new()
+        .project("ABC")
+        .issue_type("Task")
+        .summary("Tag it")
+        .priority("High")
+        .fix_versions(&["1.0", "1.1"])
+        .due_date("2026-11-02")
+        .parent("ABC-1")
+        .time_tracking("1d", Some("4h"));
+
+    assert_eq!(
+        csv_to_json(headers, records).unwrap()[0],
+        serde_json::to_value(&fields).unwrap()
+    );
+}
+
+#[test]
+fn later_values_replace_earlier_ones() {
+    let fields = IssueFields::new()
+        .assignee("5b10a2844c20165700ede21g")
+        .assignee_name("jane")
+        .custom("customfield_10010", json!(5));
+
+    assert_eq!(fields.get("assignee"), Some(&json!({ "name": "jane" })));
+    assert_eq!(fields.get("customfield_10010"), Some(&json!(5)));
+    assert_eq!(fields.get("summary"), None);
+}
+
+#[test]
+fn issues_are_created_from_fields() {
+    let jira_mock = MockJira::with_sample_data();
+    let jira = JiraClient::new(Config {
+        jira_url: jira_mock.url(),
+        jira_user: "user".to_string(),
+        jira_key: "key".to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+    let fields = IssueFields::new()
+        .project("ABC")
+        .issue_type("Task")
+        .summary("Tag the release")
+        .description("Tag *1.0* on main")
+        .priority("High")
+        .labels(&["release"])
+        .components(&["Backend"])
+        .assignee_name("jane@example.com")
+        .custom("customfield_10010", 3);
+
+    let issue: JiraIssue = fields
+        .clone()
+        .with_update(IssueUpdate::new().add_comment("Created by the release service"));
+    let issues = jira.adapt_issues(&[issue]).unwrap();
+    let created = jira.create_issue(&issues[0]).unwrap();
+
+    assert_eq!(created.key, "ABC-1");
+    let created = &jira_mock.issues()[0];
+    assert_eq!(created.fields["priority"], json!({ "name": "High" }));
+    assert_eq!(
+        created.fields["assignee"],
+        json!({ "accountId": "5b10a2844c20165700ede21g" })
+    );
+    assert_eq!(created.fields["customfield_10010"], 3);
+    assert_eq!(
+        created.fields["comment"]["comments"][0]["body"],
+        "Created by the release service"
+    );
+    assert_eq!(JiraIssue::from(fields).update, None);
+}
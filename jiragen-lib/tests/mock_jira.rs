@@ -0,0 +1,61 @@
+use jiragen::{validate_fields, AuthType, Config, JiraClient};
+use jiragen_testkit::{client, config, task, MockJira};
+use serde_json::json;
+
+#[test]
+fn created_issues_can_be_fetched_and_searched() {
+    let jira_mock = MockJira::with_sample_data();
+    let jira = client(&jira_mock);
+
+    let created = jira.create_issue(&task("First")).unwrap();
+    jira.bulk_create(&[task("Second"), task("Third")]).unwrap();
+
+    assert_eq!(created.key, "ABC-1");
+    assert_eq!(jira.get_issue("ABC-1").unwrap().fields["summary"], "First");
+    let results = jira
+        .search("project = ABC AND issuetype = Task ORDER BY key", 1, 1)
+        .unwrap();
+    assert_eq!(results.total, 3);
+    assert_eq!(results.issues[0].key, "ABC-2");
+}
+
+#[test]
+fn createmeta_validates_issues() {
+    let jira_mock = MockJira::with_sample_data();
+    let jira = client(&jira_mock);
+
+    let createmeta = jira.get_createmeta("ABC").unwrap();
+    let issues = [
+        task("Valid").fields,
+        json!({ "project": { "key": "ABC" }, "issuetype": { "name": "Bug" }, "customfield_10010": 3 }),
+    ];
+    let violations = validate_fields(&issues, &createmeta);
+
+    let mut fields: Vec<&str> = violations.iter().map(|v| v.field.as_str()).collect();
+    fields.sort();
+    assert!(violations.iter().all(|v| v.index == 1));
+    assert_eq!(fields, ["customfield_10010", "summary"]);
+}
+
+#[test]
+fn credentials_are_checked_when_required() {
+    let jira_mock = MockJira::with_sample_data();
+    jira_mock.require_credentials("user", "key");
+
+    assert!(client(&jira_mock).get_fields().is_ok());
+    let cookie = JiraClient::new(Config {
+        auth: AuthType::Cookie,
+        ..config(&jira_mock.url())
+    })
+    .unwrap();
+    assert!(cookie.get_fields().is_ok());
+
+    let wrong_key = JiraClient::new(Config {
+        jira_url: jira_mock.url(),
+        jira_user: "user".to_string(),
+        jira_key: "wrong".to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(wrong_key.get_fields().unwrap_err().is_auth());
+}
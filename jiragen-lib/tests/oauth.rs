@@ -0,0 +1,173 @@
+use jiragen::{AuthType, ClientOptions, Config, JiraClient, OAuthConfig, OAuthToken};
+use mockito::{Matcher, Server};
+use reqwest::Url;
+use serde_json::json;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn oauth_config(server: &Server, name: &str) -> OAuthConfig {
+    OAuthConfig {
+        redirect_port: free_port(),
+        auth_url: format!("{}/authorize", server.url()),
+        token_url: format!("{}/oauth/token", server.url()),
+        api_url: server.url(),
+        ..OAuthConfig::new(
+            "client",
+            "secret",
+            std::env::temp_dir().join(format!("jiragen-oauth-test-{}.json", name)),
+        )
+    }
+}
+
+#[test]
+fn login_exchanges_the_code_and_stores_the_token() {
+    let mut server = Server::new();
+    let token_mock = server
+        .mock("POST", "/oauth/token")
+        .match_body(Matcher::PartialJson(json!({
+            "grant_type": "authorization_code",
+            "code": "the-code",
+        })))
+        .match_header("x-gateway", "jiragen")
+        .with_body(r#"{"access_token":"access-1","refresh_token":"refresh-1","expires_in":3600}"#)
+        .create();
+    let resources_mock = server
+        .mock("GET", "/oauth/token/accessible-resources")
+        .match_header("authorization", "Bearer access-1")
+        .match_header("x-gateway", "jiragen")
+        .with_body(r#"[{"id":"cloud-1","url":"https://example.atlassian.net","name":"example"}]"#)
+        .create();
+    let oauth = oauth_config(&server, "login");
+    // the authorization server is sent the client options' headers, like JIRA
+    let options = ClientOptions {
+        headers: [("X-Gateway".to_string(), "jiragen".to_string())].into(),
+        ..Default::default()
+    };
+
+    let token = oauth
+        .login(
+            &options,
+            Some("https://example.atlassian.net/"),
+            |auth_url| {
+                // stand in for the browser: follow the redirect back to the loopback listener
+                let auth_url = Url::parse(auth_url).unwrap();
+                let param = |name: &str| {
+                    auth_url
+                        .query_pairs()
+                        .find(|(k, _)| k == name)
+                        .map(|(_, v)| v.into_owned())
+                        .unwrap()
+                };
+                let redirect = format!(
+                    "{}?code=the-code&state={}",
+                    param("redirect_uri"),
+                    param("state")
+                );
+                let favicon = Url::parse(&redirect).unwrap().join("/favicon.ico").unwrap();
+                thread::spawn(move || {
+                    // a preconnect that sends nothing, and the favicon, come before the redirect
+                    drop(TcpStream::connect(("127.0.0.1", favicon.port().unwrap())).unwrap());
+                    let favicon = reqwest::blocking::get(favicon).unwrap();
+                    assert_eq!(favicon.status(), 404);
+                    reqwest::blocking::get(&redirect).unwrap()
+                });
+            },
+        )
+        .unwrap();
+
+    token_mock.assert();
+    resources_mock.assert();
+    assert_eq!(token.access_token, "access-1");
+    assert_eq!(token.cloud_id, "cloud-1");
+    assert_eq!(
+        OAuthToken::load(&oauth.token_file).unwrap().refresh_token,
+        "refresh-1"
+    );
+}
+
+#[test]
+fn client_refreshes_expired_tokens_and_uses_the_api_gateway() {
+    let mut server = Server::new();
+    let refresh_mock = server
+        .mock("POST", "/oauth/token")
+        .match_body(Matcher::PartialJson(json!({
+            "grant_type": "refresh_token",
+            "refresh_token": "refresh-1",
+        })))
+        .match_header("x-gateway", "jiragen")
+        .with_body(r#"{"access_token":"access-2","refresh_token":"refresh-2","expires_in":3600}"#)
+        .expect(1)
+        .create();
+    let fields_mock = server
+        .mock("GET", "/ex/jira/cloud-1/rest/api/2/field")
+        .match_header("authorization", "Bearer access-2")
+        .with_body(r#"[{"id":"summary","name":"Summary","custom":false}]"#)
+        .expect(2)
+        .create();
+    let oauth = oauth_config(&server, "refresh");
+    OAuthToken {
+        access_token: "access-1".to_string(),
+        refresh_token: "refresh-1".to_string(),
+        expires_at: 0,
+        cloud_id: "cloud-1".to_string(),
+    }
+    .save(&oauth.token_file)
+    .unwrap();
+
+    let jira = JiraClient::new(Config {
+        auth: AuthType::OAuth,
+        oauth: Some(oauth.clone()),
+        client: ClientOptions {
+            headers: [("X-Gateway".to_string(), "jiragen".to_string())].into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(jira.get_fields().unwrap()[0].id, "summary");
+    assert_eq!(jira.get_fields().unwrap()[0].id, "summary");
+
+    refresh_mock.assert();
+    fields_mock.assert();
+    assert_eq!(
+        OAuthToken::load(&oauth.token_file).unwrap().refresh_token,
+        "refresh-2"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn secrets_are_saved_readable_only_by_the_user() {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let server = Server::new();
+    let oauth = oauth_config(&server, "permissions");
+    let config_path = std::env::temp_dir().join("jiragen-oauth-test-permissions-config.json");
+    // a file that already exists is restricted as well
+    fs::write(&oauth.token_file, "{}").unwrap();
+    fs::set_permissions(&oauth.token_file, fs::Permissions::from_mode(0o644)).unwrap();
+
+    OAuthToken {
+        access_token: "access-1".to_string(),
+        refresh_token: "refresh-1".to_string(),
+        expires_at: 0,
+        cloud_id: "cloud-1".to_string(),
+    }
+    .save(&oauth.token_file)
+    .unwrap();
+    oauth.save(&config_path).unwrap();
+
+    for path in [&oauth.token_file, &config_path] {
+        let mode = fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600, "{}", path.display());
+    }
+}
@@ -0,0 +1,142 @@
+use jiragen::{AuthType, ClientOptions, Config, JiraClient, Recording, SearchOptions, REDACTED};
+use jiragen_testkit::{task, MockJira};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn recording_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("jiragen-{}-{}.json", std::process::id(), name))
+}
+
+fn config(jira_url: String, auth: AuthType, client: ClientOptions) -> Config {
+    Config {
+        jira_url,
+        jira_user: "user".to_string(),
+        jira_key: "s3cr3t-key".to_string(),
+        auth,
+        client,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn recordings_replay_without_jira() {
+    let path = recording_path("replay");
+    let jira_mock = MockJira::with_sample_data();
+    let recorded = {
+        let record = ClientOptions {
+            record: Some(path.clone()),
+            ..Default::default()
+        };
+        let jira = JiraClient::new(config(jira_mock.url(), AuthType::Basic, record)).unwrap();
+        let created = jira.bulk_create(&[task("One"), task("Two")]).unwrap();
+        let project = jira.get_project("ABC").unwrap();
+        assert!(jira.get_project("XYZ").is_err());
+        (created, project)
+    };
+    drop(jira_mock);
+
+    let replay = ClientOptions {
+        replay: Some(path),
+        ..Default::default()
+    };
+    let jira = JiraClient::new(config(
+        "http://127.0.0.1:9".to_string(),
+        AuthType::Basic,
+        replay,
+    ))
+    .unwrap();
+
+    let created = jira.bulk_create(&[task("One"), task("Two")]).unwrap();
+    assert_eq!(format!("{:?}", created), format!("{:?}", recorded.0));
+    let project = jira.get_project("ABC").unwrap();
+    assert_eq!(format!("{:?}", project), format!("{:?}", recorded.1));
+    assert!(jira.get_project("XYZ").unwrap_err().is_not_found());
+    // every recorded response has been replayed
+    assert!(jira.get_project("ABC").is_err());
+}
+
+#[test]
+fn recordings_redact_credentials() {
+    let path = recording_path("redact");
+    let jira_mock = MockJira::with_sample_data();
+    jira_mock.require_credentials("user", "s3cr3t-key");
+    let record = ClientOptions {
+        record: Some(path.clone()),
+        ..Default::default()
+    };
+
+    let jira = JiraClient::new(config(jira_mock.url(), AuthType::Cookie, record)).unwrap();
+    jira.get_fields().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(!contents.contains("s3cr3t-key"), "{}", contents);
+    assert!(!contents.contains("mock-session"), "{}", contents);
+    assert!(!contents.contains(&jira_mock.url()), "{}", contents);
+
+    let recording = Recording::from_path(&path).unwrap();
+    let paths: Vec<&str> = recording
+        .exchanges
+        .iter()
+        .map(|e| e.request.path.as_str())
+        .collect();
+    assert_eq!(paths, ["/rest/auth/1/session", "/rest/api/2/field"]);
+    let login = &recording.exchanges[0];
+    assert_eq!(login.request.body.as_ref().unwrap()["password"], REDACTED);
+    assert_eq!(login.response.body["session"]["value"], REDACTED);
+}
+
+#[test]
+fn recordings_keep_page_tokens() {
+    let path = recording_path("pages");
+    let jira_mock = MockJira::with_sample_data();
+    let options = SearchOptions {
+        page_size: 2,
+        ..Default::default()
+    };
+    let search = |jira: &JiraClient| -> Vec<String> {
+        jira.search_iter("project = ABC", options.clone())
+            .map(|issue| issue.unwrap().key)
+            .collect()
+    };
+    let record = ClientOptions {
+        record: Some(path.clone()),
+        ..Default::default()
+    };
+    let jira = JiraClient::new(config(jira_mock.url(), AuthType::Basic, record)).unwrap();
+    jira.bulk_create(&[task("One"), task("Two"), task("Three")])
+        .unwrap();
+    let recorded = search(&jira);
+    drop(jira_mock);
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.contains(r#""nextPageToken": "2""#), "{}", contents);
+    let replay = ClientOptions {
+        replay: Some(path),
+        ..Default::default()
+    };
+    let jira = JiraClient::new(config(
+        "http://127.0.0.1:9".to_string(),
+        AuthType::Basic,
+        replay,
+    ))
+    .unwrap();
+    jira.bulk_create(&[task("One"), task("Two"), task("Three")])
+        .unwrap();
+
+    assert_eq!(recorded, ["ABC-1", "ABC-2", "ABC-3"]);
+    assert_eq!(search(&jira), recorded);
+}
+
+#[test]
+fn record_and_replay_are_exclusive() {
+    let options = ClientOptions {
+        record: Some(recording_path("exclusive")),
+        replay: Some(recording_path("exclusive")),
+        ..Default::default()
+    };
+
+    let jira = JiraClient::new(config(String::new(), AuthType::Basic, options));
+
+    assert!(jira.is_err());
+}
@@ -0,0 +1,43 @@
+use jiragen::{AuthType, ClientOptions, Config, JiraClient, OAuthConfig, OAuthToken, REDACTED};
+
+#[test]
+fn debug_output_redacts_credentials() {
+    let config = Config {
+        jira_url: "https://example.atlassian.net".to_string(),
+        jira_user: "user@example.com".to_string(),
+        jira_key: "s3cr3t-key".to_string(),
+        auth: AuthType::OAuth,
+        oauth: Some(OAuthConfig::new("client-id", "s3cr3t-client", "token.json")),
+        client: ClientOptions {
+            headers: [
+                (
+                    "Authorization".to_string(),
+                    "Bearer s3cr3t-token".to_string(),
+                ),
+                ("X-Team".to_string(), "platform".to_string()),
+            ]
+            .into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let token = OAuthToken {
+        access_token: "s3cr3t-access".to_string(),
+        refresh_token: "s3cr3t-refresh".to_string(),
+        expires_at: 0,
+        cloud_id: "cloud".to_string(),
+    };
+    let jira = JiraClient::new(config.clone()).unwrap();
+
+    for debug in [
+        format!("{:?}", config),
+        format!("{:#?}", jira),
+        format!("{:?}", token),
+    ] {
+        assert!(!debug.contains("s3cr3t"), "{}", debug);
+        assert!(debug.contains(REDACTED), "{}", debug);
+    }
+    let debug = format!("{:?}", config);
+    assert!(debug.contains("user@example.com"), "{}", debug);
+    assert!(debug.contains("platform"), "{}", debug);
+}
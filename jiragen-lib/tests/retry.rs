@@ -0,0 +1,149 @@
+use jiragen::{Config, JiraClient, RetryPolicy};
+use jiragen_testkit::{config, task};
+use mockito::Server;
+use std::time::{Duration, Instant};
+
+fn client(server: &Server, max_attempts: u32) -> JiraClient {
+    JiraClient::new(Config {
+        retry: RetryPolicy {
+            max_attempts,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 10,
+        },
+        ..config(&server.url())
+    })
+    .unwrap()
+}
+
+#[test]
+fn rate_limited_creates_are_retried_after_retry_after() {
+    let mut server = Server::new();
+    let limited = server
+        .mock("POST", "/rest/api/2/issue/bulk")
+        .with_status(429)
+        .with_header("retry-after", "0")
+        .expect(2)
+        .create();
+    let created = server
+        .mock("POST", "/rest/api/2/issue/bulk")
+        .with_status(201)
+        .with_body(r#"{"issues":[{"id":"1","key":"ABC-1","self":""}],"errors":[]}"#)
+        .expect(1)
+        .create();
+
+    let response = client(&server, 3)
+        .bulk_create(&[task("Rate limited")])
+        .unwrap();
+
+    limited.assert();
+    created.assert();
+    assert_eq!(response.issues[0].key, "ABC-1");
+}
+
+#[test]
+fn retries_stop_after_max_attempts() {
+    let mut server = Server::new();
+    let limited = server
+        .mock("GET", "/rest/api/2/field")
+        .with_status(429)
+        .with_header("x-ratelimit-reset", "2000-01-01T00:00Z")
+        .expect(2)
+        .create();
+
+    let result = client(&server, 2).get_fields();
+
+    limited.assert();
+    assert!(result.is_err());
+}
+
+#[test]
+fn unavailable_reads_are_retried_but_creates_are_not() {
+    let mut server = Server::new();
+    let unavailable_read = server
+        .mock("GET", "/rest/api/2/field")
+        .with_status(503)
+        .expect(1)
+        .create();
+    let read = server
+        .mock("GET", "/rest/api/2/field")
+        .with_body("[]")
+        .expect(1)
+        .create();
+    let unavailable_create = server
+        .mock("POST", "/rest/api/2/issue")
+        .with_status(503)
+        .expect(1)
+        .create();
+    let jira = client(&server, 3);
+
+    assert!(jira.get_fields().unwrap().is_empty());
+    assert!(jira.create_issue(&task("Rate limited")).is_err());
+
+    unavailable_read.assert();
+    read.assert();
+    unavailable_create.assert();
+}
+
+#[test]
+fn retry_after_dates_are_waited_for() {
+    let mut server = Server::new();
+    // a date that has passed means no wait, rather than the 2s backoff
+    let limited = server
+        .mock("GET", "/rest/api/2/field")
+        .with_status(429)
+        .with_header("retry-after", "Sat, 01 Jan 2000 00:00:00 GMT")
+        .expect(1)
+        .create();
+    let fields = server
+        .mock("GET", "/rest/api/2/field")
+        .with_body("[]")
+        .expect(1)
+        .create();
+    let jira = JiraClient::new(Config {
+        jira_url: server.url(),
+        retry: RetryPolicy {
+            max_attempts: 2,
+            initial_backoff_ms: 2_000,
+            max_backoff_ms: 60_000,
+        },
+        ..Default::default()
+    })
+    .unwrap();
+
+    let started = Instant::now();
+    jira.get_fields().unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(1));
+    limited.assert();
+    fields.assert();
+}
+
+#[test]
+fn waits_jira_asks_for_are_capped_at_max_backoff() {
+    let mut server = Server::new();
+    let limited = server
+        .mock("GET", "/rest/api/2/field")
+        .with_status(429)
+        .with_header("retry-after", "3600")
+        .expect(1)
+        .create();
+    let rate_limited_until_2100 = server
+        .mock("GET", "/rest/api/2/field")
+        .with_status(429)
+        .with_header("retry-after", "Fri, 01 Jan 2100 00:00:00 GMT")
+        .expect(1)
+        .create();
+    let fields = server
+        .mock("GET", "/rest/api/2/field")
+        .with_body("[]")
+        .expect(1)
+        .create();
+
+    let started = Instant::now();
+    client(&server, 3).get_fields().unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    limited.assert();
+    rate_limited_until_2100.assert();
+    fields.assert();
+}
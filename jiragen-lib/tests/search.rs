@@ -0,0 +1,129 @@
+use jiragen::{FakeJira, JiraApi, JiraClient, JiraIssue, Jql, Order, SearchOptions};
+use jiragen_testkit::{client, config, task, MockJira};
+use mockito::Matcher;
+use serde_json::json;
+
+fn labeled_task(summary: &str, labels: &[&str]) -> JiraIssue {
+    let mut issue = task(summary);
+    issue.fields["labels"] = json!(labels);
+    issue
+}
+
+#[test]
+fn values_are_quoted_and_escaped() {
+    let jql = Jql::new()
+        .field("Story Points", "3")
+        .field("cf[10010]", "say \"hi\" \\o/")
+        .text("C++ -crash*")
+        .clause("assignee = currentUser() OR reporter = currentUser()");
+
+    assert_eq!(
+        jql.to_string(),
+        r#""Story Points" = "3" AND cf[10010] = "say \"hi\" \\o/" AND text ~ "C\\+\\+ \\-crash\\*" AND (assignee = currentUser() OR reporter = currentUser())"#
+    );
+    assert_eq!(Jql::new().to_string(), "");
+    assert_eq!(
+        Jql::new()
+            .clause("a = 1 OR b = 2")
+            .order_by("Story Points", Order::Desc)
+            .to_string(),
+        r#"a = 1 OR b = 2 ORDER BY "Story Points" DESC"#
+    );
+}
+
+#[test]
+fn every_page_is_iterated_on_both_deployments() {
+    for jira_mock in [
+        MockJira::with_sample_data(),
+        MockJira::with_sample_server_data(),
+    ] {
+        let jira = client(&jira_mock);
+        let issues: Vec<JiraIssue> = (1..=5)
+            .map(|i| match i % 2 {
+                0 => labeled_task(&format!("Issue {}", i), &["docs"]),
+                _ => labeled_task(&format!("Issue {}", i), &["release"]),
+            })
+            .collect();
+        jira.bulk_create(&issues).unwrap();
+        let options = SearchOptions {
+            page_size: 2,
+            ..Default::default()
+        };
+
+        let keys: Vec<String> = jira
+            .search_iter(Jql::new().project("ABC").label("release"), options)
+            .map(|issue| issue.unwrap().key)
+            .collect();
+
+        assert_eq!(keys, ["ABC-1", "ABC-3", "ABC-5"]);
+    }
+}
+
+#[test]
+fn fields_and_expansions_are_requested() {
+    let mut server = mockito::Server::new();
+    server
+        .mock("GET", "/rest/api/2/serverInfo")
+        .with_body(json!({ "version": "1001.0.0", "deploymentType": "Cloud" }).to_string())
+        .create();
+    let search = server
+        .mock("POST", "/rest/api/2/search/jql")
+        .match_body(Matcher::PartialJson(json!({
+            "jql": "project = \"ABC\"",
+            "maxResults": 10,
+            "fields": ["summary", "labels"],
+            "expand": "renderedFields,changelog",
+        })))
+        .with_body(json!({ "issues": [{ "id": "1", "key": "ABC-1", "self": "" }] }).to_string())
+        .create();
+    let options = SearchOptions {
+        fields: vec!["summary".to_string(), "labels".to_string()],
+        expand: vec!["renderedFields".to_string(), "changelog".to_string()],
+        page_size: 10,
+    };
+
+    let issues: Vec<_> = JiraClient::new(config(&server.url()))
+        .unwrap()
+        .search_iter(Jql::new().project("ABC"), options)
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    search.assert();
+    assert_eq!(issues.len(), 1);
+}
+
+#[test]
+fn an_error_ends_the_iteration() {
+    let jira_mock = MockJira::with_sample_server_data();
+    let jira = client(&jira_mock);
+
+    let mut issues = jira.search_iter("project ~ ABC", SearchOptions::default());
+
+    assert!(matches!(issues.next(), Some(Err(_))));
+    assert!(issues.next().is_none());
+}
+
+#[test]
+fn fakes_are_searched_through_the_trait() {
+    let jira = FakeJira::new();
+    jira.add_project("ABC", &["Task"]);
+    jira.bulk_create(&[
+        labeled_task("One", &["release"]),
+        labeled_task("Two", &["release"]),
+    ])
+    .unwrap();
+    let options = SearchOptions {
+        fields: vec!["summary".to_string()],
+        page_size: 1,
+        ..Default::default()
+    };
+
+    let issues: Vec<_> = jira
+        .search_iter(Jql::new().project("ABC").label("release"), options)
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(issues.len(), 2);
+    assert_eq!(issues[1].fields["summary"], "Two");
+    assert!(!issues[1].fields.contains_key("labels"));
+}
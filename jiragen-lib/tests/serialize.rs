@@ -0,0 +1,40 @@
+use csv::StringRecord;
+use jiragen::csv_to_json;
+use serde_json::{json, Value};
+
+fn row(headers: &[&str], cells: &[&str]) -> Value {
+    let records = vec![StringRecord::from(cells.to_vec())];
+    csv_to_json(headers.to_vec(), records).unwrap().remove(0)
+}
+
+#[test]
+fn siblings_are_merged_at_any_depth() {
+    assert_eq!(
+        row(&["a.b.c", "a.b.d", "a.e"], &["1", "2", "3"]),
+        json!({ "a": { "b": { "c": "1", "d": "2" }, "e": "3" } })
+    );
+}
+
+#[test]
+fn nested_arrays_are_appended_to() {
+    assert_eq!(
+        row(&["x.y[]", "x.y[]", "x.z"], &["ios", "android", "1"]),
+        json!({ "x": { "y": ["ios", "android"], "z": "1" } })
+    );
+    assert_eq!(
+        row(&["x.y[].id", "x.y[].id"], &["10000", "10001"]),
+        json!({ "x": { "y": [{ "id": "10000" }, { "id": "10001" }] } })
+    );
+}
+
+#[test]
+fn repeated_leaf_keys_keep_the_last_cell() {
+    assert_eq!(
+        row(&["summary", "summary"], &["First", "Last"]),
+        json!({ "summary": "Last" })
+    );
+    assert_eq!(
+        row(&["a.b.c", "a.b.c"], &["First", "Last"]),
+        json!({ "a": { "b": { "c": "Last" } } })
+    );
+}
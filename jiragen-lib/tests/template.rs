@@ -0,0 +1,70 @@
+use jiragen::Template;
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+
+fn template_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("jiragen-template-{}", name));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn merged_templates_leave_out_the_columns_a_row_lacks() {
+    let dir = template_dir("merge");
+    fs::write(
+        dir.join("shared.csv"),
+        "summary,labels[],@foreach\nSummary,Labels,Loop\nTest {{platform}},qa,platform in ios\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("release.csv"),
+        "project.key,summary,fixVersions[].id\nProject,Summary,Fix versions\n\
+         REL,Ship it,10000\n@include shared.csv,,\n",
+    )
+    .unwrap();
+
+    let template = Template::from_path(dir.join("release.csv"))
+        .unwrap()
+        .expand()
+        .unwrap();
+
+    assert_eq!(
+        template.ids(),
+        ["project.key", "summary", "fixVersions[].id", "labels[]"]
+    );
+    assert_eq!(
+        template.to_json().unwrap(),
+        [
+            json!({ "project": { "key": "REL" }, "summary": "Ship it", "fixVersions": [{ "id": "10000" }] }),
+            json!({ "summary": "Test ios", "labels": ["qa"] }),
+        ]
+    );
+}
+
+#[test]
+fn nested_includes_keep_the_columns_missing() {
+    let dir = template_dir("nested");
+    fs::write(dir.join("leaf.csv"), "summary\nSummary\nLeaf\n").unwrap();
+    fs::write(
+        dir.join("middle.csv"),
+        "summary,labels[]\nSummary,Labels\nMiddle,qa\n@include leaf.csv,\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("root.csv"),
+        "labels[],project.key\nLabels,Project\nops,REL\n@include middle.csv,\n",
+    )
+    .unwrap();
+
+    let template = Template::from_path(dir.join("root.csv")).unwrap();
+
+    assert_eq!(
+        template.to_json().unwrap(),
+        [
+            json!({ "labels": ["ops"], "project": { "key": "REL" } }),
+            json!({ "labels": ["qa"], "summary": "Middle" }),
+            json!({ "summary": "Leaf" }),
+        ]
+    );
+}
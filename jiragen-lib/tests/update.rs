@@ -0,0 +1,165 @@
+use jiragen::{adf, Config, Error, IssueUpdate, JiraClient, JiraIssue, UpdateOperation};
+use jiragen_testkit::MockJira;
+use serde_json::json;
+
+#[test]
+fn columns_are_expanded_into_operations() {
+    let update = IssueUpdate::from_columns(&json!({
+        "labels": { "add": ["release", "", "docs"], "remove": "draft" },
+        "components": { "set": [{ "name": "Backend" }] },
+        "comment": { "add": { "body": "Created from a template" } },
+        "worklog": { "add": { "timeSpent": " " } },
+    }))
+    .unwrap();
+
+    assert_eq!(
+        update.operations("labels"),
+        [
+            UpdateOperation::Add(json!("release")),
+            UpdateOperation::Add(json!("docs")),
+            UpdateOperation::Remove(json!("draft")),
+        ]
+    );
+    assert_eq!(
+        update,
+        IssueUpdate::new()
+            .add_label("release")
+            .add_label("docs")
+            .remove_label("draft")
+            .set_components(&["Backend"])
+            .add_comment("Created from a template")
+    );
+    assert!(update.operations("worklog").is_empty());
+}
+
+#[test]
+fn comments_worklogs_and_links_are_edited_and_removed_by_id() {
+    let update = IssueUpdate::new()
+        .edit_comment("10000", "Tagged **1.0**")
+        .remove_comment("10001")
+        .edit_worklog("10100", "2h")
+        .remove_worklog("10101")
+        .remove_link("10200");
+
+    assert_eq!(
+        serde_json::to_value(&update).unwrap(),
+        json!({
+            "comment": [
+                { "edit": { "id": "10000", "body": "Tagged **1.0**" } },
+                { "remove": { "id": "10001" } },
+            ],
+            "worklog": [
+                { "edit": { "id": "10100", "timeSpent": "2h" } },
+                { "remove": { "id": "10101" } },
+            ],
+            "issuelinks": [{ "remove": { "id": "10200" } }],
+        })
+    );
+
+    let issue = JiraIssue {
+        update: Some(update.into()),
+        fields: json!({ "summary": "Tag it" }),
+    };
+    let comments = &issue.to_adf().update.unwrap()["comment"];
+    assert_eq!(adf::to_text(&comments[0]["edit"]["body"]), "Tagged 1.0");
+    assert_eq!(comments[1], json!({ "remove": { "id": "10001" } }));
+}
+
+#[test]
+fn comment_and_worklog_text_is_their_body_and_time_spent() {
+    let update = IssueUpdate::from_columns(&json!({
+        "comment": { "add": "Created from a template" },
+        "worklog": { "add": ["1h", "30m"] },
+    }))
+    .unwrap();
+
+    assert_eq!(
+        update,
+        IssueUpdate::new()
+            .add_comment("Created from a template")
+            .add_worklog("1h")
+            .add_worklog("30m")
+    );
+}
+
+#[test]
+fn unknown_operations_are_rejected() {
+    let result = IssueUpdate::from_columns(&json!({ "labels": { "append": "release" } }));
+
+    match result {
+        Err(Error::CustomError(e)) => {
+            assert_eq!(e.message, "Invalid update columns");
+            assert!(e.details.contains("update.labels.append"), "{}", e.details);
+        }
+        other => panic!("expected an error, got {:?}", other),
+    }
+    assert!(IssueUpdate::from_columns(&json!({ "labels": "release" })).is_err());
+}
+
+#[test]
+fn updates_are_merged_after_the_issues_own() {
+    let mut issue = JiraIssue::from_row(json!({
+        "summary": "Tag it",
+        "update": { "labels": { "add": "release" } },
+    }))
+    .unwrap();
+
+    issue
+        .add_update(
+            IssueUpdate::new()
+                .add_label("docs")
+                .add_link("Relates", "ABC-1"),
+        )
+        .unwrap();
+
+    assert_eq!(issue.fields, json!({ "summary": "Tag it" }));
+    assert_eq!(
+        issue.update,
+        Some(json!({
+            "issuelinks": [{ "add": { "type": { "name": "Relates" }, "outwardIssue": { "key": "ABC-1" } } }],
+            "labels": [{ "add": "release" }, { "add": "docs" }],
+        }))
+    );
+}
+
+#[test]
+fn jira_applies_the_update() {
+    let jira_mock = MockJira::with_sample_data();
+    let jira = JiraClient::new(Config {
+        jira_url: jira_mock.url(),
+        jira_user: "user".to_string(),
+        jira_key: "key".to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+    let mut issue = JiraIssue {
+        update: None,
+        fields: json!({
+            "project": { "key": "ABC" },
+            "issuetype": { "name": "Task" },
+            "summary": "Tag it",
+            "labels": ["draft"],
+        }),
+    };
+    issue
+        .add_update(
+            IssueUpdate::new()
+                .add_label("release")
+                .remove_label("draft")
+                .add_component("Backend")
+                .add_comment("Tagged by the release script")
+                .add_worklog("15m"),
+        )
+        .unwrap();
+
+    jira.create_issue(&issue).unwrap();
+
+    let fields = &jira_mock.issues()[0].fields;
+    assert_eq!(fields["labels"], json!(["release"]));
+    assert_eq!(fields["components"], json!([{ "name": "Backend" }]));
+    assert_eq!(
+        fields["comment"]["comments"][0]["body"],
+        "Tagged by the release script"
+    );
+    assert_eq!(fields["worklog"]["worklogs"][0]["timeSpent"], "15m");
+}
@@ -0,0 +1,117 @@
+use jiragen::{adf, validate_fields, CreateMeta};
+use serde_json::{json, Value};
+
+fn createmeta() -> CreateMeta {
+    let field = |field_type: &str| json!({ "required": false, "schema": { "type": field_type } });
+    serde_json::from_value(json!({ "projects": [{
+        "id": "10000",
+        "key": "REL",
+        "issuetypes": [{
+            "id": "3",
+            "name": "Task",
+            "fields": {
+                "project": field("project"),
+                "issuetype": field("issuetype"),
+                "summary": field("string"),
+                "labels": field("array"),
+                "duedate": field("date"),
+                "priority": field("priority"),
+                "customfield_10010": field("number"),
+                "customfield_10020": field("datetime"),
+            }
+        }]
+    }]}))
+    .unwrap()
+}
+
+// The fields that fail the type checks, for an issue with the given fields.
+fn type_violations(fields: Value) -> Vec<String> {
+    let mut issue = json!({ "project": { "key": "REL" }, "issuetype": { "name": "Task" } });
+    for (id, value) in fields.as_object().unwrap() {
+        issue[id] = value.clone();
+    }
+
+    validate_fields(&[issue], &createmeta())
+        .into_iter()
+        .map(|v| v.field)
+        .collect()
+}
+
+#[test]
+fn values_of_the_schema_type_pass() {
+    let violations = type_violations(json!({
+        "summary": "Tag the release",
+        "labels": ["release"],
+        "duedate": "2026-11-01",
+        "priority": { "name": "High" },
+        "customfield_10010": 3,
+        "customfield_10020": "2026-11-01T09:00:00.000+0000",
+    }));
+
+    assert!(violations.is_empty(), "{:?}", violations);
+}
+
+#[test]
+fn numbers_may_be_strings_from_template_cells() {
+    assert!(type_violations(json!({ "customfield_10010": "3" })).is_empty());
+    assert!(type_violations(json!({ "customfield_10010": " 2.5 " })).is_empty());
+    assert!(type_violations(json!({ "customfield_10010": "-1e3" })).is_empty());
+
+    assert_eq!(
+        type_violations(json!({ "customfield_10010": "three" })),
+        ["customfield_10010"]
+    );
+    assert_eq!(
+        type_violations(json!({ "customfield_10010": { "value": 3 } })),
+        ["customfield_10010"]
+    );
+}
+
+#[test]
+fn strings_may_be_adf_documents() {
+    let document = adf::from_markdown("Tag the **release**");
+
+    assert!(type_violations(json!({ "summary": document })).is_empty());
+    assert_eq!(
+        type_violations(json!({ "summary": { "type": "paragraph" } })),
+        ["summary"]
+    );
+}
+
+#[test]
+fn values_of_other_types_fail() {
+    let violations = type_violations(json!({
+        "summary": { "text": "Tag the release" },
+        "labels": "release",
+        "duedate": "01/11/2026",
+        "priority": "High",
+        "customfield_10020": "2026-11-01",
+    }));
+
+    assert_eq!(
+        violations,
+        [
+            "customfield_10020",
+            "duedate",
+            "labels",
+            "priority",
+            "summary"
+        ]
+    );
+}
+
+#[test]
+fn type_problems_name_the_expected_shape() {
+    let issue = json!({
+        "project": { "key": "REL" },
+        "issuetype": { "name": "Task" },
+        "customfield_10010": "three",
+    });
+
+    let violations = validate_fields(&[issue], &createmeta());
+
+    assert_eq!(
+        violations[0].problem,
+        "expected a number for a field of type `number`, found \"three\""
+    );
+}
@@ -0,0 +1,29 @@
+// The clients and issues most tests need, so that each test only spells out what it is about.
+use crate::MockJira;
+use jiragen::{Config, IssueFields, JiraClient, JiraIssue};
+
+/// The `Config` of a client of the JIRA at `jira_url`, with credentials a mock accepts unless
+/// [`require_credentials`](struct.MockJira.html#method.require_credentials) says otherwise. Set
+/// other options with struct update syntax, e.g. `Config { auth, ..config(&jira_mock.url()) }`.
+pub fn config(jira_url: &str) -> Config {
+    Config {
+        jira_url: jira_url.to_string(),
+        jira_user: "user".to_string(),
+        jira_key: "key".to_string(),
+        ..Default::default()
+    }
+}
+
+/// A client of a mock JIRA, with the [`config`](fn.config.html) of its URL.
+pub fn client(jira: &MockJira) -> JiraClient {
+    JiraClient::new(config(&jira.url())).expect("the default config is valid")
+}
+
+/// A `Task` of the sample data's `ABC` project, with a summary.
+pub fn task(summary: &str) -> JiraIssue {
+    IssueFields::new()
+        .project("ABC")
+        .issue_type("Task")
+        .summary(summary)
+        .into()
+}
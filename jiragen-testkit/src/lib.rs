@@ -0,0 +1,285 @@
+//! `jiragen-testkit` is an in-process mock JIRA server, for testing JiraGen, and automations built
+//! on it, without a JIRA instance. It serves the REST API endpoints JiraGen uses (server info,
+//! projects, fields, create metadata, users, issues, bulk create, links, transitions, search and
+//! session login), validates issues against its create screens, and responds with JIRA's error
+//! bodies. It is deployed as JIRA Cloud unless [`MockDeployment::Server`](enum.MockDeployment.html)
+//! is set. [`client`](fn.client.html) and [`task`](fn.task.html) are a client of a mock and an
+//! issue of its sample data.
+//! ```
+//! use jiragen::JiraIssue;
+//! use jiragen_testkit::{client, task, MockJira};
+//! use serde_json::json;
+//!
+//! let jira_mock = MockJira::with_sample_data();
+//! let jira = client(&jira_mock);
+//!
+//! let response = jira.bulk_create(&[
+//!   task("Ship it"),
+//!   JiraIssue {
+//!     update: None,
+//!     fields: json!({ "project": { "key": "ABC" }, "issuetype": { "name": "Task" } }),
+//!   },
+//! ])?;
+//!
+//! assert_eq!(response.issues[0].key, "ABC-1");
+//! assert_eq!(response.errors[0].element_errors.errors["summary"], "Summary is required.");
+//! assert_eq!(jira_mock.issues().len(), 1);
+//! # Ok::<(), jiragen::Error>(())
+//! ```
+mod model;
+pub use model::{
+    MockDeployment, MockField, MockIssue, MockIssueType, MockLink, MockProject, MockTransition,
+    MockUser, ScreenField,
+};
+
+mod server;
+
+mod fixtures;
+pub use fixtures::{client, config, task};
+
+use model::State;
+use server::Request;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use tiny_http::{Header, Response, Server};
+
+/// A mock JIRA server, listening on a local port until it is dropped. Its projects, fields, users
+/// and transitions can be added at any time, and the issues and links created through it
+/// inspected.
+pub struct MockJira {
+    url: String,
+    state: Arc<Mutex<State>>,
+    server: Arc<Server>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockJira {
+    /// Starts a mock JIRA without any projects, fields or users. Any credentials are accepted.
+    pub fn start() -> Self {
+        let server = Arc::new(Server::http("127.0.0.1:0").expect("failed to start the mock JIRA"));
+        let url = format!(
+            "http://{}",
+            server
+                .server_addr()
+                .to_ip()
+                .expect("the mock JIRA listens on an IP address")
+        );
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let thread = {
+            let (server, state, url) = (server.clone(), state.clone(), url.clone());
+            thread::spawn(move || serve(&server, &state, &url))
+        };
+
+        Self {
+            url,
+            state,
+            server,
+            thread: Some(thread),
+        }
+    }
+
+    /// Starts a mock JIRA Cloud with the project `ABC` ("Alphabet"), whose `Task`, `Bug` and `Epic`
+    /// issue types can be created with the usual system fields (`summary` is required, and
+    /// `priority` and `components` are restricted), `parent` to link issues to epics, the custom
+    /// field `customfield_10010` ("Story Points"), the user `jane@example.com`, and
+    /// `Start Progress` and `Done` transitions.
+    pub fn with_sample_data() -> Self {
+        Self::sample(MockDeployment::Cloud)
+    }
+
+    /// Starts a mock JIRA Server with the sample data of
+    /// [`with_sample_data`](#method.with_sample_data), except that issues are linked to epics with
+    /// the custom field `customfield_10014` ("Epic Link") rather than `parent`, and the user
+    /// is named `jane`.
+    pub fn with_sample_server_data() -> Self {
+        Self::sample(MockDeployment::Server)
+    }
+
+    fn sample(deployment: MockDeployment) -> Self {
+        let jira = Self::start();
+        jira.set_deployment(deployment);
+
+        let epic_field = match deployment {
+            MockDeployment::Cloud => MockField::system("parent", "Parent", "issuelink", None),
+            MockDeployment::Server => {
+                MockField::custom("customfield_10014", "Epic Link", "any", None)
+            }
+        };
+        for field in [
+            MockField::system("project", "Project", "project", None),
+            MockField::system("issuetype", "Issue Type", "issuetype", None),
+            MockField::system("summary", "Summary", "string", None),
+            MockField::system("description", "Description", "string", None),
+            MockField::system("labels", "Labels", "array", Some("string")),
+            MockField::system("priority", "Priority", "priority", None),
+            MockField::system("components", "Component/s", "array", Some("component")),
+            MockField::system("assignee", "Assignee", "user", None),
+            MockField::custom("customfield_10010", "Story Points", "number", None),
+            epic_field.clone(),
+        ] {
+            jira.add_field(field);
+        }
+
+        let screen = vec![
+            ScreenField::required("project"),
+            ScreenField::required("issuetype"),
+            ScreenField::required("summary"),
+            ScreenField::optional("description"),
+            ScreenField::optional("labels"),
+            ScreenField::optional("priority").allowing(&["High", "Medium", "Low"]),
+            ScreenField::optional("components").allowing(&["Backend", "Frontend"]),
+            ScreenField::optional("assignee"),
+        ];
+        let mut bug_screen = screen.clone();
+        bug_screen.push(ScreenField::optional(&epic_field.id));
+        let mut task_screen = bug_screen.clone();
+        task_screen.push(ScreenField::optional("customfield_10010"));
+
+        jira.add_project(MockProject {
+            id: "10000".to_string(),
+            key: "ABC".to_string(),
+            name: "Alphabet".to_string(),
+            components: vec!["Backend".to_string(), "Frontend".to_string()],
+            issue_types: vec![
+                MockIssueType {
+                    id: "10001".to_string(),
+                    name: "Task".to_string(),
+                    subtask: false,
+                    screen: task_screen,
+                },
+                MockIssueType {
+                    id: "10002".to_string(),
+                    name: "Bug".to_string(),
+                    subtask: false,
+                    screen: bug_screen,
+                },
+                MockIssueType {
+                    id: "10003".to_string(),
+                    name: "Epic".to_string(),
+                    subtask: false,
+                    screen,
+                },
+            ],
+        });
+
+        jira.add_user(MockUser {
+            account_id: "5b10a2844c20165700ede21g".to_string(),
+            name: "jane".to_string(),
+            display_name: "Jane Doe".to_string(),
+            email: "jane@example.com".to_string(),
+        });
+
+        for (id, name, to_status) in [
+            ("11", "Start Progress", "In Progress"),
+            ("21", "Done", "Done"),
+        ] {
+            jira.add_transition(MockTransition {
+                id: id.to_string(),
+                name: name.to_string(),
+                to_status: to_status.to_string(),
+            });
+        }
+
+        jira
+    }
+
+    /// The base URL of the mock, to use as `Config.jira_url`.
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Only accepts requests authenticated with this username and API key (or password, or
+    /// bearer token).
+    pub fn require_credentials(&self, user: &str, key: &str) -> &Self {
+        self.state().credentials = Some((user.to_string(), key.to_string()));
+        self
+    }
+
+    /// Deploys the mock as JIRA Cloud or Server, as `/rest/api/2/serverInfo` reports.
+    pub fn set_deployment(&self, deployment: MockDeployment) -> &Self {
+        self.state().deployment = deployment;
+        self
+    }
+
+    pub fn add_project(&self, project: MockProject) -> &Self {
+        self.state().projects.push(project);
+        self
+    }
+
+    pub fn add_field(&self, field: MockField) -> &Self {
+        self.state().fields.push(field);
+        self
+    }
+
+    pub fn add_user(&self, user: MockUser) -> &Self {
+        self.state().users.push(user);
+        self
+    }
+
+    pub fn add_transition(&self, transition: MockTransition) -> &Self {
+        self.state().transitions.push(transition);
+        self
+    }
+
+    /// The issues created so far, in order.
+    pub fn issues(&self) -> Vec<MockIssue> {
+        self.state().issues.clone()
+    }
+
+    /// The links created so far, in order.
+    pub fn links(&self) -> Vec<MockLink> {
+        self.state().links.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+}
+
+impl Drop for MockJira {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(server: &Server, state: &Mutex<State>, url: &str) {
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let _ = std::io::Read::read_to_string(request.as_reader(), &mut body);
+        let header = |name: &'static str| {
+            request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv(name))
+                .map(|h| h.value.as_str().to_string())
+        };
+        let (authorization, cookie) = (header("Authorization"), header("Cookie"));
+        let method = request.method().as_str().to_uppercase();
+
+        let response = server::handle(
+            state,
+            url,
+            Request {
+                method: &method,
+                url: request.url(),
+                authorization: authorization.as_deref(),
+                cookie: cookie.as_deref(),
+                body: &body,
+            },
+        );
+
+        let body = match response.body.is_null() {
+            true => String::new(),
+            false => response.body.to_string(),
+        };
+        let response = Response::from_string(body)
+            .with_status_code(response.status)
+            .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+            .with_header(Header::from_bytes("X-AREQUESTID", "mock-request").unwrap());
+        let _ = request.respond(response);
+    }
+}
@@ -0,0 +1,665 @@
+// The JIRA instance a `MockJira` serves: its projects, fields, users and workflow, and the issues
+// and links created through it.
+use serde_json::{json, Map, Value};
+
+/// A project, with the issue types that can be created in it.
+#[derive(Clone, Debug)]
+pub struct MockProject {
+    pub id: String,
+    pub key: String,
+    pub name: String,
+    /// Component names.
+    pub components: Vec<String>,
+    pub issue_types: Vec<MockIssueType>,
+}
+
+/// An issue type, with the fields on its create screen.
+#[derive(Clone, Debug)]
+pub struct MockIssueType {
+    pub id: String,
+    pub name: String,
+    pub subtask: bool,
+    pub screen: Vec<ScreenField>,
+}
+
+/// A field on a create screen.
+#[derive(Clone, Debug)]
+pub struct ScreenField {
+    /// The id of a [`MockField`](struct.MockField.html).
+    pub field_id: String,
+    pub required: bool,
+    /// Whether JIRA fills in the field when it is left out.
+    pub has_default_value: bool,
+    /// The names or values the field may be set to. Empty when any value is allowed.
+    pub allowed_values: Vec<String>,
+}
+
+impl ScreenField {
+    /// An optional field, with any value allowed.
+    pub fn optional(field_id: &str) -> Self {
+        Self {
+            field_id: field_id.to_string(),
+            required: false,
+            has_default_value: false,
+            allowed_values: Vec::new(),
+        }
+    }
+
+    /// A required field, with any value allowed.
+    pub fn required(field_id: &str) -> Self {
+        Self {
+            required: true,
+            ..Self::optional(field_id)
+        }
+    }
+
+    /// The field, restricted to the given names or values.
+    pub fn allowing(mut self, values: &[&str]) -> Self {
+        self.allowed_values = values.iter().map(|v| v.to_string()).collect();
+        self
+    }
+}
+
+/// A system or custom field.
+#[derive(Clone, Debug)]
+pub struct MockField {
+    pub id: String,
+    pub name: String,
+    pub custom: bool,
+    /// The value's type, e.g. `string`, `array`, `number`, `user` or `option`.
+    pub schema_type: String,
+    /// The type of the items of an `array` field.
+    pub items: Option<String>,
+}
+
+impl MockField {
+    /// A system field.
+    pub fn system(id: &str, name: &str, schema_type: &str, items: Option<&str>) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            custom: false,
+            schema_type: schema_type.to_string(),
+            items: items.map(str::to_string),
+        }
+    }
+
+    /// A custom field.
+    pub fn custom(id: &str, name: &str, schema_type: &str, items: Option<&str>) -> Self {
+        Self {
+            custom: true,
+            ..Self::system(id, name, schema_type, items)
+        }
+    }
+
+    // Whether the field is rich text, which version 3 of the API expects in ADF.
+    fn is_rich_text(&self) -> bool {
+        !self.custom && matches!(self.id.as_str(), "description" | "environment")
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "name": self.name,
+            "custom": self.custom,
+            "schema": self.schema(),
+        })
+    }
+
+    fn schema(&self) -> Value {
+        let mut schema = json!({ "type": self.schema_type });
+        if let Some(items) = &self.items {
+            schema["items"] = json!(items);
+        }
+        match self.custom {
+            true => schema["custom"] = json!(format!("mock:{}", self.schema_type)),
+            false => schema["system"] = json!(self.id),
+        }
+        schema
+    }
+}
+
+/// A user that issues can be assigned to. JIRA Cloud refers to users by `account_id`, and
+/// JIRA Server by `name`.
+#[derive(Clone, Debug)]
+pub struct MockUser {
+    pub account_id: String,
+    pub name: String,
+    pub display_name: String,
+    pub email: String,
+}
+
+/// A workflow transition, available from every status.
+#[derive(Clone, Debug)]
+pub struct MockTransition {
+    pub id: String,
+    pub name: String,
+    /// The status an issue is in after the transition.
+    pub to_status: String,
+}
+
+/// An issue created through the mock.
+#[derive(Clone, Debug)]
+pub struct MockIssue {
+    pub id: String,
+    pub key: String,
+    pub project_key: String,
+    pub issue_type: String,
+    pub status: String,
+    /// The fields the issue was created with, keyed by field id.
+    pub fields: Map<String, Value>,
+}
+
+/// A link between two issues.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MockLink {
+    /// The link type's name, e.g. `Relates`.
+    pub link_type: String,
+    pub inward_issue: String,
+    pub outward_issue: String,
+}
+
+/// How the mock JIRA is deployed, which decides how users are referred to, how issues are linked
+/// to epics, and which search endpoints there are.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MockDeployment {
+    #[default]
+    Cloud,
+    Server,
+}
+
+/// The version of the REST API a request was sent to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ApiVersion {
+    V2,
+    V3,
+}
+
+/// Everything the mock knows.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct State {
+    pub projects: Vec<MockProject>,
+    pub fields: Vec<MockField>,
+    pub users: Vec<MockUser>,
+    pub transitions: Vec<MockTransition>,
+    pub issues: Vec<MockIssue>,
+    pub links: Vec<MockLink>,
+    pub deployment: MockDeployment,
+    /// The `user:key` pair Basic auth must use, when set.
+    pub credentials: Option<(String, String)>,
+    next_id: u64,
+}
+
+// Field errors JIRA reports for a create.
+type FieldErrors = Map<String, Value>;
+
+impl State {
+    /// A project by key or id.
+    pub fn project(&self, key_or_id: &str) -> Option<&MockProject> {
+        self.projects
+            .iter()
+            .find(|p| p.key == key_or_id || p.id == key_or_id)
+    }
+
+    /// An issue by key or id.
+    pub fn issue_mut(&mut self, key_or_id: &str) -> Option<&mut MockIssue> {
+        self.issues
+            .iter_mut()
+            .find(|i| i.key == key_or_id || i.id == key_or_id)
+    }
+
+    pub fn issue_json(&self, issue: &MockIssue, base_url: &str) -> Value {
+        let mut fields = issue.fields.clone();
+        fields.insert("status".to_string(), json!({ "name": issue.status }));
+        let links: Vec<Value> = self
+            .links
+            .iter()
+            .filter(|l| l.inward_issue == issue.key || l.outward_issue == issue.key)
+            .map(|l| {
+                let (direction, other) = match l.inward_issue == issue.key {
+                    true => ("outwardIssue", &l.outward_issue),
+                    false => ("inwardIssue", &l.inward_issue),
+                };
+                json!({ "type": { "name": l.link_type }, direction: { "key": other } })
+            })
+            .collect();
+        fields.insert("issuelinks".to_string(), json!(links));
+
+        json!({
+            "id": issue.id,
+            "key": issue.key,
+            "self": format!("{}/rest/api/2/issue/{}", base_url, issue.id),
+            "fields": fields,
+        })
+    }
+
+    pub fn project_json(&self, project: &MockProject, base_url: &str) -> Value {
+        json!({
+            "id": project.id,
+            "key": project.key,
+            "name": project.name,
+            "components": project
+                .components
+                .iter()
+                .enumerate()
+                .map(|(i, name)| json!({ "id": (10000 + i).to_string(), "name": name }))
+                .collect::<Vec<_>>(),
+            "issueTypes": project
+                .issue_types
+                .iter()
+                .map(|t| json!({ "id": t.id, "name": t.name, "subtask": t.subtask }))
+                .collect::<Vec<_>>(),
+            "roles": {
+                "Administrators": format!("{}/rest/api/2/project/{}/role/10002", base_url, project.id),
+            },
+        })
+    }
+
+    pub fn server_info_json(&self, base_url: &str) -> Value {
+        let (version, deployment_type) = match self.deployment {
+            MockDeployment::Cloud => ("1001.0.0-SNAPSHOT", "Cloud"),
+            MockDeployment::Server => ("9.12.2", "Server"),
+        };
+        let version_numbers: Vec<u64> = version
+            .split(['.', '-'])
+            .filter_map(|n| n.parse().ok())
+            .collect();
+
+        json!({
+            "baseUrl": base_url,
+            "version": version,
+            "versionNumbers": version_numbers,
+            "deploymentType": deployment_type,
+            "buildNumber": 100000,
+            "serverTitle": "Mock JIRA",
+        })
+    }
+
+    /// A user as the user search endpoint returns it.
+    pub fn user_json(&self, user: &MockUser) -> Value {
+        match self.deployment {
+            MockDeployment::Cloud => json!({
+                "accountId": user.account_id,
+                "displayName": user.display_name,
+                "emailAddress": user.email,
+                "active": true,
+            }),
+            MockDeployment::Server => json!({
+                "name": user.name,
+                "key": user.name,
+                "displayName": user.display_name,
+                "emailAddress": user.email,
+                "active": true,
+            }),
+        }
+    }
+
+    pub fn fields_json(&self) -> Value {
+        json!(self
+            .fields
+            .iter()
+            .map(MockField::to_json)
+            .collect::<Vec<_>>())
+    }
+
+    pub fn createmeta_json(&self, projects: &[&MockProject]) -> Value {
+        let projects: Vec<Value> = projects
+            .iter()
+            .map(|project| {
+                let issue_types: Vec<Value> = project
+                    .issue_types
+                    .iter()
+                    .map(|issue_type| {
+                        let fields: Map<String, Value> = issue_type
+                            .screen
+                            .iter()
+                            .filter_map(|screen| {
+                                let field = self.field(&screen.field_id)?;
+                                let allowed: Vec<Value> = screen
+                                    .allowed_values
+                                    .iter()
+                                    .map(|v| json!({ "id": v, "name": v, "value": v }))
+                                    .collect();
+                                Some((
+                                    field.id.clone(),
+                                    json!({
+                                        "required": screen.required,
+                                        "name": field.name,
+                                        "schema": field.schema(),
+                                        "hasDefaultValue": screen.has_default_value,
+                                        "allowedValues": allowed,
+                                    }),
+                                ))
+                            })
+                            .collect();
+                        json!({ "id": issue_type.id, "name": issue_type.name, "fields": fields })
+                    })
+                    .collect();
+                json!({
+                    "id": project.id,
+                    "key": project.key,
+                    "name": project.name,
+                    "issuetypes": issue_types,
+                })
+            })
+            .collect();
+
+        json!({ "projects": projects })
+    }
+
+    /// Validates an issue's `fields` and `update` as JIRA does, and creates it.
+    pub fn create(
+        &mut self,
+        issue: &Value,
+        api_version: ApiVersion,
+    ) -> Result<MockIssue, FieldErrors> {
+        let fields = issue["fields"].as_object().cloned().unwrap_or_default();
+        let mut errors = FieldErrors::new();
+
+        let project = match self.project_of(&fields["project"]) {
+            Some(project) => project.clone(),
+            None => {
+                errors.insert("project".into(), json!("valid project is required"));
+                return Err(errors);
+            }
+        };
+        let issue_type = match project
+            .issue_types
+            .iter()
+            .find(|t| matches_ref(&fields["issuetype"], &[&t.id, &t.name]))
+        {
+            Some(issue_type) => issue_type.clone(),
+            None => {
+                errors.insert("issuetype".into(), json!("valid issue type is required"));
+                return Err(errors);
+            }
+        };
+
+        for (id, value) in fields.iter() {
+            if id == "project" || id == "issuetype" {
+                continue;
+            }
+            match issue_type.screen.iter().find(|s| &s.field_id == id) {
+                None => {
+                    let message = format!(
+                        "Field '{}' cannot be set. It is not on the appropriate screen, or unknown.",
+                        id
+                    );
+                    errors.insert(id.clone(), json!(message));
+                }
+                Some(screen) => {
+                    if let Err(message) = self.check_value(screen, value, api_version) {
+                        errors.insert(id.clone(), json!(message));
+                    }
+                }
+            }
+        }
+
+        for screen in issue_type.screen.iter() {
+            let missing = match fields.get(&screen.field_id) {
+                None | Some(Value::Null) => true,
+                Some(Value::String(s)) => s.trim().is_empty(),
+                Some(_) => false,
+            };
+            if screen.required && !screen.has_default_value && missing {
+                let name = self.field_name(&screen.field_id);
+                errors.insert(
+                    screen.field_id.clone(),
+                    json!(format!("{} is required.", name)),
+                );
+            }
+        }
+
+        let links = self.links_of(&issue["update"], &mut errors);
+        let mut fields = fields;
+        apply_update(&mut fields, &issue["update"], &mut errors);
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        self.next_id += 1;
+        let number = self
+            .issues
+            .iter()
+            .filter(|i| i.project_key == project.key)
+            .count()
+            + 1;
+        let created = MockIssue {
+            id: (10000 + self.next_id).to_string(),
+            key: format!("{}-{}", project.key, number),
+            project_key: project.key.clone(),
+            issue_type: issue_type.name.clone(),
+            status: "To Do".to_string(),
+            fields,
+        };
+        for (link_type, outward_issue) in links {
+            self.links.push(MockLink {
+                link_type,
+                inward_issue: created.key.clone(),
+                outward_issue,
+            });
+        }
+        self.issues.push(created.clone());
+
+        Ok(created)
+    }
+
+    /// Links two existing issues.
+    pub fn link(&mut self, link: &Value) -> Result<(), String> {
+        let link_type = link["type"]["name"]
+            .as_str()
+            .ok_or("A link type name is required.")?;
+        let inward = link["inwardIssue"]["key"].as_str().unwrap_or_default();
+        let outward = link["outwardIssue"]["key"].as_str().unwrap_or_default();
+        for key in [inward, outward] {
+            if self.issue_mut(key).is_none() {
+                return Err(format!("Issue Does Not Exist: {}", key));
+            }
+        }
+
+        self.links.push(MockLink {
+            link_type: link_type.to_string(),
+            inward_issue: inward.to_string(),
+            outward_issue: outward.to_string(),
+        });
+        Ok(())
+    }
+
+    fn field(&self, id: &str) -> Option<&MockField> {
+        self.fields.iter().find(|f| f.id == id)
+    }
+
+    fn field_name(&self, id: &str) -> String {
+        self.field(id).map_or(id.to_string(), |f| f.name.clone())
+    }
+
+    fn project_of(&self, project: &Value) -> Option<&MockProject> {
+        self.projects
+            .iter()
+            .find(|p| matches_ref(project, &[&p.id, &p.key]))
+    }
+
+    // Checks a value against its field's type, and the values the screen allows.
+    fn check_value(
+        &self,
+        screen: &ScreenField,
+        value: &Value,
+        api_version: ApiVersion,
+    ) -> Result<(), String> {
+        let field = match self.field(&screen.field_id) {
+            Some(field) => field,
+            None => return Ok(()),
+        };
+
+        if field.is_rich_text() && api_version == ApiVersion::V3 {
+            return match value["type"] == "doc" && value["content"].is_array() {
+                true => Ok(()),
+                false => Err(
+                    "Operation value must be an Atlassian Document (see the Atlassian Document Format)"
+                        .to_string(),
+                ),
+            };
+        }
+
+        let values: Vec<&Value> = match (field.schema_type.as_str(), value) {
+            ("array", Value::Array(items)) => items.iter().collect(),
+            ("array", _) => return Err(format!("data was not an array for {}", field.name)),
+            ("string" | "any", Value::String(_)) | ("number", Value::Number(_)) => vec![value],
+            ("string", _) => return Err("Operation value must be a string".to_string()),
+            ("number", _) => return Err("Operation value must be a number".to_string()),
+            (_, Value::Object(_)) => vec![value],
+            (_, _) => return Err(format!("data was not an object for {}", field.name)),
+        };
+
+        if field.schema_type == "user" || field.items.as_deref() == Some("user") {
+            for user in values.iter() {
+                // Cloud only knows users by account id, and Server by name or key
+                let known = self.users.iter().any(|u| match self.deployment {
+                    MockDeployment::Cloud => user["accountId"] == u.account_id.as_str(),
+                    MockDeployment::Server => {
+                        user["name"] == u.name.as_str() || user["key"] == u.name.as_str()
+                    }
+                });
+                if !known {
+                    return Err(format!("User '{}' does not exist.", describe(user)));
+                }
+            }
+        }
+
+        // `parent` and the Epic Link field refer to issues
+        if field.schema_type == "issuelink" || field.name == "Epic Link" {
+            for issue in values.iter() {
+                if !self.issues.iter().any(|i| matches_ref(issue, &[&i.key])) {
+                    return Err(format!("Issue '{}' does not exist.", describe(issue)));
+                }
+            }
+        }
+
+        if !screen.allowed_values.is_empty() {
+            for value in values.iter() {
+                let allowed: Vec<&str> = screen.allowed_values.iter().map(String::as_str).collect();
+                if !matches_ref(value, &allowed) {
+                    return Err(format!(
+                        "Specify a valid value for {}: '{}' is not allowed",
+                        field.name,
+                        describe(value)
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // The `(link type, outward issue)` pairs an `update.issuelinks` adds.
+    fn links_of(&self, update: &Value, errors: &mut FieldErrors) -> Vec<(String, String)> {
+        let operations = match update["issuelinks"].as_array() {
+            Some(operations) => operations,
+            None => return Vec::new(),
+        };
+
+        let mut links = Vec::new();
+        for operation in operations {
+            let add = &operation["add"];
+            let link_type = add["type"]["name"].as_str().unwrap_or_default();
+            let outward = add["outwardIssue"]["key"].as_str().unwrap_or_default();
+            if self.issues.iter().all(|i| i.key != outward) {
+                errors.insert(
+                    "issuelinks".into(),
+                    json!(format!("Issue Does Not Exist: {}", outward)),
+                );
+                continue;
+            }
+            links.push((link_type.to_string(), outward.to_string()));
+        }
+        links
+    }
+}
+
+// Applies an `update`'s operations on labels and components to the fields, and keeps the comments
+// and worklogs it adds in the `comment` and `worklog` fields, as JIRA returns them.
+fn apply_update(fields: &mut Map<String, Value>, update: &Value, errors: &mut FieldErrors) {
+    let update = match update.as_object() {
+        Some(update) => update,
+        None => return,
+    };
+
+    for (id, operations) in update.iter() {
+        let operations = operations.as_array().cloned().unwrap_or_default();
+        for operation in operations.iter() {
+            let (verb, value) = match operation.as_object().and_then(|o| o.iter().next()) {
+                Some((verb, value)) => (verb.as_str(), value),
+                None => continue,
+            };
+            match (id.as_str(), verb) {
+                ("issuelinks", "add") => {}
+                ("labels" | "components", "add") => {
+                    let values = fields.entry(id.as_str()).or_insert_with(|| json!([]));
+                    if let Some(values) = values.as_array_mut() {
+                        values.push(value.clone());
+                    }
+                }
+                ("labels" | "components", "set") => {
+                    fields.insert(id.clone(), value.clone());
+                }
+                ("labels" | "components", "remove") => {
+                    if let Some(values) = fields.get_mut(id).and_then(Value::as_array_mut) {
+                        values.retain(|v| v != value);
+                    }
+                }
+                ("comment", "add") => {
+                    let comments = fields
+                        .entry("comment")
+                        .or_insert_with(|| json!({ "comments": [] }));
+                    if let Some(comments) = comments["comments"].as_array_mut() {
+                        comments.push(value.clone());
+                    }
+                }
+                ("worklog", "add") => {
+                    let worklogs = fields
+                        .entry("worklog")
+                        .or_insert_with(|| json!({ "worklogs": [] }));
+                    if let Some(worklogs) = worklogs["worklogs"].as_array_mut() {
+                        worklogs.push(value.clone());
+                    }
+                }
+                _ => {
+                    errors.insert(
+                        id.clone(),
+                        json!(format!(
+                            "Field '{}' does not support operation '{}'",
+                            id, verb
+                        )),
+                    );
+                }
+            }
+        }
+    }
+}
+
+// Whether a reference to a project, option, user, etc., e.g. `{"key": "ABC"}` or `"ABC"`, names
+// one of `names`.
+fn matches_ref(reference: &Value, names: &[&str]) -> bool {
+    let candidates = match reference {
+        Value::String(s) => vec![s.as_str()],
+        Value::Object(o) => ["id", "key", "name", "value", "accountId", "emailAddress"]
+            .iter()
+            .filter_map(|k| o.get(*k).and_then(Value::as_str))
+            .collect(),
+        _ => Vec::new(),
+    };
+    candidates.iter().any(|c| names.contains(c))
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Object(o) => ["name", "value", "id", "key", "accountId", "emailAddress"]
+            .iter()
+            .find_map(|k| o.get(*k).and_then(Value::as_str))
+            .unwrap_or_default()
+            .to_string(),
+        v => v.to_string(),
+    }
+}
@@ -0,0 +1,407 @@
+// Routes requests to the JIRA REST API endpoints the mock implements.
+use crate::model::{ApiVersion, MockDeployment, State};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use url::Url;
+
+// JIRA's limit on the issues of one bulk create.
+const BULK_CREATE_LIMIT: usize = 50;
+const SESSION_COOKIE: &str = "JSESSIONID=mock-session";
+
+/// A request, as the handler sees it.
+pub(crate) struct Request<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    pub authorization: Option<&'a str>,
+    pub cookie: Option<&'a str>,
+    pub body: &'a str,
+}
+
+/// A JSON response.
+pub(crate) struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn created(body: Value) -> Self {
+        Self { status: 201, body }
+    }
+
+    fn no_content() -> Self {
+        Self {
+            status: 204,
+            body: Value::Null,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "errorMessages": [message], "errors": {} }),
+        }
+    }
+
+    fn field_errors(status: u16, errors: Value) -> Self {
+        Self {
+            status,
+            body: json!({ "errorMessages": [], "errors": errors }),
+        }
+    }
+}
+
+pub(crate) fn handle(state: &Mutex<State>, base_url: &str, request: Request) -> Response {
+    let url = match Url::parse(&format!("{}{}", base_url, request.url)) {
+        Ok(url) => url,
+        Err(_) => return Response::error(400, "Invalid URL"),
+    };
+    let query = |name: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    };
+    let body: Value = serde_json::from_str(request.body).unwrap_or(Value::Null);
+    let mut segments: Vec<&str> = url.path().trim_matches('/').split('/').collect();
+    // versions 2 and 3 of the API have the same endpoints; only rich text differs
+    let api_version = match segments.as_slice() {
+        ["rest", "api", "3", ..] => {
+            segments[2] = "2";
+            ApiVersion::V3
+        }
+        _ => ApiVersion::V2,
+    };
+    let mut state = state.lock().unwrap();
+
+    if request.method == "POST" && segments == ["rest", "auth", "1", "session"] {
+        return login(&state, &body);
+    }
+    if !authenticated(&state, &request) {
+        return Response::error(
+            401,
+            "You are not authenticated. Authentication required to perform this operation.",
+        );
+    }
+
+    match (request.method, segments.as_slice()) {
+        ("GET", ["rest", "api", "2", "field"]) => Response::ok(state.fields_json()),
+        ("GET", ["rest", "api", "2", "project", key]) => match state.project(key) {
+            Some(project) => Response::ok(state.project_json(project, base_url)),
+            None => Response::error(
+                404,
+                &format!("No project could be found with key '{}'.", key),
+            ),
+        },
+        ("GET", ["rest", "api", "2", "issue", "createmeta"]) => {
+            let wanted: Vec<String> = query("projectKeys")
+                .or_else(|| query("projectIds"))
+                .map(|p| p.split(',').map(str::to_string).collect())
+                .unwrap_or_default();
+            let projects: Vec<_> = state
+                .projects
+                .iter()
+                .filter(|p| wanted.is_empty() || wanted.contains(&p.key) || wanted.contains(&p.id))
+                .collect();
+            Response::ok(state.createmeta_json(&projects))
+        }
+        ("GET", ["rest", "api", "2", "serverInfo"]) => {
+            Response::ok(state.server_info_json(base_url))
+        }
+        ("GET", ["rest", "api", "2", "user", "search"]) => {
+            // Cloud searches with `query`, and Server with `username`, which also matches names
+            let text = match state.deployment {
+                MockDeployment::Cloud => query("query"),
+                MockDeployment::Server => query("username"),
+            }
+            .unwrap_or_default();
+            let users: Vec<Value> = state
+                .users
+                .iter()
+                .filter(|u| {
+                    let name = state.deployment == MockDeployment::Server && u.name == text;
+                    u.display_name.contains(&text) || u.email.contains(&text) || name
+                })
+                .map(|u| state.user_json(u))
+                .collect();
+            Response::ok(json!(users))
+        }
+        ("POST", ["rest", "api", "2", "issue"]) => match state.create(&body, api_version) {
+            Ok(issue) => Response::created(json!({
+                "id": issue.id,
+                "key": issue.key,
+                "self": format!("{}/rest/api/2/issue/{}", base_url, issue.id),
+            })),
+            Err(errors) => Response::field_errors(400, Value::Object(errors)),
+        },
+        ("POST", ["rest", "api", "2", "issue", "bulk"]) => {
+            bulk_create(&mut state, base_url, &body, api_version)
+        }
+        ("GET", ["rest", "api", "2", "issue", key]) => match state.issue_mut(key).cloned() {
+            Some(issue) => Response::ok(state.issue_json(&issue, base_url)),
+            None => Response::error(
+                404,
+                "Issue does not exist or you do not have permission to see it.",
+            ),
+        },
+        ("GET", ["rest", "api", "2", "issue", key, "transitions"]) => {
+            if state.issue_mut(key).is_none() {
+                return Response::error(
+                    404,
+                    "Issue does not exist or you do not have permission to see it.",
+                );
+            }
+            let transitions: Vec<Value> = state
+                .transitions
+                .iter()
+                .map(|t| json!({ "id": t.id, "name": t.name, "to": { "name": t.to_status } }))
+                .collect();
+            Response::ok(json!({ "transitions": transitions }))
+        }
+        ("POST", ["rest", "api", "2", "issue", key, "transitions"]) => {
+            let id = body["transition"]["id"].as_str().unwrap_or_default();
+            let to_status = match state.transitions.iter().find(|t| t.id == id) {
+                Some(t) => t.to_status.clone(),
+                None => {
+                    return Response::field_errors(
+                        400,
+                        json!({ "transition": format!("Transition id '{}' is not valid for this issue.", id) }),
+                    )
+                }
+            };
+            match state.issue_mut(key) {
+                Some(issue) => {
+                    issue.status = to_status;
+                    Response::no_content()
+                }
+                None => Response::error(
+                    404,
+                    "Issue does not exist or you do not have permission to see it.",
+                ),
+            }
+        }
+        ("POST", ["rest", "api", "2", "issueLink"]) => match state.link(&body) {
+            Ok(()) => Response::created(Value::Null),
+            Err(message) => Response::error(404, &message),
+        },
+        ("POST", ["rest", "api", "2", "search"]) => search(
+            &state,
+            base_url,
+            body["jql"].as_str().unwrap_or_default(),
+            body["startAt"].as_u64().unwrap_or(0),
+            body["maxResults"].as_u64().unwrap_or(50),
+        ),
+        ("POST", ["rest", "api", "2", "search", "jql"])
+            if state.deployment == MockDeployment::Cloud =>
+        {
+            // the enhanced search of Cloud pages with tokens, here the `startAt` of the next page
+            let start_at = body["nextPageToken"]
+                .as_str()
+                .and_then(|t| t.parse().ok())
+                .unwrap_or(0);
+            let max_results = body["maxResults"].as_u64().unwrap_or(50);
+            let page = search(
+                &state,
+                base_url,
+                body["jql"].as_str().unwrap_or_default(),
+                start_at,
+                max_results,
+            );
+            if page.status != 200 {
+                return page;
+            }
+            let next = start_at + max_results;
+            let is_last = next >= page.body["total"].as_u64().unwrap_or_default();
+            let mut body = json!({ "issues": page.body["issues"], "isLast": is_last });
+            if !is_last {
+                body["nextPageToken"] = json!(next.to_string());
+            }
+            Response::ok(body)
+        }
+        ("GET", ["rest", "api", "2", "search"]) => search(
+            &state,
+            base_url,
+            &query("jql").unwrap_or_default(),
+            query("startAt").and_then(|s| s.parse().ok()).unwrap_or(0),
+            query("maxResults")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(50),
+        ),
+        _ => Response::error(404, &format!("null for uri: {}{}", base_url, request.url)),
+    }
+}
+
+fn login(state: &State, body: &Value) -> Response {
+    let username = body["username"].as_str().unwrap_or_default();
+    let password = body["password"].as_str().unwrap_or_default();
+    let valid = match &state.credentials {
+        Some((user, key)) => user == username && key == password,
+        None => !username.is_empty(),
+    };
+
+    match valid {
+        true => Response::ok(json!({
+            "session": { "name": "JSESSIONID", "value": "mock-session" },
+            "loginInfo": { "failedLoginCount": 0, "loginCount": 1 },
+        })),
+        false => Response::error(401, "Login failed"),
+    }
+}
+
+fn authenticated(state: &State, request: &Request) -> bool {
+    if request
+        .cookie
+        .is_some_and(|c| c.split(';').any(|c| c.trim() == SESSION_COOKIE))
+    {
+        return true;
+    }
+
+    let authorization = request.authorization.unwrap_or_default();
+    match (&state.credentials, authorization.split_once(' ')) {
+        (None, Some((_, credentials))) => !credentials.is_empty(),
+        (Some((user, key)), Some(("Basic", credentials))) => {
+            credentials == base64(format!("{}:{}", user, key).as_bytes())
+        }
+        (Some((_, key)), Some(("Bearer", token))) => token == key,
+        _ => false,
+    }
+}
+
+fn bulk_create(
+    state: &mut State,
+    base_url: &str,
+    body: &Value,
+    api_version: ApiVersion,
+) -> Response {
+    let updates = body["issueUpdates"].as_array().cloned().unwrap_or_default();
+    if updates.len() > BULK_CREATE_LIMIT {
+        return Response::error(
+            400,
+            &format!(
+                "Bulk create request contains {} issues, which is more than the limit of {}.",
+                updates.len(),
+                BULK_CREATE_LIMIT
+            ),
+        );
+    }
+
+    let mut issues = Vec::new();
+    let mut errors = Vec::new();
+    for (i, update) in updates.iter().enumerate() {
+        match state.create(update, api_version) {
+            Ok(issue) => issues.push(json!({
+                "id": issue.id,
+                "key": issue.key,
+                "self": format!("{}/rest/api/2/issue/{}", base_url, issue.id),
+            })),
+            Err(field_errors) => errors.push(json!({
+                "status": 400,
+                "elementErrors": { "errorMessages": [], "errors": field_errors },
+                "failedElementNumber": i,
+            })),
+        }
+    }
+
+    // JIRA only responds with `201 Created` when at least one issue was created
+    let status = match issues.is_empty() && !errors.is_empty() {
+        true => 400,
+        false => 201,
+    };
+    Response {
+        status,
+        body: json!({ "issues": issues, "errors": errors }),
+    }
+}
+
+// Supports `field = value` clauses on `project`, `key`, `status`, `issuetype` and `labels`,
+// joined with `AND`; `ORDER BY` is ignored.
+fn search(state: &State, base_url: &str, jql: &str, start_at: u64, max_results: u64) -> Response {
+    let jql = match jql.to_lowercase().find("order by") {
+        Some(i) => &jql[..i],
+        None => jql,
+    };
+
+    let mut clauses = Vec::new();
+    for clause in split_and(jql).into_iter().filter(|c| !c.trim().is_empty()) {
+        match clause.split_once('=') {
+            Some((field, value)) => clauses.push((
+                field.trim().to_lowercase(),
+                value
+                    .trim()
+                    .trim_matches('"')
+                    .trim_matches('\'')
+                    .to_string(),
+            )),
+            None => {
+                return Response::error(
+                    400,
+                    &format!("Error in the JQL Query: '{}'", clause.trim()),
+                )
+            }
+        }
+    }
+
+    let matching: Vec<_> = state
+        .issues
+        .iter()
+        .filter(|issue| {
+            clauses.iter().all(|(field, value)| match field.as_str() {
+                "project" => &issue.project_key == value,
+                "key" | "issuekey" => &issue.key == value,
+                "status" => &issue.status == value,
+                "issuetype" | "type" => &issue.issue_type == value,
+                "labels" => issue.fields["labels"]
+                    .as_array()
+                    .is_some_and(|labels| labels.iter().any(|l| l == value.as_str())),
+                _ => false,
+            })
+        })
+        .collect();
+
+    let issues: Vec<Value> = matching
+        .iter()
+        .skip(start_at as usize)
+        .take(max_results as usize)
+        .map(|issue| state.issue_json(issue, base_url))
+        .collect();
+
+    Response::ok(json!({
+        "startAt": start_at,
+        "maxResults": max_results,
+        "total": matching.len(),
+        "issues": issues,
+    }))
+}
+
+// Splits a query at each `AND`, in any case.
+fn split_and(jql: &str) -> Vec<&str> {
+    let lower = jql.to_lowercase();
+    let mut clauses = Vec::new();
+    let mut start = 0;
+    while let Some(i) = lower[start..].find(" and ") {
+        clauses.push(&jql[start..start + i]);
+        start += i + " and ".len();
+    }
+    clauses.push(&jql[start..]);
+    clauses
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
+}